@@ -21,10 +21,11 @@ RSpec.describe BasicCli::Commands::Benchmark do
         command = described_class.new(iterations)
         output = capture_stdout { command.execute }
 
-        expect(output).to include('Iterations:')
-        expect(output).to include('Total time:')
-        expect(output).to include('Avg time/op:')
-        expect(output).to include('Ops/second:')
+        expect(output).to include('│ Benchmark')
+        expect(output).to include('Iterations')
+        expect(output).to include('Total time')
+        expect(output).to include('Avg time/op')
+        expect(output).to include('Ops/second')
       end
 
       it 'displays total benchmark time' do
@@ -91,6 +92,29 @@ RSpec.describe BasicCli::Commands::Benchmark do
       end
     end
 
+    context 'with Markdown output' do
+      it 'outputs a Markdown table' do
+        command = described_class.new(iterations, output: 'markdown')
+        output = capture_stdout { command.execute }
+
+        lines = output.split("\n")
+        expect(lines[0]).to eq('| Benchmark | Iterations | Total time | Avg time/op | Ops/second |')
+        expect(lines[1]).to eq('| --- | ---: | ---: | ---: | ---: |')
+        expect(lines[2]).to start_with("| String Manipulation | #{iterations} |")
+      end
+    end
+
+    context 'with HTML output' do
+      it 'outputs an HTML table' do
+        command = described_class.new(iterations, output: 'html')
+        output = capture_stdout { command.execute }
+
+        expect(output).to start_with("<table>\n  <thead>")
+        expect(output).to include('<td style="text-align: right">')
+        expect(output.strip).to end_with('</table>')
+      end
+    end
+
     context 'with verbose option' do
       it 'shows running message' do
         command = described_class.new(iterations, verbose: true)
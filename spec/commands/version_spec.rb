@@ -8,18 +8,18 @@ RSpec.describe BasicCli::Commands::Version do
         command = described_class.new
 
         expect { command.execute }.to output(/BasicCli/).to_stdout
-        expect { command.execute }.to output(/Version:.*1\.0\.0/).to_stdout
-        expect { command.execute }.to output(/Build Date:.*2025-01-15/).to_stdout
-        expect { command.execute }.to output(/Ruby Version:/).to_stdout
+        expect { command.execute }.to output(/Version.*1\.0\.0/).to_stdout
+        expect { command.execute }.to output(/Build Date.*2025-01-15/).to_stdout
+        expect { command.execute }.to output(/Ruby Version/).to_stdout
       end
 
-      it 'displays a formatted box' do
+      it 'displays a table' do
         command = described_class.new
         output = capture_stdout { command.execute }
 
-        expect(output).to include('╔')
-        expect(output).to include('╚')
-        expect(output).to include('║')
+        expect(output).to include('┌')
+        expect(output).to include('└')
+        expect(output).to include('│ Version')
       end
     end
 
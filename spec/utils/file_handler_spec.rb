@@ -53,6 +53,17 @@ RSpec.describe BasicCli::Utils::FileHandler do
         expect(read_data.dig(:config, :host)).to eq('localhost')
         expect(read_data.dig(:config, :port)).to eq(3000)
       end
+
+      # The Rust binary's Psych-compatible emitter is tested against the
+      # same fixtures, so both implementations write identical YAML.
+      Dir[File.expand_path('../fixtures/yaml/*.json', __dir__)].sort.each do |json_fixture|
+        it "matches the #{File.basename(json_fixture, '.json')} golden file" do
+          data = JSON.parse(File.read(json_fixture))
+          described_class.write(filepath, data, format: :yaml)
+
+          expect(File.read(filepath)).to eq(File.read(json_fixture.sub(/\.json\z/, '.yml')))
+        end
+      end
     end
 
     context 'CSV format' do
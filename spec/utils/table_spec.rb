@@ -0,0 +1,82 @@
+require 'spec_helper'
+require_relative '../../src/utils/table'
+
+RSpec.describe BasicCli::Utils::Table do
+  def width(text)
+    described_class.display_width(text)
+  end
+
+  describe '#render_console' do
+    it 'aligns columns' do
+      table = described_class.new(%w[Name Count], alignments: { 1 => :right }, max_width: nil)
+      table.add_row(%w[alpha 1])
+      table.add_row(%w[beta 200])
+
+      lines = table.render_console.split("\n")
+      expect(lines[0]).to eq('┌───────┬───────┐')
+      expect(lines[1]).to eq('│ Name  │ Count │')
+      expect(lines[3]).to eq('│ alpha │     1 │')
+      expect(lines[4]).to eq('│ beta  │   200 │')
+      expect(lines.map { |l| width(l) }.uniq.size).to eq(1)
+    end
+
+    it 'measures wide characters' do
+      table = described_class.new(['Word'], max_width: nil)
+      table.add_row(['日本語'])
+      table.add_row(['abc'])
+
+      expect(table.render_console.split("\n").map { |l| width(l) }.uniq.size).to eq(1)
+    end
+
+    it 'shrinks the widest column to fit the terminal' do
+      table = described_class.new(%w[a b], max_width: 20)
+      table.add_row(['x' * 30, 'y'])
+
+      output = table.render_console
+      expect(output.split("\n").map { |l| width(l) }.max).to be <= 20
+      expect(output).to include('…')
+    end
+
+    it 'handles a tiny terminal with a title' do
+      table = described_class.new(['a'], title: 'Title', max_width: 3)
+      table.add_row(['value'])
+
+      expect(table.render_console).to include('va…')
+    end
+  end
+
+  describe '.truncate' do
+    it 'cuts text to a display width' do
+      expect(described_class.truncate('hello', 10)).to eq('hello')
+      expect(described_class.truncate('hello world', 6)).to eq('hello…')
+      expect(width(described_class.truncate('日本語日本語', 5))).to eq(5)
+    end
+  end
+
+  describe '#render_markdown' do
+    it 'marks right-aligned columns and escapes pipes' do
+      table = described_class.new(%w[Name Count], alignments: { 1 => :right })
+      table.add_row(['a|b', '1'])
+
+      expect(table.render_markdown).to eq("| Name | Count |\n| --- | ---: |\n| a\\|b | 1 |")
+    end
+  end
+
+  describe '#render_html' do
+    it 'escapes the title, headers and cells' do
+      table = described_class.new(['<h>'], title: 'T & T')
+      table.add_row(['a"b'])
+
+      html = table.render_html
+      expect(html).to include('<caption>T &amp; T</caption>')
+      expect(html).to include('<th>&lt;h&gt;</th>')
+      expect(html).to include('<td>a&quot;b</td>')
+    end
+  end
+
+  describe '#render' do
+    it 'rejects unknown formats' do
+      expect { described_class.new(['a']).render('xml') }.to raise_error(ArgumentError)
+    end
+  end
+end
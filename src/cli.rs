@@ -1,25 +1,99 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use clap::{ArgGroup, Parser, Subcommand};
+use std::time::Duration;
 
 mod commands {
     pub mod benchmark;
+    pub mod checksum;
+    pub mod convert;
+    pub mod count;
+    pub mod du;
+    pub mod encode;
+    pub mod find;
+    pub mod frontmatter;
     pub mod hello;
+    pub mod join;
+    pub mod profile;
+    pub mod search;
+    pub mod snapshot;
+    pub mod split;
+    pub mod stat;
+    pub mod textdiff;
+    pub mod tree;
     pub mod version;
 }
 
 mod utils {
+    pub mod byte_size;
+    pub mod content_type;
+    pub mod csv_dialect;
+    pub mod dir_tree;
+    pub mod encoding;
     pub mod file_handler;
+    pub mod front_matter;
+    pub mod glob;
+    pub mod hashing;
+    pub mod http_fetcher;
+    pub mod json_ref;
+    pub mod line_diff;
     pub mod logger;
+    pub mod marshal;
+    pub mod psych_yaml;
+    pub mod ruby_json;
+    pub mod table;
+    pub mod template;
+    pub mod walker;
+    pub mod xlsx;
+    pub mod zip_archive;
 }
 
-use commands::{benchmark::BenchmarkCommand, hello::HelloCommand, version::VersionCommand};
+use commands::{
+    benchmark::BenchmarkCommand,
+    checksum::ChecksumCommand,
+    convert::{ConvertCommand, CsvSettings},
+    count::CountCommand,
+    du::DuCommand,
+    encode::EncodeCommand,
+    find::FindCommand,
+    frontmatter::{FrontmatterAction, FrontmatterCommand},
+    hello::HelloCommand,
+    join::JoinCommand,
+    profile::ProfileCommand,
+    search::SearchCommand,
+    snapshot::{SnapshotAction, SnapshotCommand},
+    split::{SplitCommand, SplitMode},
+    stat::StatCommand,
+    textdiff::TextDiffCommand,
+    tree::TreeCommand,
+    version::VersionCommand,
+};
+use utils::csv_dialect;
+use utils::file_handler::{FileHandler, SearchOptions};
+use utils::http_fetcher::HttpFetcher;
+use utils::json_ref::RefResolver;
 use utils::logger::Logger;
+use utils::ruby_json;
+use utils::template::Template;
+use utils::walker::WalkOptions;
+use utils::xlsx::SheetOptions;
 
 #[derive(Parser)]
 #[command(name = "basiccli-rust")]
 #[command(author, version, about = "BasicCli - High Performance CLI (Rust Version)", long_about = None)]
 struct Cli {
+    /// Render the command's structured output through a template (inline, or @file)
+    #[arg(long, global = true, value_name = "TEMPLATE")]
+    template: Option<String>,
+
+    /// Hold an exclusive lock on this file while the command runs, so
+    /// invocations sharing it run one at a time
+    #[arg(long, global = true, value_name = "PATH")]
+    lock: Option<std::path::PathBuf>,
+
+    /// Give up if the --lock file is still held after this many seconds
+    #[arg(long, global = true, value_name = "SECONDS", requires = "lock")]
+    lock_timeout: Option<f64>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -53,7 +127,7 @@ enum Commands {
         #[arg(default_value_t = 1000)]
         iterations: usize,
 
-        /// Output format: console, json, or csv
+        /// Output format: console, json, csv, markdown, or html
         #[arg(short, long, default_value = "console")]
         output: String,
 
@@ -62,10 +136,10 @@ enum Commands {
         verbose: bool,
     },
 
-    /// Process a JSON file and demonstrate file I/O
+    /// Process a JSON (or Ruby .marshal) file and demonstrate file I/O
     Process {
-        /// File to process
-        file: PathBuf,
+        /// File or http(s) URL to process
+        file: String,
 
         /// Pretty print JSON output
         #[arg(short, long)]
@@ -74,11 +148,469 @@ enum Commands {
         /// Show processing statistics
         #[arg(short, long)]
         stats: bool,
+
+        /// Verify the input against this SHA-256 digest before processing
+        #[arg(long)]
+        sha256: Option<String>,
+
+        /// Inline $ref pointers (local JSON Pointers and relative files)
+        #[arg(long)]
+        resolve_refs: bool,
+    },
+
+    /// Profile the columns of a CSV file or JSON array of objects
+    Profile {
+        /// CSV or JSON file (or http(s) URL) to profile
+        file: String,
+
+        /// Output format: console, json, markdown, or html
+        #[arg(short, long, default_value = "console")]
+        output: String,
+
+        /// Number of most frequent values to report per column
+        #[arg(short, long, default_value_t = 5)]
+        top: usize,
+
+        /// Number of histogram bins for numeric columns
+        #[arg(short, long, default_value_t = 10)]
+        bins: usize,
+    },
+
+    /// Compare two text files line by line (exits 1 when they differ, 2 on errors)
+    #[command(name = "textdiff")]
+    TextDiff {
+        /// Original file, http(s) URL, or - for stdin
+        old: String,
+
+        /// Changed file, http(s) URL, or - for stdin
+        new: String,
+
+        /// Output format: unified, side-by-side, or json
+        #[arg(short, long, default_value = "unified")]
+        output: String,
+
+        /// Number of context lines around each change
+        #[arg(short = 'U', long, default_value_t = 3)]
+        context: usize,
+
+        /// Diff algorithm: myers or patience
+        #[arg(short, long, default_value = "myers")]
+        algorithm: String,
+
+        /// Colorize output: auto, always, or never
+        #[arg(long, default_value = "auto")]
+        color: String,
+    },
+
+    /// Search files for lines matching a regular expression (exits 2 if a path can't be read)
+    Search {
+        /// Regular expression to search for
+        pattern: String,
+
+        /// Files or directories to search (defaults to the current directory)
+        paths: Vec<std::path::PathBuf>,
+
+        /// Only search files matching this glob (repeatable)
+        #[arg(long)]
+        include: Vec<String>,
+
+        /// Skip files and directories matching this glob (repeatable)
+        #[arg(long)]
+        exclude: Vec<String>,
+
+        /// Number of context lines around each match
+        #[arg(short = 'C', long, default_value_t = 0)]
+        context: usize,
+
+        /// Only print the number of matching lines per file
+        #[arg(short, long)]
+        count: bool,
+
+        /// Match case-insensitively
+        #[arg(short, long)]
+        ignore_case: bool,
+
+        /// Output format: console or json
+        #[arg(short, long, default_value = "console")]
+        output: String,
+
+        /// Colorize output: auto, always, or never
+        #[arg(long, default_value = "auto")]
+        color: String,
+    },
+
+    /// Recursively list files and directories, filtered by name, type, size and age
+    Find {
+        /// Directories or files to walk (defaults to the current directory)
+        paths: Vec<std::path::PathBuf>,
+
+        /// Only list entries matching this glob (repeatable)
+        #[arg(long)]
+        include: Vec<String>,
+
+        /// Skip entries matching this glob, and anything below them (repeatable)
+        #[arg(long)]
+        exclude: Vec<String>,
+
+        /// Honour .gitignore files found along the way, and skip .git
+        #[arg(long)]
+        gitignore: bool,
+
+        /// Read gitignore-style rules from files with this name in each directory (repeatable)
+        #[arg(long = "ignore-file", value_name = "NAME")]
+        ignore_files: Vec<String>,
+
+        /// Entry type: f (file), d (directory), l (symlink) or o (other) (repeatable)
+        #[arg(short = 't', long = "type", value_name = "TYPE")]
+        types: Vec<String>,
+
+        /// Minimum file size, e.g. 10K or 1.5M
+        #[arg(long)]
+        min_size: Option<String>,
+
+        /// Maximum file size, e.g. 10K or 1.5M
+        #[arg(long)]
+        max_size: Option<String>,
+
+        /// Only entries modified after this: a duration ago (2h, 7d), a date or an RFC 3339 time
+        #[arg(long, value_name = "TIME")]
+        newer: Option<String>,
+
+        /// Only entries modified before this: a duration ago (2h, 7d), a date or an RFC 3339 time
+        #[arg(long, value_name = "TIME")]
+        older: Option<String>,
+
+        /// Don't list entries above this depth (1 = the paths' direct children)
+        #[arg(long)]
+        min_depth: Option<usize>,
+
+        /// Don't descend below this depth
+        #[arg(long)]
+        max_depth: Option<usize>,
+
+        /// Follow symbolic links to directories
+        #[arg(short = 'L', long)]
+        follow: bool,
+
+        /// Terminate paths with NUL instead of newline, for xargs -0
+        #[arg(short = '0', long)]
+        print0: bool,
+
+        /// Output format: console or json
+        #[arg(short, long, default_value = "console")]
+        output: String,
+    },
+
+    /// Show a directory hierarchy as a tree
+    Tree {
+        /// Directory to show (defaults to the current directory)
+        #[arg(default_value = ".")]
+        path: std::path::PathBuf,
+
+        /// Descend at most this many levels
+        #[arg(short = 'L', long)]
+        depth: Option<usize>,
+
+        /// Show file sizes and directory totals
+        #[arg(short, long)]
+        sizes: bool,
+
+        /// List directories before files
+        #[arg(long)]
+        dirs_first: bool,
+
+        /// Leave out entries matching this glob (repeatable)
+        #[arg(short = 'I', long)]
+        exclude: Vec<String>,
+
+        /// Honour .gitignore files found along the way, and skip .git
+        #[arg(long)]
+        gitignore: bool,
+
+        /// Output format: console or json
+        #[arg(short, long, default_value = "console")]
+        output: String,
+    },
+
+    /// Summarize disk usage per directory, largest first
+    Du {
+        /// Directory to measure (defaults to the current directory)
+        #[arg(default_value = ".")]
+        path: std::path::PathBuf,
+
+        /// Only list directories down to this depth (totals still include everything)
+        #[arg(short = 'd', long)]
+        max_depth: Option<usize>,
+
+        /// Number of directories to show (0 for all)
+        #[arg(short = 'n', long, default_value_t = 10)]
+        top: usize,
+
+        /// Leave out entries matching this glob (repeatable)
+        #[arg(long)]
+        exclude: Vec<String>,
+
+        /// Honour .gitignore files found along the way, and skip .git
+        #[arg(long)]
+        gitignore: bool,
+
+        /// Output format: console or json
+        #[arg(short, long, default_value = "console")]
+        output: String,
+    },
+
+    /// Show file details: size, mode, owner, inode, links, timestamps and content type
+    Stat {
+        /// Files, directories or symlinks to describe
+        #[arg(required = true)]
+        paths: Vec<std::path::PathBuf>,
+
+        /// Follow symlinks and describe their targets
+        #[arg(short = 'L', long)]
+        dereference: bool,
+
+        /// Output format: console or json
+        #[arg(short, long, default_value = "console")]
+        output: String,
+    },
+
+    /// Count lines, words, bytes and characters in one or more files
+    Count {
+        /// Files or http(s) URLs to count (defaults to stdin, also -)
+        inputs: Vec<String>,
+
+        /// Output format: console, json, markdown, or html
+        #[arg(short, long, default_value = "console")]
+        output: String,
+    },
+
+    /// Encode a file or stdin as base64, base64url, base32, hex, or url
+    Encode {
+        /// Encoding: base64, base64url, base32, hex, or url
+        encoding: String,
+
+        /// File or http(s) URL to encode (defaults to stdin, also -)
+        input: Option<String>,
+
+        /// Wrap encoded lines after N characters (0 disables wrapping)
+        #[arg(short, long, default_value_t = 0)]
+        wrap: usize,
+    },
+
+    /// Decode base64, base64url, base32, hex, or url encoded input
+    Decode {
+        /// Encoding: base64, base64url, base32, hex, or url
+        encoding: String,
+
+        /// File or http(s) URL to decode (defaults to stdin, also -)
+        input: Option<String>,
+    },
+
+    /// Split a file into chunks by size, line count, or CSV record count
+    #[command(group(ArgGroup::new("limit").required(true).args(["bytes", "lines", "records"])))]
+    Split {
+        /// File to split
+        file: std::path::PathBuf,
+
+        /// Maximum chunk size, e.g. 500K, 10M, 1G
+        #[arg(short, long)]
+        bytes: Option<String>,
+
+        /// Number of lines per chunk
+        #[arg(short, long)]
+        lines: Option<u64>,
+
+        /// Number of CSV records per chunk (the header is repeated in each)
+        #[arg(short, long)]
+        records: Option<u64>,
+
+        /// Directory for the chunks and manifest (defaults to the file's directory)
+        #[arg(short = 'd', long)]
+        out_dir: Option<std::path::PathBuf>,
+    },
+
+    /// Reassemble a split file from its manifest, verifying every chunk
+    Join {
+        /// Manifest written by split (<file>.manifest.json)
+        manifest: std::path::PathBuf,
+
+        /// Output file (defaults to the original name next to the manifest)
+        output: Option<std::path::PathBuf>,
+
+        /// Overwrite the output file if it exists
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Convert tabular data between CSV, TSV, JSON and Excel (.xlsx)
+    Convert {
+        /// Input file or URL (.csv, .tsv, .json, .xlsx or Ruby .marshal)
+        input: String,
+
+        /// Output file; the format follows the extension
+        output: std::path::PathBuf,
+
+        /// Sheet to read from an .xlsx input (name or 1-based number)
+        #[arg(long)]
+        sheet: Option<String>,
+
+        /// Row holding the column names in an .xlsx input
+        #[arg(long, default_value = "1")]
+        header_row: usize,
+
+        /// Input field delimiter: a character, tab, comma, semicolon, pipe or
+        /// space (sniffed from the file by default)
+        #[arg(short, long, value_parser = csv_dialect::parse_delimiter)]
+        delimiter: Option<u8>,
+
+        /// Treat the first CSV row as data and name columns column_1, column_2, ...
+        #[arg(long)]
+        no_header: bool,
+
+        /// Skip CSV lines starting with this character
+        #[arg(long, value_parser = csv_dialect::parse_char)]
+        comment: Option<u8>,
+
+        /// Strip whitespace around CSV fields
+        #[arg(long)]
+        trim: bool,
+
+        /// Allow CSV rows with differing numbers of fields
+        #[arg(long)]
+        flexible: bool,
+
+        /// Output field delimiter (defaults to tab for .tsv, comma otherwise)
+        #[arg(long, value_parser = csv_dialect::parse_delimiter)]
+        output_delimiter: Option<u8>,
+
+        /// Output quoting: necessary, always, non-numeric or never
+        #[arg(long, value_parser = csv_dialect::parse_quote_style)]
+        quote_style: Option<csv::QuoteStyle>,
+
+        /// Verify the input against this SHA-256 digest before converting
+        #[arg(long)]
+        sha256: Option<String>,
+    },
+
+    /// Print or verify file checksums (sha256sum-compatible)
+    Checksum {
+        /// Files or directories to hash; stdin when omitted or "-"
+        paths: Vec<String>,
+
+        /// md5, sha1, sha256, sha512, sha3-256, blake3, xxh64, xxh3 or crc32
+        #[arg(short, long, default_value = "sha256")]
+        algorithm: String,
+
+        /// Write BSD-style lines: ALGORITHM (file) = digest
+        #[arg(long)]
+        tag: bool,
+
+        /// Verify the files listed in this manifest instead of hashing
+        #[arg(short, long, value_name = "MANIFEST")]
+        check: Option<std::path::PathBuf>,
+
+        /// With --check, only print files that fail
+        #[arg(short, long)]
+        quiet: bool,
+    },
+
+    /// Record a directory's files and hashes, or verify it against a snapshot
+    Snapshot {
+        #[command(subcommand)]
+        action: SnapshotCommands,
+    },
+
+    /// Query or edit YAML front matter in Markdown files
+    Frontmatter {
+        #[command(subcommand)]
+        action: FrontmatterCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum SnapshotCommands {
+    /// Record every file's path, size, mode, mtime and hash plus a Merkle root
+    Create {
+        /// Directory to snapshot
+        dir: std::path::PathBuf,
+
+        /// Snapshot file to write (prints to stdout when omitted)
+        file: Option<std::path::PathBuf>,
+
+        /// Glob of paths to leave out (repeatable)
+        #[arg(short = 'x', long)]
+        exclude: Vec<String>,
+    },
+
+    /// Report files added, removed, modified or with changed permissions
+    Verify {
+        /// Directory to check
+        dir: std::path::PathBuf,
+
+        /// Snapshot written by `snapshot create`
+        snapshot: std::path::PathBuf,
+
+        /// Output format (console, json)
+        #[arg(short, long, default_value = "console")]
+        output: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum FrontmatterCommands {
+    /// Show the front matter of each file
+    List {
+        /// Files or directories (directories are searched for .md files)
+        paths: Vec<std::path::PathBuf>,
+
+        /// Output format (console, json)
+        #[arg(short, long, default_value = "console")]
+        output: String,
+    },
+
+    /// Print one key (dotted paths like author.name work) from each file
+    Get {
+        /// Key to look up
+        key: String,
+
+        /// Files or directories (directories are searched for .md files)
+        paths: Vec<std::path::PathBuf>,
+
+        /// Output format (console, json)
+        #[arg(short, long, default_value = "console")]
+        output: String,
+    },
+
+    /// Set or remove keys in every file; the body is left untouched
+    Set {
+        /// Files or directories (directories are searched for .md files)
+        paths: Vec<std::path::PathBuf>,
+
+        /// KEY=VALUE to set; the value is read as YAML (repeatable)
+        #[arg(short, long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+
+        /// Key to remove (repeatable)
+        #[arg(long, value_name = "KEY")]
+        unset: Vec<String>,
+
+        /// Show which files would change without writing them
+        #[arg(short = 'n', long)]
+        dry_run: bool,
     },
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let template = cli.template.as_deref().map(Template::load).transpose()?;
+    let _lock = match (&cli.lock, cli.lock_timeout) {
+        (Some(path), Some(seconds)) => Some(FileHandler::lock_exclusive_timeout(
+            path,
+            Duration::try_from_secs_f64(seconds)?,
+        )?),
+        (Some(path), None) => Some(FileHandler::lock_exclusive(path)?),
+        (None, _) => None,
+    };
 
     match cli.command {
         Commands::Hello {
@@ -86,12 +618,16 @@ fn main() -> Result<()> {
             uppercase,
             repeat,
         } => {
+            reject_template(&template, "hello")?;
             let command = HelloCommand::new(name, uppercase, repeat);
             command.execute()?;
         }
         Commands::Version { json } => {
             let command = VersionCommand::new(json);
-            command.execute()?;
+            match &template {
+                Some(template) => print_template(template, &command.build_version_info())?,
+                None => command.execute()?,
+            }
         }
         Commands::Benchmark {
             iterations,
@@ -99,48 +635,501 @@ fn main() -> Result<()> {
             verbose,
         } => {
             let command = BenchmarkCommand::new(iterations, output, verbose);
-            command.execute()?;
+            match &template {
+                Some(template) => print_template(template, &command.report())?,
+                None => command.execute()?,
+            }
         }
         Commands::Process {
             file,
             pretty,
             stats,
+            sha256,
+            resolve_refs,
         } => {
-            process_file(file, pretty, stats)?;
+            process_file(file, pretty, stats, sha256, resolve_refs, template.as_ref())?;
         }
+        Commands::Profile {
+            file,
+            output,
+            top,
+            bins,
+        } => {
+            let file = HttpFetcher::new().resolve(&file)?;
+            let command = ProfileCommand::new(file, output, top, bins)?;
+            match &template {
+                Some(template) => print_template(template, &command.report()?)?,
+                None => command.execute()?,
+            }
+        }
+        Commands::TextDiff {
+            old,
+            new,
+            output,
+            context,
+            algorithm,
+            color,
+        } => {
+            // Like diff(1): 1 means the inputs differ, 2 means trouble.
+            let differs = text_diff(
+                old,
+                new,
+                output,
+                context,
+                &algorithm,
+                &color,
+                template.as_ref(),
+            )
+            .unwrap_or_else(|e| {
+                eprintln!("Error: {:?}", e);
+                std::process::exit(2);
+            });
+            if differs {
+                std::process::exit(1);
+            }
+        }
+        Commands::Search {
+            pattern,
+            paths,
+            include,
+            exclude,
+            context,
+            count,
+            ignore_case,
+            output,
+            color,
+        } => {
+            let options = SearchOptions {
+                include: utils::glob::compile_all(&include)?,
+                exclude: utils::glob::compile_all(&exclude)?,
+                context,
+            };
+            let command =
+                SearchCommand::new(&pattern, ignore_case, paths, options, count, output, &color)?;
+            let failed = match &template {
+                Some(template) => {
+                    let report = command.report()?;
+                    print_template(template, &report)?;
+                    !report["errors"].as_array().is_some_and(|e| e.is_empty())
+                }
+                None => command.execute()?,
+            };
+            // Like grep, unreadable paths exit 2.
+            if failed {
+                std::process::exit(2);
+            }
+        }
+        Commands::Find {
+            paths,
+            include,
+            exclude,
+            gitignore,
+            ignore_files,
+            types,
+            min_size,
+            max_size,
+            newer,
+            older,
+            min_depth,
+            max_depth,
+            follow,
+            print0,
+            output,
+        } => {
+            let options = WalkOptions {
+                include: utils::glob::compile_all(&include)?,
+                types: types.iter().map(|t| t.parse()).collect::<Result<_>>()?,
+                min_size: min_size
+                    .as_deref()
+                    .map(utils::byte_size::parse)
+                    .transpose()?,
+                max_size: max_size
+                    .as_deref()
+                    .map(utils::byte_size::parse)
+                    .transpose()?,
+                newer_than: newer
+                    .as_deref()
+                    .map(utils::walker::parse_time)
+                    .transpose()?,
+                older_than: older
+                    .as_deref()
+                    .map(utils::walker::parse_time)
+                    .transpose()?,
+                min_depth,
+                max_depth,
+                follow_links: follow,
+                ..ignore_options(exclude, gitignore, ignore_files)?
+            };
+            let command = FindCommand::new(paths, options, print0, output)?;
+            let failed = match &template {
+                Some(template) => {
+                    let report = command.report()?;
+                    print_template(template, &report)?;
+                    !report["errors"].as_array().is_some_and(|e| e.is_empty())
+                }
+                None => command.execute()?,
+            };
+            if failed {
+                std::process::exit(1);
+            }
+        }
+        Commands::Tree {
+            path,
+            depth,
+            sizes,
+            dirs_first,
+            exclude,
+            gitignore,
+            output,
+        } => {
+            let options = ignore_options(exclude, gitignore, Vec::new())?;
+            let command = TreeCommand::new(path, options, depth, sizes, dirs_first, output)?;
+            let failed = match &template {
+                Some(template) => {
+                    let report = command.report()?;
+                    print_template(template, &report)?;
+                    !report["errors"].as_array().is_some_and(|e| e.is_empty())
+                }
+                None => command.execute()?,
+            };
+            if failed {
+                std::process::exit(1);
+            }
+        }
+        Commands::Du {
+            path,
+            max_depth,
+            top,
+            exclude,
+            gitignore,
+            output,
+        } => {
+            let options = ignore_options(exclude, gitignore, Vec::new())?;
+            let command = DuCommand::new(path, options, max_depth, top, output)?;
+            let failed = match &template {
+                Some(template) => {
+                    let report = command.report()?;
+                    print_template(template, &report)?;
+                    !report["errors"].as_array().is_some_and(|e| e.is_empty())
+                }
+                None => command.execute()?,
+            };
+            if failed {
+                std::process::exit(1);
+            }
+        }
+        Commands::Stat {
+            paths,
+            dereference,
+            output,
+        } => {
+            let command = StatCommand::new(paths, dereference, output)?;
+            let failed = match &template {
+                Some(template) => {
+                    let report = command.report()?;
+                    print_template(template, &report)?;
+                    !report["errors"].as_array().is_some_and(|e| e.is_empty())
+                }
+                None => command.execute()?,
+            };
+            if failed {
+                std::process::exit(1);
+            }
+        }
+        Commands::Count { inputs, output } => {
+            let command = CountCommand::new(inputs, output);
+            match &template {
+                Some(template) => print_template(template, &command.report()?)?,
+                None => command.execute()?,
+            }
+        }
+        Commands::Encode {
+            encoding,
+            input,
+            wrap,
+        } => {
+            reject_template(&template, "encode")?;
+            let command = EncodeCommand::new(encoding.parse()?, input, false, wrap);
+            command.execute()?;
+        }
+        Commands::Decode { encoding, input } => {
+            reject_template(&template, "decode")?;
+            let command = EncodeCommand::new(encoding.parse()?, input, true, 0);
+            command.execute()?;
+        }
+        Commands::Split {
+            file,
+            bytes,
+            lines,
+            records,
+            out_dir,
+        } => {
+            let (mode, limit) = match (bytes, lines, records) {
+                (Some(bytes), _, _) => (SplitMode::Bytes, utils::byte_size::parse(&bytes)?),
+                (_, Some(lines), _) => (SplitMode::Lines, lines),
+                (_, _, records) => (SplitMode::Records, records.unwrap_or_default()),
+            };
+            let command = SplitCommand::new(file, mode, limit, out_dir);
+            match &template {
+                Some(template) => {
+                    let manifest: serde_json::Value = FileHandler::read_json(command.split()?)?;
+                    print_template(template, &manifest)?;
+                }
+                None => command.execute()?,
+            }
+        }
+        Commands::Join {
+            manifest,
+            output,
+            force,
+        } => {
+            reject_template(&template, "join")?;
+            let command = JoinCommand::new(manifest, output, force);
+            command.execute()?;
+        }
+        Commands::Convert {
+            input,
+            output,
+            sheet,
+            header_row,
+            delimiter,
+            no_header,
+            comment,
+            trim,
+            flexible,
+            output_delimiter,
+            quote_style,
+            sha256,
+        } => {
+            reject_template(&template, "convert")?;
+            let csv = CsvSettings {
+                delimiter,
+                comment,
+                no_header,
+                trim,
+                flexible,
+                output_delimiter,
+                quote_style,
+            };
+            let command = ConvertCommand::new(
+                input,
+                output,
+                SheetOptions { sheet, header_row },
+                csv,
+                sha256,
+            );
+            command.execute()?;
+        }
+        Commands::Checksum {
+            paths,
+            algorithm,
+            tag,
+            check,
+            quiet,
+        } => {
+            let command = ChecksumCommand::new(paths, algorithm.parse()?, tag, check, quiet);
+            let failed = match &template {
+                Some(template) => {
+                    let report = command.report()?;
+                    print_template(template, &report)?;
+                    ChecksumCommand::report_failed(&report)
+                }
+                None => command.execute()?,
+            };
+            if failed {
+                std::process::exit(1);
+            }
+        }
+        Commands::Snapshot { action } => {
+            let command = match action {
+                SnapshotCommands::Create { dir, file, exclude } => SnapshotCommand::new(
+                    dir,
+                    SnapshotAction::Create {
+                        output: file,
+                        exclude,
+                    },
+                ),
+                SnapshotCommands::Verify {
+                    dir,
+                    snapshot,
+                    output,
+                } => SnapshotCommand::new(
+                    dir,
+                    SnapshotAction::Verify {
+                        snapshot,
+                        output_format: output,
+                    },
+                ),
+            };
+            let drifted = match &template {
+                Some(template) => {
+                    let report = command.report()?;
+                    print_template(template, &report)?;
+                    report["matches"] == false
+                }
+                None => command.execute()?,
+            };
+            if drifted {
+                std::process::exit(1);
+            }
+        }
+        Commands::Frontmatter { action } => {
+            let command = match action {
+                FrontmatterCommands::List { paths, output } => {
+                    FrontmatterCommand::new(FrontmatterAction::List, paths, output)?
+                }
+                FrontmatterCommands::Get { key, paths, output } => {
+                    FrontmatterCommand::new(FrontmatterAction::Get(key), paths, output)?
+                }
+                FrontmatterCommands::Set {
+                    paths,
+                    set,
+                    unset,
+                    dry_run,
+                } => FrontmatterCommand::new(
+                    FrontmatterAction::set(&set, unset, dry_run)?,
+                    paths,
+                    "console".to_string(),
+                )?,
+            };
+            match &template {
+                Some(template) => print_template(template, &command.report()?)?,
+                None => command.execute()?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Walk options for the shared `--exclude`, `--gitignore` and
+/// `--ignore-file` flags. `--gitignore` also skips `.git` itself.
+fn ignore_options(
+    mut exclude: Vec<String>,
+    gitignore: bool,
+    mut ignore_files: Vec<String>,
+) -> Result<WalkOptions> {
+    if gitignore {
+        ignore_files.insert(0, ".gitignore".to_string());
+        exclude.push(".git".to_string());
+    }
+    Ok(WalkOptions {
+        exclude: utils::glob::compile_all(&exclude)?,
+        ignore_files,
+        ..Default::default()
+    })
+}
+
+/// Prints `data` through the `--template`, ending with a newline.
+fn print_template(template: &Template, data: &serde_json::Value) -> Result<()> {
+    let output = template.render(data)?;
+    if output.ends_with('\n') {
+        print!("{}", output);
+    } else {
+        println!("{}", output);
     }
+    Ok(())
+}
 
+fn reject_template(template: &Option<Template>, command: &str) -> Result<()> {
+    if template.is_some() {
+        anyhow::bail!("--template is not supported by the {} command", command);
+    }
     Ok(())
 }
 
-fn process_file(file: PathBuf, pretty: bool, stats: bool) -> Result<()> {
+/// Runs `textdiff` and returns whether the inputs differ.
+fn text_diff(
+    old: String,
+    new: String,
+    output: String,
+    context: usize,
+    algorithm: &str,
+    color: &str,
+    template: Option<&Template>,
+) -> Result<bool> {
+    let command = TextDiffCommand::new(old, new, output, context, algorithm.parse()?, color)?;
+    match template {
+        Some(template) => {
+            let report = command.report()?;
+            print_template(template, &report)?;
+            Ok(report["identical"] == false)
+        }
+        None => command.execute(),
+    }
+}
+
+fn process_file(
+    input: String,
+    pretty: bool,
+    stats: bool,
+    sha256: Option<String>,
+    resolve_refs: bool,
+    template: Option<&Template>,
+) -> Result<()> {
     let logger = Logger::new(if stats {
         utils::logger::LogLevel::Debug
     } else {
         utils::logger::LogLevel::Info
     });
 
-    logger.info(&format!("Processing file: {}", file.display()));
+    logger.info(&format!("Processing file: {}", input));
+
+    let file = HttpFetcher::new()
+        .resolve(&input)
+        .inspect_err(|e| logger.error(&format!("Fetch failed: {}", e)))?;
 
     if !file.exists() {
         logger.error(&format!("File not found: {}", file.display()));
         std::process::exit(1);
     }
 
-    let content = std::fs::read_to_string(&file)?;
-    let data: serde_json::Value = serde_json::from_str(&content).map_err(|e| {
-        logger.error(&format!("Invalid JSON: {}", e));
-        e
-    })?;
+    if let Some(expected) = sha256 {
+        HttpFetcher::verify_sha256(&file, &expected)
+            .inspect_err(|e| logger.error(&e.to_string()))?;
+        logger.debug("SHA-256 checksum verified");
+    }
+
+    let is_marshal = file
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("marshal"));
+
+    let data: serde_json::Value = if is_marshal {
+        FileHandler::read_marshal(&file).inspect_err(|e| logger.error(&e.to_string()))?
+    } else {
+        let content = std::fs::read_to_string(&file)?;
+        serde_json::from_str(&content).map_err(|e| {
+            logger.error(&format!("Invalid JSON: {}", e));
+            e
+        })?
+    };
+
+    let data = if resolve_refs {
+        let mut resolver = RefResolver::new();
+        let resolved = resolver
+            .resolve(&data, &file)
+            .inspect_err(|e| logger.error(&e.to_string()))?;
+        logger.debug(&format!("Resolved {} $ref pointers", resolver.count()));
+        resolved
+    } else {
+        data
+    };
 
     if let Some(obj) = data.as_object() {
-        logger.info(&format!("Successfully parsed JSON with {} keys", obj.len()));
+        let format = if is_marshal { "Marshal data" } else { "JSON" };
+        logger.info(&format!(
+            "Successfully parsed {} with {} keys",
+            format,
+            obj.len()
+        ));
     }
 
-    if pretty {
-        println!("{}", serde_json::to_string_pretty(&data)?);
+    if let Some(template) = template {
+        print_template(template, &data)?;
+    } else if pretty {
+        println!("{}", ruby_json::pretty_generate(&data)?);
     } else {
-        println!("{}", serde_json::to_string(&data)?);
+        println!("{}", ruby_json::generate(&data)?);
     }
 
     if stats {
@@ -2,10 +2,13 @@ require 'benchmark'
 require 'json'
 require 'csv'
 require 'tempfile'
+require_relative '../utils/table'
 
 module BasicCli
   module Commands
     class Benchmark
+      TABLE_HEADERS = ['Benchmark', 'Iterations', 'Total time', 'Avg time/op', 'Ops/second'].freeze
+
       def initialize(iterations, options = {})
         @iterations = iterations
         @output_format = options[:output] || 'console'
@@ -22,6 +25,8 @@ module BasicCli
           output_json(results)
         when 'csv'
           output_csv(results)
+        when 'markdown', 'html'
+          output_table(results, @output_format)
         else
           output_console(results)
         end
@@ -168,21 +173,8 @@ module BasicCli
       end
 
       def output_console(results)
-        puts "\n#{'=' * 60}"
-        puts "#{' ' * 20}BENCHMARK RESULTS"
-        puts '=' * 60
-
-        results.each_value do |result|
-          puts "\n#{result[:name]}:"
-          puts "  Iterations:     #{result[:iterations]}"
-          puts "  Total time:     #{format_time(result[:total_time])}"
-          puts "  Avg time/op:    #{format_time(result[:avg_time])}"
-          puts "  Ops/second:     #{result[:ops_per_sec].round(2)}"
-        end
-
-        puts "\n#{'=' * 60}"
+        puts "\n#{results_table(results, title: 'BENCHMARK RESULTS').render_console}"
         puts "Total benchmark time: #{format_time(results.values.sum { |r| r[:total_time] })}"
-        puts '=' * 60
       end
 
       def output_json(results)
@@ -219,6 +211,26 @@ module BasicCli
         end
       end
 
+      def output_table(results, format)
+        puts results_table(results).render(format)
+      end
+
+      # Numeric columns are right-aligned, as in the Rust binary.
+      def results_table(results, title: nil)
+        alignments = (1...TABLE_HEADERS.size).to_h { |i| [i, :right] }
+        table = Utils::Table.new(TABLE_HEADERS, title: title, alignments: alignments)
+        results.each_value do |r|
+          table.add_row([
+            r[:name],
+            r[:iterations].to_s,
+            format_time(r[:total_time]),
+            format_time(r[:avg_time]),
+            format('%.2f', r[:ops_per_sec])
+          ])
+        end
+        table
+      end
+
       def format_time(seconds)
         if seconds < 0.001
           "#{(seconds * 1_000_000).round(2)} μs"
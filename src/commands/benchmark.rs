@@ -1,3 +1,5 @@
+use crate::utils::ruby_json;
+use crate::utils::table::{Alignment, Table, TableFormat};
 use anyhow::Result;
 use serde_json::json;
 use std::collections::HashMap;
@@ -39,6 +41,8 @@ impl BenchmarkCommand {
         match self.output_format.as_str() {
             "json" => self.output_json(&results),
             "csv" => self.output_csv(&results),
+            "markdown" => self.output_table(&results, TableFormat::Markdown),
+            "html" => self.output_table(&results, TableFormat::Html),
             _ => self.output_console(&results),
         }
 
@@ -189,26 +193,49 @@ impl BenchmarkCommand {
     }
 
     fn output_console(&self, results: &[BenchmarkResult]) {
-        println!("\n{}", "=".repeat(60));
-        println!("{:^60}", "BENCHMARK RESULTS");
-        println!("{}", "=".repeat(60));
+        let table = self.results_table(results).with_title("BENCHMARK RESULTS");
+        println!("\n{}", table.render_console());
+
+        let total_time: Duration = results.iter().map(|r| r.total_time).sum();
+        println!("Total benchmark time: {}", format_duration(total_time));
+    }
+
+    fn output_table(&self, results: &[BenchmarkResult], format: TableFormat) {
+        println!("{}", self.results_table(results).render(format));
+    }
+
+    fn results_table(&self, results: &[BenchmarkResult]) -> Table {
+        let mut table = Table::new(vec![
+            "Benchmark",
+            "Iterations",
+            "Total time",
+            "Avg time/op",
+            "Ops/second",
+        ]);
+        for column in 1..=4 {
+            table = table.with_alignment(column, Alignment::Right);
+        }
 
         for result in results {
-            println!("\n{}:", result.name);
-            println!("  Iterations:     {}", result.iterations);
-            println!("  Total time:     {}", format_duration(result.total_time));
-            println!("  Avg time/op:    {}", format_duration(result.avg_time));
-            println!("  Ops/second:     {:.2}", result.ops_per_sec);
+            table.add_row(vec![
+                result.name.clone(),
+                result.iterations.to_string(),
+                format_duration(result.total_time),
+                format_duration(result.avg_time),
+                format!("{:.2}", result.ops_per_sec),
+            ]);
         }
 
-        let total_time: Duration = results.iter().map(|r| r.total_time).sum();
-        println!("\n{}", "=".repeat(60));
-        println!("Total benchmark time: {}", format_duration(total_time));
-        println!("{}", "=".repeat(60));
+        table
     }
 
-    fn output_json(&self, results: &[BenchmarkResult]) {
-        let output = json!({
+    /// The structured results behind `--output json` and `--template`.
+    pub fn report(&self) -> serde_json::Value {
+        self.results_json(&self.run_benchmarks())
+    }
+
+    fn results_json(&self, results: &[BenchmarkResult]) -> serde_json::Value {
+        json!({
             "timestamp": chrono::Utc::now().to_rfc3339(),
             "platform": std::env::consts::OS,
             "ruby_version": format!("Rust {}", env!("CARGO_PKG_RUST_VERSION")),
@@ -221,9 +248,12 @@ impl BenchmarkCommand {
                     "ops_per_second": r.ops_per_sec
                 })
             }).collect::<Vec<_>>()
-        });
+        })
+    }
 
-        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    fn output_json(&self, results: &[BenchmarkResult]) {
+        let output = self.results_json(results);
+        println!("{}", ruby_json::pretty_generate(&output).unwrap());
     }
 
     fn output_csv(&self, results: &[BenchmarkResult]) {
@@ -273,6 +303,25 @@ mod tests {
         assert!(cmd.execute().is_ok());
     }
 
+    #[test]
+    fn test_table_outputs() {
+        for format in ["markdown", "html"] {
+            let cmd = BenchmarkCommand::new(10, format.to_string(), false);
+            assert!(cmd.execute().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_results_table() {
+        let cmd = BenchmarkCommand::new(10, "console".to_string(), false);
+        let results = cmd.run_benchmarks();
+        let markdown = cmd.results_table(&results).render_markdown();
+
+        assert!(markdown.starts_with("| Benchmark | Iterations |"));
+        assert!(markdown.contains("| String Manipulation | 10 |"));
+        assert_eq!(markdown.lines().count(), 2 + results.len());
+    }
+
     #[test]
     fn test_verbose_mode() {
         let cmd = BenchmarkCommand::new(10, "console".to_string(), true);
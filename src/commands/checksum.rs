@@ -0,0 +1,497 @@
+use crate::utils::file_handler::{FileHandler, SearchOptions};
+use crate::utils::hashing::{self, HashAlgorithm};
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde_json::{json, Value};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+pub struct ChecksumCommand {
+    paths: Vec<String>,
+    algorithm: HashAlgorithm,
+    tag: bool,
+    check: Option<PathBuf>,
+    quiet: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Failed,
+    Missing,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Status::Ok => "OK",
+            Status::Failed => "FAILED",
+            Status::Missing => "MISSING",
+        }
+    }
+}
+
+/// One properly formatted line of a checksum manifest.
+#[derive(Debug, PartialEq)]
+struct ManifestEntry {
+    algorithm: HashAlgorithm,
+    digest: String,
+    name: String,
+}
+
+struct CheckResults {
+    results: Vec<(String, Status)>,
+    improperly_formatted: usize,
+}
+
+impl ChecksumCommand {
+    pub fn new(
+        paths: Vec<String>,
+        algorithm: HashAlgorithm,
+        tag: bool,
+        check: Option<PathBuf>,
+        quiet: bool,
+    ) -> Self {
+        Self {
+            paths,
+            algorithm,
+            tag,
+            check,
+            quiet,
+        }
+    }
+
+    /// Prints digests, or verifies a manifest with `--check`. Returns whether
+    /// anything failed, so the caller can exit non-zero like `sha256sum`.
+    pub fn execute(&self) -> Result<bool> {
+        match &self.check {
+            Some(manifest) => self.execute_check(manifest),
+            None => self.execute_hash(),
+        }
+    }
+
+    /// The structured results behind `--template`.
+    pub fn report(&self) -> Result<Value> {
+        if let Some(manifest) = &self.check {
+            let check = self.check(manifest)?;
+            let count = |status| check.results.iter().filter(|(_, s)| *s == status).count();
+            let files: Vec<Value> = check
+                .results
+                .iter()
+                .map(|(name, status)| json!({"path": name, "status": status.label()}))
+                .collect();
+            return Ok(json!({
+                "manifest": manifest.display().to_string(),
+                "ok": count(Status::Ok),
+                "failed": count(Status::Failed),
+                "missing": count(Status::Missing),
+                "improperly_formatted": check.improperly_formatted,
+                "files": files,
+            }));
+        }
+
+        let mut files = Vec::new();
+        let mut failed = 0;
+        for (name, digest) in self.hash_all()? {
+            files.push(match digest {
+                Ok(digest) => json!({"path": name, "digest": digest}),
+                Err(e) => {
+                    failed += 1;
+                    json!({"path": name, "error": e.to_string()})
+                }
+            });
+        }
+        Ok(json!({"algorithm": self.algorithm.name(), "failed": failed, "files": files}))
+    }
+
+    /// Whether a `report()` should make the command exit non-zero.
+    pub fn report_failed(report: &Value) -> bool {
+        let count = |key: &str| report[key].as_u64().unwrap_or(0);
+        let no_entries = report.get("manifest").is_some()
+            && report["files"]
+                .as_array()
+                .is_none_or(|files| files.is_empty());
+        count("failed") > 0
+            || count("missing") > 0
+            || count("improperly_formatted") > 0
+            || no_entries
+    }
+
+    fn execute_hash(&self) -> Result<bool> {
+        let mut failed = false;
+        for (name, digest) in self.hash_all()? {
+            match digest {
+                Ok(digest) => println!("{}", format_line(self.algorithm, &digest, &name, self.tag)),
+                Err(e) => {
+                    eprintln!("checksum: {}: {}", name, e);
+                    failed = true;
+                }
+            }
+        }
+        Ok(failed)
+    }
+
+    fn execute_check(&self, manifest: &Path) -> Result<bool> {
+        let check = self.check(manifest)?;
+
+        let mut failed = 0;
+        let mut missing = 0;
+        for (name, status) in &check.results {
+            match status {
+                Status::Ok => {}
+                Status::Failed => failed += 1,
+                Status::Missing => missing += 1,
+            }
+            if *status != Status::Ok || !self.quiet {
+                println!("{}: {}", name, status.label());
+            }
+        }
+
+        let plural = |n: usize, one: &str, many: &str| {
+            if n == 1 {
+                one.to_string()
+            } else {
+                many.to_string()
+            }
+        };
+        if check.improperly_formatted > 0 {
+            eprintln!(
+                "WARNING: {} {} improperly formatted",
+                check.improperly_formatted,
+                plural(check.improperly_formatted, "line is", "lines are")
+            );
+        }
+        if missing > 0 {
+            eprintln!(
+                "WARNING: {} listed {} could not be read",
+                missing,
+                plural(missing, "file", "files")
+            );
+        }
+        if failed > 0 {
+            eprintln!(
+                "WARNING: {} computed {} did NOT match",
+                failed,
+                plural(failed, "checksum", "checksums")
+            );
+        }
+        if check.results.is_empty() {
+            eprintln!(
+                "checksum: {}: no properly formatted checksum lines found",
+                manifest.display()
+            );
+        }
+
+        Ok(failed > 0 || missing > 0 || check.results.is_empty())
+    }
+
+    fn check(&self, manifest: &Path) -> Result<CheckResults> {
+        let content = if manifest == Path::new("-") {
+            io::read_to_string(io::stdin())?
+        } else {
+            FileHandler::read(manifest)?
+        };
+
+        let mut entries = Vec::new();
+        let mut improperly_formatted = 0;
+        for line in content.lines() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match parse_line(line, self.algorithm) {
+                Some(entry) => entries.push(entry),
+                None => improperly_formatted += 1,
+            }
+        }
+
+        let jobs: Vec<(PathBuf, HashAlgorithm)> = entries
+            .iter()
+            .map(|e| (PathBuf::from(&e.name), e.algorithm))
+            .collect();
+        let digests = FileHandler::checksum_files(&jobs);
+
+        let results = entries
+            .into_iter()
+            .zip(digests)
+            .map(|(entry, digest)| {
+                let status = match digest {
+                    Ok(digest) if digest.eq_ignore_ascii_case(&entry.digest) => Status::Ok,
+                    Ok(_) => Status::Failed,
+                    Err(_) if !Path::new(&entry.name).exists() => Status::Missing,
+                    Err(_) => Status::Failed,
+                };
+                (entry.name, status)
+            })
+            .collect();
+
+        Ok(CheckResults {
+            results,
+            improperly_formatted,
+        })
+    }
+
+    /// Expands directories and hashes everything, keeping argument order.
+    /// Stdin (`-`, or no paths at all) is hashed on the calling thread.
+    fn hash_all(&self) -> Result<Vec<(String, Result<String>)>> {
+        let paths = if self.paths.is_empty() {
+            vec!["-".to_string()]
+        } else {
+            self.paths.clone()
+        };
+
+        let mut files = Vec::new();
+        for path in &paths {
+            let as_path = Path::new(path);
+            if path != "-" && as_path.is_dir() {
+                files.extend(FileHandler::list_files(
+                    &[as_path],
+                    &SearchOptions::default(),
+                )?);
+            } else {
+                files.push(PathBuf::from(path));
+            }
+        }
+
+        let jobs: Vec<(PathBuf, HashAlgorithm)> = files
+            .iter()
+            .filter(|f| f.as_os_str() != "-")
+            .map(|f| (f.clone(), self.algorithm))
+            .collect();
+        let mut digests = FileHandler::checksum_files(&jobs).into_iter();
+
+        let mut results = Vec::new();
+        for file in files {
+            let digest = if file.as_os_str() == "-" {
+                hashing::digest_reader(io::stdin().lock(), &[self.algorithm])
+                    .map(|mut d| d.remove(0))
+            } else {
+                digests
+                    .next()
+                    .unwrap_or_else(|| Err(anyhow!("missing digest for {}", file.display())))
+            };
+            results.push((file.display().to_string(), digest));
+        }
+        Ok(results)
+    }
+}
+
+/// Formats one manifest line. Names containing a backslash or newline are
+/// escaped and the line prefixed with `\`, as coreutils does.
+fn format_line(algorithm: HashAlgorithm, digest: &str, name: &str, tag: bool) -> String {
+    let needs_escape = name.contains(['\\', '\n', '\r']);
+    let escaped = if needs_escape {
+        name.replace('\\', "\\\\")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+    } else {
+        name.to_string()
+    };
+    let prefix = if needs_escape { "\\" } else { "" };
+
+    if tag {
+        format!(
+            "{}{} ({}) = {}",
+            prefix,
+            algorithm.name().to_uppercase(),
+            escaped,
+            digest
+        )
+    } else {
+        format!("{}{}  {}", prefix, digest, escaped)
+    }
+}
+
+/// Parses a BSD tag line (`SHA256 (name) = digest`) or a coreutils line
+/// (`digest  name`, or `digest *name` for binary mode).
+fn parse_line(line: &str, default: HashAlgorithm) -> Option<ManifestEntry> {
+    static TAG: OnceLock<Regex> = OnceLock::new();
+    static GNU: OnceLock<Regex> = OnceLock::new();
+    let tag =
+        TAG.get_or_init(|| Regex::new(r"^([A-Za-z0-9-]+) \((.*)\) = ([0-9A-Fa-f]+)$").unwrap());
+    let gnu = GNU.get_or_init(|| Regex::new(r"^([0-9A-Fa-f]+) [ *](.+)$").unwrap());
+
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+
+    let (algorithm, digest, name) = if let Some(caps) = tag.captures(line) {
+        (
+            caps[1].parse().ok()?,
+            caps[3].to_string(),
+            caps[2].to_string(),
+        )
+    } else {
+        let caps = gnu.captures(line)?;
+        (default, caps[1].to_string(), caps[2].to_string())
+    };
+    if digest.len() != algorithm.hex_len() {
+        return None;
+    }
+
+    let name = if escaped { unescape_name(&name)? } else { name };
+    Some(ManifestEntry {
+        algorithm,
+        digest,
+        name,
+    })
+}
+
+fn unescape_name(name: &str) -> Option<String> {
+    let mut out = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '\\' => out.push('\\'),
+            'n' => out.push('\n'),
+            'r' => out.push('\r'),
+            _ => return None,
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const HELLO_SHA256: &str = "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+
+    #[test]
+    fn test_format_and_parse_lines() {
+        let gnu = format_line(HashAlgorithm::Sha256, HELLO_SHA256, "a b.txt", false);
+        assert_eq!(gnu, format!("{}  a b.txt", HELLO_SHA256));
+        let tag = format_line(HashAlgorithm::Crc32, "0d4a1185", "x", true);
+        assert_eq!(tag, "CRC32 (x) = 0d4a1185");
+        let escaped = format_line(HashAlgorithm::Sha256, HELLO_SHA256, "new\nline\\", false);
+        assert_eq!(escaped, format!("\\{}  new\\nline\\\\", HELLO_SHA256));
+
+        for (line, algorithm, name) in [
+            (gnu.as_str(), HashAlgorithm::Sha256, "a b.txt"),
+            (tag.as_str(), HashAlgorithm::Crc32, "x"),
+            (escaped.as_str(), HashAlgorithm::Sha256, "new\nline\\"),
+        ] {
+            let entry = parse_line(line, HashAlgorithm::Sha256).unwrap();
+            assert_eq!(entry.algorithm, algorithm);
+            assert_eq!(entry.name, name);
+        }
+
+        let binary = format!("{} *data.bin", HELLO_SHA256);
+        assert_eq!(
+            parse_line(&binary, HashAlgorithm::Sha256).unwrap().name,
+            "data.bin"
+        );
+        assert!(parse_line("abc123  short.txt", HashAlgorithm::Sha256).is_none());
+        assert!(parse_line("not a checksum line", HashAlgorithm::Sha256).is_none());
+    }
+
+    #[test]
+    fn test_hash_directory_in_order() {
+        let dir = TempDir::new().unwrap();
+        for (name, content) in [("b.txt", "b"), ("a.txt", "hello world"), ("sub/c.txt", "c")] {
+            FileHandler::write(dir.path().join(name), content).unwrap();
+        }
+
+        let root = dir.path().display().to_string();
+        let command = ChecksumCommand::new(
+            vec![root.clone()],
+            HashAlgorithm::Sha256,
+            false,
+            None,
+            false,
+        );
+        let report = command.report().unwrap();
+        let files = report["files"].as_array().unwrap();
+
+        let names: Vec<&str> = files.iter().map(|f| f["path"].as_str().unwrap()).collect();
+        assert_eq!(
+            names,
+            vec![
+                format!("{}/a.txt", root),
+                format!("{}/b.txt", root),
+                format!("{}/sub/c.txt", root)
+            ]
+        );
+        assert_eq!(files[0]["digest"], HELLO_SHA256);
+    }
+
+    #[test]
+    fn test_check_reports_each_status() {
+        let dir = TempDir::new().unwrap();
+        let good = dir.path().join("good.txt");
+        let bad = dir.path().join("bad.txt");
+        fs::write(&good, "hello world").unwrap();
+        fs::write(&bad, "tampered").unwrap();
+
+        let manifest = dir.path().join("SHA256SUMS");
+        fs::write(
+            &manifest,
+            format!(
+                "{h}  {}\nSHA256 ({}) = {h}\n{h}  {}\ngarbage\n",
+                good.display(),
+                bad.display(),
+                dir.path().join("gone.txt").display(),
+                h = HELLO_SHA256
+            ),
+        )
+        .unwrap();
+
+        let command = ChecksumCommand::new(
+            vec![],
+            HashAlgorithm::Sha256,
+            false,
+            Some(manifest.clone()),
+            false,
+        );
+        let report = command.report().unwrap();
+        assert_eq!(report["ok"], 1);
+        assert_eq!(report["failed"], 1);
+        assert_eq!(report["missing"], 1);
+        assert_eq!(report["improperly_formatted"], 1);
+        assert_eq!(report["files"][1]["status"], "FAILED");
+        assert!(ChecksumCommand::report_failed(&report));
+        assert!(command.execute().unwrap());
+
+        fs::write(
+            &manifest,
+            format!("{}  {}\nnot a line\n", HELLO_SHA256, good.display()),
+        )
+        .unwrap();
+        let report = command.report().unwrap();
+        assert_eq!(report["ok"], 1);
+        assert!(ChecksumCommand::report_failed(&report));
+
+        fs::write(&manifest, "").unwrap();
+        assert!(ChecksumCommand::report_failed(&command.report().unwrap()));
+    }
+
+    #[test]
+    fn test_hash_report_keeps_going_past_errors() {
+        let dir = TempDir::new().unwrap();
+        let good = dir.path().join("good.txt");
+        fs::write(&good, "hello world").unwrap();
+        let gone = dir.path().join("gone.txt").display().to_string();
+
+        let command = ChecksumCommand::new(
+            vec![gone.clone(), good.display().to_string()],
+            HashAlgorithm::Sha256,
+            false,
+            None,
+            false,
+        );
+        let report = command.report().unwrap();
+        assert_eq!(report["failed"], 1);
+        assert_eq!(report["files"][0]["path"], gone);
+        assert!(report["files"][0]["error"].is_string());
+        assert_eq!(report["files"][1]["digest"], HELLO_SHA256);
+        assert!(ChecksumCommand::report_failed(&report));
+    }
+}
@@ -0,0 +1,422 @@
+use crate::utils::csv_dialect::CsvOptions;
+use crate::utils::file_handler::{CsvTable, FileError, FileHandler};
+use crate::utils::http_fetcher::HttpFetcher;
+use crate::utils::ruby_json;
+use crate::utils::xlsx::{self, SheetOptions};
+use anyhow::{bail, Result};
+use csv::QuoteStyle;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Csv,
+    Tsv,
+    Json,
+    Xlsx,
+    /// Ruby `Marshal.dump` output; input only.
+    Marshal,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+
+        match extension.as_str() {
+            "csv" => Ok(Format::Csv),
+            "tsv" | "tab" => Ok(Format::Tsv),
+            "json" => Ok(Format::Json),
+            "xlsx" => Ok(Format::Xlsx),
+            "marshal" => Ok(Format::Marshal),
+            _ => bail!(FileError::UnsupportedFormat(path.display().to_string())),
+        }
+    }
+}
+
+/// Dialect choices for CSV and TSV files. Without `delimiter`, the input's
+/// delimiter and quote character are sniffed; comment lines are only
+/// skipped with `comment`. Unset output settings follow the extension.
+#[derive(Debug, Default, Clone)]
+pub struct CsvSettings {
+    pub delimiter: Option<u8>,
+    pub comment: Option<u8>,
+    pub no_header: bool,
+    pub trim: bool,
+    pub flexible: bool,
+    pub output_delimiter: Option<u8>,
+    pub quote_style: Option<QuoteStyle>,
+}
+
+impl CsvSettings {
+    fn input_options(&self, path: &Path) -> Result<CsvOptions> {
+        let mut options = CsvOptions::detect(path)?;
+        if let Some(delimiter) = self.delimiter {
+            options.delimiter = delimiter;
+        }
+        options.comment = self.comment;
+        options.has_headers = !self.no_header;
+        options.trim = self.trim;
+        options.flexible = self.flexible;
+        Ok(options)
+    }
+
+    fn output_options(&self, path: &Path) -> CsvOptions {
+        let mut options = CsvOptions::for_path(path);
+        if let Some(delimiter) = self.output_delimiter {
+            options.delimiter = delimiter;
+        }
+        if let Some(quote_style) = self.quote_style {
+            options.quote_style = quote_style;
+        }
+        options
+    }
+}
+
+pub struct ConvertCommand {
+    input: String,
+    output: PathBuf,
+    sheet: SheetOptions,
+    csv: CsvSettings,
+    sha256: Option<String>,
+}
+
+impl ConvertCommand {
+    pub fn new(
+        input: String,
+        output: PathBuf,
+        sheet: SheetOptions,
+        csv: CsvSettings,
+        sha256: Option<String>,
+    ) -> Self {
+        Self {
+            input,
+            output,
+            sheet,
+            csv,
+            sha256,
+        }
+    }
+
+    pub fn execute(&self) -> Result<()> {
+        let table = self.convert()?;
+
+        println!(
+            "Converted {} rows ({} columns) to {}",
+            table.rows.len(),
+            table.headers.len(),
+            self.output.display()
+        );
+
+        Ok(())
+    }
+
+    fn convert(&self) -> Result<CsvTable> {
+        let output_format = Format::from_path(&self.output)?;
+        if output_format == Format::Marshal {
+            bail!(FileError::UnsupportedFormat(format!(
+                "{} (.marshal is only supported as input)",
+                self.output.display()
+            )));
+        }
+        let input = HttpFetcher::new().resolve(&self.input)?;
+        if !input.exists() {
+            bail!(FileError::NotFound(input.display().to_string()));
+        }
+        if let Some(expected) = &self.sha256 {
+            HttpFetcher::verify_sha256(&input, expected)?;
+        }
+
+        let table = match Format::from_path(&input)? {
+            Format::Csv | Format::Tsv => {
+                FileHandler::read_csv_table_with(&input, &self.csv.input_options(&input)?)?
+            }
+            Format::Json => {
+                table_from_records(FileHandler::read_json(&input)?, FileError::InvalidJson)?
+            }
+            Format::Marshal => table_from_records(
+                FileHandler::read_marshal(&input)?,
+                FileError::InvalidMarshal,
+            )?,
+            Format::Xlsx => {
+                let bytes = fs::read(&input)?;
+                let sheet = xlsx::read_sheet(&bytes, &self.sheet)
+                    .map_err(|e| FileError::InvalidXlsx(format!("{:#}", e)))?;
+                CsvTable {
+                    headers: sheet.headers,
+                    rows: sheet.rows,
+                }
+            }
+        };
+
+        match output_format {
+            Format::Csv | Format::Tsv => FileHandler::write_csv_table_with(
+                &self.output,
+                &table,
+                &self.csv.output_options(&self.output),
+            )?,
+            Format::Json => FileHandler::write(
+                &self.output,
+                &format!("{}\n", ruby_json::pretty_generate(&table_json(&table))?),
+            )?,
+            Format::Xlsx => {
+                let bytes = xlsx::write_workbook("Sheet1", &table.headers, &table.rows)?;
+                FileHandler::atomic_write_with(&self.output, |out| Ok(out.write_all(&bytes)?))?;
+            }
+            Format::Marshal => unreachable!("rejected before reading the input"),
+        }
+
+        Ok(table)
+    }
+}
+
+/// Accepts an array of objects (from JSON, or an array of hashes from
+/// Marshal). Columns are the union of keys in order of first appearance;
+/// non-string values are written as JSON text.
+fn table_from_records(data: Value, invalid: fn(String) -> FileError) -> Result<CsvTable> {
+    let invalid = || invalid("expected an array of objects".to_string());
+    let Some(records) = data.as_array() else {
+        bail!(invalid());
+    };
+
+    let mut headers: Vec<String> = Vec::new();
+    for record in records {
+        let Some(object) = record.as_object() else {
+            bail!(invalid());
+        };
+        for key in object.keys() {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+    }
+
+    let rows = records
+        .iter()
+        .map(|record| {
+            headers
+                .iter()
+                .map(|h| match record.get(h) {
+                    None | Some(Value::Null) => String::new(),
+                    Some(Value::String(s)) => s.clone(),
+                    Some(other) => other.to_string(),
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(CsvTable { headers, rows })
+}
+
+fn table_json(table: &CsvTable) -> Value {
+    Value::Array(
+        table
+            .rows
+            .iter()
+            .map(|row| {
+                let object: Map<String, Value> = table
+                    .headers
+                    .iter()
+                    .cloned()
+                    .zip(row.iter().map(|v| Value::String(v.clone())))
+                    .collect();
+                Value::Object(object)
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hashing::HashAlgorithm;
+    use tempfile::TempDir;
+
+    fn convert(input: &Path, output: &Path) -> CsvTable {
+        ConvertCommand::new(
+            input.display().to_string(),
+            output.to_path_buf(),
+            SheetOptions::default(),
+            CsvSettings::default(),
+            None,
+        )
+        .convert()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_csv_xlsx_json_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let csv = dir.path().join("people.csv");
+        fs::write(&csv, "name,age,note\nAda,36,\"a, b\"\nGrace,,\n").unwrap();
+
+        let xlsx = dir.path().join("people.xlsx");
+        let table = convert(&csv, &xlsx);
+        assert_eq!(table.headers, vec!["name", "age", "note"]);
+
+        let json = dir.path().join("people.json");
+        assert_eq!(convert(&xlsx, &json), table);
+        assert_eq!(
+            FileHandler::read_json::<Value, _>(&json).unwrap()[0],
+            serde_json::json!({"name": "Ada", "age": "36", "note": "a, b"})
+        );
+
+        let back = dir.path().join("back.csv");
+        convert(&json, &back);
+        assert_eq!(
+            fs::read_to_string(back).unwrap(),
+            fs::read_to_string(csv).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_json_values_and_sparse_keys() {
+        let dir = TempDir::new().unwrap();
+        let json = dir.path().join("in.json");
+        fs::write(
+            &json,
+            r#"[{"id": 1, "ok": true}, {"id": 2, "tags": ["x"], "ok": null}]"#,
+        )
+        .unwrap();
+
+        let table = convert(&json, &dir.path().join("out.csv"));
+        assert_eq!(table.headers, vec!["id", "ok", "tags"]);
+        assert_eq!(table.rows[0], vec!["1", "true", ""]);
+        assert_eq!(table.rows[1], vec!["2", "", "[\"x\"]"]);
+    }
+
+    #[test]
+    fn test_csv_dialects() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("export.csv");
+        fs::write(&input, "Name;Preis\nApfel;1,20\nBirne;0,95\n").unwrap();
+
+        let tsv = dir.path().join("prices.tsv");
+        let table = convert(&input, &tsv);
+        assert_eq!(table.headers, vec!["Name", "Preis"]);
+        assert_eq!(
+            fs::read_to_string(&tsv).unwrap(),
+            "Name\tPreis\nApfel\t1,20\nBirne\t0,95\n"
+        );
+
+        let settings = CsvSettings {
+            no_header: true,
+            output_delimiter: Some(b'|'),
+            quote_style: Some(QuoteStyle::Always),
+            ..Default::default()
+        };
+        let output = dir.path().join("out.csv");
+        let table = ConvertCommand::new(
+            tsv.display().to_string(),
+            output.clone(),
+            SheetOptions::default(),
+            settings,
+            None,
+        )
+        .convert()
+        .unwrap();
+        assert_eq!(table.headers, vec!["column_1", "column_2"]);
+        assert_eq!(table.rows.len(), 3);
+        assert!(fs::read_to_string(&output)
+            .unwrap()
+            .starts_with("\"column_1\"|\"column_2\"\n\"Name\"|\"Preis\"\n"));
+
+        // A `#` header is a column name, not a comment.
+        let ranked = dir.path().join("ranked.csv");
+        fs::write(&ranked, "#,name,score\n1,Ada,9\n2,Bob,7\n").unwrap();
+        let table = convert(&ranked, &dir.path().join("ranked.json"));
+        assert_eq!(table.headers, vec!["#", "name", "score"]);
+        assert_eq!(table.rows.len(), 2);
+    }
+
+    #[test]
+    fn test_marshal_input() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("people.marshal");
+        // Marshal.dump([{name: "Ada", age: 36}])
+        fs::write(
+            &input,
+            b"\x04\x08[\x06{\x07:\x09nameI\"\x08Ada\x06:\x06ET:\x08agei\x29",
+        )
+        .unwrap();
+
+        let table = convert(&input, &dir.path().join("people.csv"));
+        assert_eq!(table.headers, vec!["name", "age"]);
+        assert_eq!(table.rows, vec![vec!["Ada", "36"]]);
+
+        let command = ConvertCommand::new(
+            dir.path().join("people.csv").display().to_string(),
+            dir.path().join("back.marshal"),
+            SheetOptions::default(),
+            CsvSettings::default(),
+            None,
+        );
+        assert!(command.convert().is_err());
+
+        // Marshal.dump("not a table")
+        fs::write(&input, b"\x04\x08I\"\x10not a table\x06:\x06ET").unwrap();
+        let error = ConvertCommand::new(
+            input.display().to_string(),
+            dir.path().join("out.json"),
+            SheetOptions::default(),
+            CsvSettings::default(),
+            None,
+        )
+        .convert()
+        .unwrap_err();
+        assert!(
+            matches!(
+                error.downcast_ref::<FileError>(),
+                Some(FileError::InvalidMarshal(_))
+            ),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_unsupported_formats() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("in.csv");
+        fs::write(&input, "a\n1\n").unwrap();
+
+        let command = ConvertCommand::new(
+            input.display().to_string(),
+            dir.path().join("out.txt"),
+            SheetOptions::default(),
+            CsvSettings::default(),
+            None,
+        );
+        assert!(command.convert().is_err());
+    }
+
+    #[test]
+    fn test_sha256() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("in.csv");
+        fs::write(&input, "a\n1\n").unwrap();
+        let command = |sha256: &str| {
+            ConvertCommand::new(
+                input.display().to_string(),
+                dir.path().join("out.json"),
+                SheetOptions::default(),
+                CsvSettings::default(),
+                Some(sha256.to_string()),
+            )
+        };
+
+        let error = command(&"0".repeat(64)).convert().unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<FileError>(),
+            Some(FileError::ChecksumMismatch(..))
+        ));
+        assert!(!dir.path().join("out.json").exists());
+
+        let digest = FileHandler::checksum(&input, HashAlgorithm::Sha256).unwrap();
+        assert_eq!(command(&digest).convert().unwrap().rows.len(), 1);
+    }
+}
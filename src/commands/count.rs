@@ -0,0 +1,340 @@
+use crate::utils::http_fetcher::HttpFetcher;
+use crate::utils::ruby_json;
+use crate::utils::table::{Alignment, Table, TableFormat};
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use unicode_width::UnicodeWidthChar;
+
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+pub struct CountCommand {
+    inputs: Vec<String>,
+    output_format: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TextStats {
+    /// Number of lines, counting a final line without a newline.
+    pub lines: usize,
+    pub words: usize,
+    pub bytes: u64,
+    /// Unicode scalar values; invalid UTF-8 counts as U+FFFD per bad sequence.
+    pub chars: usize,
+    /// Approximate user-perceived characters (see `count_graphemes`).
+    pub graphemes: usize,
+    /// Display width of the widest line, like `wc -L`.
+    pub longest_line: usize,
+    pub lf: usize,
+    pub crlf: usize,
+    pub valid_utf8: bool,
+}
+
+impl TextStats {
+    /// Reads `reader` one line at a time, so memory stays bounded by the
+    /// longest line rather than the size of the input.
+    pub fn from_reader<R: BufRead>(mut reader: R) -> Result<Self> {
+        let mut stats = TextStats {
+            valid_utf8: true,
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+
+        loop {
+            buffer.clear();
+            let read = reader.read_until(b'\n', &mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            stats.add_line(&buffer);
+        }
+
+        Ok(stats)
+    }
+
+    fn add_line(&mut self, raw: &[u8]) {
+        self.lines += 1;
+        self.bytes += raw.len() as u64;
+
+        let mut content = raw;
+        if let Some(rest) = content.strip_suffix(b"\n") {
+            content = rest;
+            match rest.strip_suffix(b"\r") {
+                Some(rest) => {
+                    content = rest;
+                    self.crlf += 1;
+                }
+                None => self.lf += 1,
+            }
+        }
+
+        let text = match std::str::from_utf8(content) {
+            Ok(text) => std::borrow::Cow::Borrowed(text),
+            Err(_) => {
+                self.valid_utf8 = false;
+                String::from_utf8_lossy(content)
+            }
+        };
+
+        self.words += text.split_whitespace().count();
+        self.chars += text.chars().count() + (raw.len() - content.len());
+        self.graphemes += count_graphemes(&text) + usize::from(raw.len() > content.len());
+
+        let width: usize = text.chars().filter_map(|c| c.width()).sum();
+        self.longest_line = self.longest_line.max(width);
+    }
+
+    pub fn line_endings(&self) -> &'static str {
+        match (self.lf, self.crlf) {
+            (0, 0) => "none",
+            (_, 0) => "LF",
+            (0, _) => "CRLF",
+            _ => "mixed",
+        }
+    }
+
+    fn merge(&mut self, other: &TextStats) {
+        self.lines += other.lines;
+        self.words += other.words;
+        self.bytes += other.bytes;
+        self.chars += other.chars;
+        self.graphemes += other.graphemes;
+        self.longest_line = self.longest_line.max(other.longest_line);
+        self.lf += other.lf;
+        self.crlf += other.crlf;
+        self.valid_utf8 &= other.valid_utf8;
+    }
+}
+
+impl CountCommand {
+    pub fn new(inputs: Vec<String>, output_format: String) -> Self {
+        let inputs = if inputs.is_empty() {
+            vec!["-".to_string()]
+        } else {
+            inputs
+        };
+
+        Self {
+            inputs,
+            output_format,
+        }
+    }
+
+    pub fn execute(&self) -> Result<()> {
+        let (results, total) = self.count_all()?;
+
+        match self.output_format.as_str() {
+            "json" => {
+                let output = report_json(&results, &total);
+                println!("{}", ruby_json::pretty_generate(&output)?);
+            }
+            format => {
+                let format: TableFormat = format.parse()?;
+                println!("{}", stats_table(&results, &total).render(format));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The structured counts behind `--output json` and `--template`.
+    pub fn report(&self) -> Result<Value> {
+        let (results, total) = self.count_all()?;
+        Ok(report_json(&results, &total))
+    }
+
+    fn count_all(&self) -> Result<(Vec<(String, TextStats)>, TextStats)> {
+        let mut results = Vec::new();
+        for input in &self.inputs {
+            results.push((input.clone(), count_input(input)?));
+        }
+
+        let mut total = TextStats {
+            valid_utf8: true,
+            ..Default::default()
+        };
+        for (_, stats) in &results {
+            total.merge(stats);
+        }
+
+        Ok((results, total))
+    }
+}
+
+/// Counts a file, an http(s) URL, or stdin for `-`.
+fn count_input(input: &str) -> Result<TextStats> {
+    if input == "-" {
+        return TextStats::from_reader(std::io::stdin().lock());
+    }
+
+    let path = HttpFetcher::new().resolve(input)?;
+    let file = File::open(&path).with_context(|| format!("Failed to open {}", input))?;
+    TextStats::from_reader(BufReader::new(file))
+}
+
+fn report_json(results: &[(String, TextStats)], total: &TextStats) -> Value {
+    let files: Vec<Value> = results
+        .iter()
+        .map(|(name, stats)| stats_json(name, stats))
+        .collect();
+    json!({
+        "files": files,
+        "total": stats_json("total", total),
+    })
+}
+
+fn stats_json(name: &str, stats: &TextStats) -> Value {
+    json!({
+        "file": name,
+        "lines": stats.lines,
+        "words": stats.words,
+        "bytes": stats.bytes,
+        "chars": stats.chars,
+        "graphemes": stats.graphemes,
+        "longest_line": stats.longest_line,
+        "line_endings": stats.line_endings(),
+        "valid_utf8": stats.valid_utf8,
+    })
+}
+
+/// Per-file rows, plus a total row when more than one input was counted.
+fn stats_table(results: &[(String, TextStats)], total: &TextStats) -> Table {
+    let mut table = Table::new(vec![
+        "File",
+        "Lines",
+        "Words",
+        "Bytes",
+        "Chars",
+        "Graphemes",
+        "Longest",
+        "Endings",
+    ]);
+    for column in 1..=6 {
+        table = table.with_alignment(column, Alignment::Right);
+    }
+
+    let row = |name: &str, stats: &TextStats| {
+        let mut name = name.to_string();
+        if !stats.valid_utf8 {
+            name.push_str(" (invalid UTF-8)");
+        }
+        vec![
+            name,
+            stats.lines.to_string(),
+            stats.words.to_string(),
+            stats.bytes.to_string(),
+            stats.chars.to_string(),
+            stats.graphemes.to_string(),
+            stats.longest_line.to_string(),
+            stats.line_endings().to_string(),
+        ]
+    };
+
+    for (name, stats) in results {
+        table.add_row(row(name, stats));
+    }
+    if results.len() > 1 {
+        table.add_row(row("total", total));
+    }
+
+    table
+}
+
+/// Approximates extended grapheme clusters without the full Unicode tables:
+/// zero-width characters (combining marks, variation selectors), anything
+/// joined by a ZWJ, emoji skin-tone modifiers and the second regional
+/// indicator of a flag all extend the preceding character.
+fn count_graphemes(text: &str) -> usize {
+    let mut count = 0;
+    let mut previous: Option<char> = None;
+    let mut open_flag = false;
+
+    for c in text.chars() {
+        let regional = ('\u{1F1E6}'..='\u{1F1FF}').contains(&c);
+        let extends = match previous {
+            None => false,
+            Some(ZERO_WIDTH_JOINER) => true,
+            Some(_) if regional => open_flag,
+            Some(_) => {
+                c == ZERO_WIDTH_JOINER
+                    || ('\u{1F3FB}'..='\u{1F3FF}').contains(&c)
+                    || (c.width() == Some(0) && !c.is_control())
+            }
+        };
+
+        if !extends {
+            count += 1;
+        }
+        open_flag = regional && !(extends && open_flag);
+        previous = Some(c);
+    }
+
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(bytes: &[u8]) -> TextStats {
+        TextStats::from_reader(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_basic_counts() {
+        let stats = stats(b"hello world\nsecond line here\nlast");
+
+        assert_eq!(stats.lines, 3);
+        assert_eq!(stats.words, 6);
+        assert_eq!(stats.bytes, 33);
+        assert_eq!(stats.chars, 33);
+        assert_eq!(stats.longest_line, 16);
+        assert_eq!(stats.line_endings(), "LF");
+        assert!(stats.valid_utf8);
+    }
+
+    #[test]
+    fn test_line_endings() {
+        assert_eq!(stats(b"a\r\nb\r\n").line_endings(), "CRLF");
+        assert_eq!(stats(b"a\r\nb\n").line_endings(), "mixed");
+        assert_eq!(stats(b"no newline").line_endings(), "none");
+        assert_eq!(stats(b"a\r\n").graphemes, 2);
+    }
+
+    #[test]
+    fn test_unicode_counts() {
+        let stats = stats("e\u{301}t\u{e9} 👍🏽 🇳🇱🇩🇪 👨‍👩‍👧\n".as_bytes());
+
+        assert_eq!(stats.chars, 19);
+        assert_eq!(stats.graphemes, 11);
+        assert_eq!(stats.words, 4);
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let stats = stats(b"caf\xe9 ok\n");
+
+        assert!(!stats.valid_utf8);
+        assert_eq!(stats.bytes, 8);
+        assert_eq!(stats.chars, 8);
+        assert_eq!(stats.words, 2);
+    }
+
+    #[test]
+    fn test_total_row() {
+        let results = vec![
+            ("a".to_string(), stats(b"one\n")),
+            ("b".to_string(), stats(b"two words\r\n")),
+        ];
+        let mut total = results[0].1.clone();
+        total.merge(&results[1].1);
+
+        assert_eq!(total.lines, 2);
+        assert_eq!(total.words, 3);
+        assert_eq!(total.line_endings(), "mixed");
+
+        let table = stats_table(&results, &total).render(TableFormat::Markdown);
+        assert!(table.contains("| total"));
+    }
+}
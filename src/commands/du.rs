@@ -0,0 +1,187 @@
+use crate::utils::byte_size;
+use crate::utils::dir_tree::DirNode;
+use crate::utils::ruby_json;
+use crate::utils::walker::WalkOptions;
+use anyhow::{bail, Result};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+pub struct DuCommand {
+    path: PathBuf,
+    options: WalkOptions,
+    max_depth: Option<usize>,
+    top: usize,
+    output_format: String,
+}
+
+impl DuCommand {
+    /// `top` of 0 lists every directory.
+    pub fn new(
+        path: PathBuf,
+        options: WalkOptions,
+        max_depth: Option<usize>,
+        top: usize,
+        output_format: String,
+    ) -> Result<Self> {
+        if !matches!(output_format.as_str(), "console" | "json") {
+            bail!(
+                "Unknown output format: {:?} (expected console or json)",
+                output_format
+            );
+        }
+        Ok(Self {
+            path,
+            options,
+            max_depth,
+            top,
+            output_format,
+        })
+    }
+
+    /// Prints the largest directories. Returns whether any directory
+    /// couldn't be read.
+    pub fn execute(&self) -> Result<bool> {
+        let (root, errors) = DirNode::build(&self.path, &self.options)?;
+        if self.output_format == "json" {
+            println!(
+                "{}",
+                ruby_json::pretty_generate(&self.json_report(&root, &errors))?
+            );
+        } else {
+            for dir in self.largest(&root) {
+                println!(
+                    "{:>10}  {:>5.1}%  {}",
+                    byte_size::format(dir.size),
+                    percent(dir.size, root.size),
+                    dir.path.display()
+                );
+            }
+        }
+
+        for error in &errors {
+            eprintln!("du: {}", error);
+        }
+        Ok(!errors.is_empty())
+    }
+
+    /// The structured results behind `--output json` and `--template`.
+    pub fn report(&self) -> Result<Value> {
+        let (root, errors) = DirNode::build(&self.path, &self.options)?;
+        Ok(self.json_report(&root, &errors))
+    }
+
+    /// Directories within the depth limit, largest first (ties by path).
+    fn largest<'a>(&self, root: &'a DirNode) -> Vec<&'a DirNode> {
+        let mut dirs: Vec<&DirNode> = root
+            .directories()
+            .into_iter()
+            .filter(|d| self.max_depth.is_none_or(|max| d.depth <= max))
+            .collect();
+        dirs.sort_by(|a, b| {
+            b.size
+                .cmp(&a.size)
+                .then_with(|| a.relative.cmp(&b.relative))
+        });
+        if self.top > 0 {
+            dirs.truncate(self.top);
+        }
+        dirs
+    }
+
+    fn json_report(&self, root: &DirNode, errors: &[String]) -> Value {
+        let directories: Vec<Value> = self
+            .largest(root)
+            .iter()
+            .map(|dir| {
+                json!({
+                    "path": dir.path.display().to_string(),
+                    "relative": dir.relative,
+                    "size": dir.size,
+                    "files": dir.file_count,
+                    "percent": (percent(dir.size, root.size) * 10.0).round() / 10.0,
+                })
+            })
+            .collect();
+        json!({
+            "path": self.path.display().to_string(),
+            "total_size": root.size,
+            "files": root.file_count,
+            "directories": root.dir_count + 1,
+            "largest": directories,
+            "errors": errors,
+        })
+    }
+}
+
+fn percent(size: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        size as f64 * 100.0 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_handler::FileHandler;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_largest_directories() {
+        let dir = TempDir::new().unwrap();
+        for (path, size) in [
+            ("logs/old/a.log", 600),
+            ("logs/b.log", 100),
+            ("src/main.rs", 250),
+            ("README.md", 50),
+        ] {
+            FileHandler::write(dir.path().join(path), &"x".repeat(size)).unwrap();
+        }
+
+        let du = DuCommand::new(
+            dir.path().to_path_buf(),
+            WalkOptions::default(),
+            None,
+            3,
+            "json".into(),
+        )
+        .unwrap();
+        let report = du.report().unwrap();
+        assert_eq!(report["total_size"], 1000);
+        assert_eq!(report["files"], 4);
+        assert_eq!(report["directories"], 4);
+        let relative: Vec<&str> = report["largest"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|d| d["relative"].as_str().unwrap())
+            .collect();
+        assert_eq!(relative, ["", "logs", "logs/old"]);
+        assert_eq!(report["largest"][1]["percent"], 70.0);
+
+        let du = DuCommand::new(
+            dir.path().to_path_buf(),
+            WalkOptions::default(),
+            Some(1),
+            0,
+            "json".into(),
+        )
+        .unwrap();
+        let report = du.report().unwrap();
+        assert_eq!(report["largest"].as_array().unwrap().len(), 3);
+        assert_eq!(report["largest"][2]["relative"], "src");
+    }
+
+    #[test]
+    fn test_rejects_unknown_output_format() {
+        let result = DuCommand::new(
+            PathBuf::from("."),
+            WalkOptions::default(),
+            None,
+            0,
+            "yaml".into(),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,48 @@
+use crate::utils::encoding::{self, Encoding};
+use crate::utils::http_fetcher::HttpFetcher;
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read};
+
+/// Backs both the `encode` and `decode` commands.
+pub struct EncodeCommand {
+    encoding: Encoding,
+    input: Option<String>,
+    decode: bool,
+    wrap: usize,
+}
+
+impl EncodeCommand {
+    pub fn new(encoding: Encoding, input: Option<String>, decode: bool, wrap: usize) -> Self {
+        Self {
+            encoding,
+            input,
+            decode,
+            wrap,
+        }
+    }
+
+    pub fn execute(&self) -> Result<()> {
+        let reader = self.open_input()?;
+        let writer = BufWriter::new(std::io::stdout().lock());
+
+        if self.decode {
+            encoding::decode(self.encoding, reader, writer)
+        } else {
+            encoding::encode(self.encoding, reader, writer, self.wrap)
+        }
+    }
+
+    /// Opens a file, an http(s) URL, or stdin when no input (or `-`) is given.
+    fn open_input(&self) -> Result<Box<dyn Read>> {
+        match self.input.as_deref() {
+            None | Some("-") => Ok(Box::new(std::io::stdin().lock())),
+            Some(input) => {
+                let path = HttpFetcher::new().resolve(input)?;
+                let file =
+                    File::open(&path).with_context(|| format!("Failed to open {}", input))?;
+                Ok(Box::new(BufReader::new(file)))
+            }
+        }
+    }
+}
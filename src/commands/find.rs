@@ -0,0 +1,172 @@
+use crate::utils::file_handler::FileStats;
+use crate::utils::ruby_json;
+use crate::utils::walker::{WalkEntry, WalkOptions, Walker};
+use anyhow::{bail, Result};
+use serde_json::{json, Value};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+pub struct FindCommand {
+    paths: Vec<PathBuf>,
+    options: WalkOptions,
+    print0: bool,
+    output_format: String,
+}
+
+impl FindCommand {
+    pub fn new(
+        paths: Vec<PathBuf>,
+        options: WalkOptions,
+        print0: bool,
+        output_format: String,
+    ) -> Result<Self> {
+        if !matches!(output_format.as_str(), "console" | "json") {
+            bail!(
+                "Unknown output format: {:?} (expected console or json)",
+                output_format
+            );
+        }
+        let paths = if paths.is_empty() {
+            vec![PathBuf::from(".")]
+        } else {
+            paths
+        };
+        Ok(Self {
+            paths,
+            options,
+            print0,
+            output_format,
+        })
+    }
+
+    /// Lists matching entries, one path per line (or NUL-terminated with
+    /// `--print0`). Returns whether any directory couldn't be read, so the
+    /// caller can exit non-zero like `find`.
+    pub fn execute(&self) -> Result<bool> {
+        if self.output_format == "json" {
+            let report = self.report()?;
+            println!("{}", ruby_json::pretty_generate(&report)?);
+            return Ok(!report["errors"].as_array().is_some_and(|e| e.is_empty()));
+        }
+
+        let walker = Walker::new(self.options.clone());
+        let mut out = BufWriter::new(io::stdout().lock());
+        let terminator = if self.print0 { b'\0' } else { b'\n' };
+        let result = walker.visit(&self.paths, |entry| {
+            out.write_all(entry.path.to_string_lossy().as_bytes())?;
+            out.write_all(&[terminator])?;
+            Ok(())
+        });
+        let errors = match result.and_then(|errors| Ok(out.flush().map(|_| errors)?)) {
+            Ok(errors) => errors,
+            Err(e) if is_broken_pipe(&e) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+
+        for error in &errors {
+            eprintln!("find: {}", error);
+        }
+        Ok(!errors.is_empty())
+    }
+
+    /// The structured results behind `--output json` and `--template`.
+    pub fn report(&self) -> Result<Value> {
+        let walk = Walker::new(self.options.clone()).walk(&self.paths)?;
+        let entries = walk
+            .entries
+            .iter()
+            .map(entry_json)
+            .collect::<Result<Vec<Value>>>()?;
+        let total_size: u64 = walk
+            .entries
+            .iter()
+            .filter(|e| e.metadata.is_file())
+            .map(|e| e.metadata.len())
+            .sum();
+
+        Ok(json!({
+            "paths": self.paths.iter().map(|p| p.display().to_string()).collect::<Vec<_>>(),
+            "count": entries.len(),
+            "total_size": total_size,
+            "entries": entries,
+            "errors": walk.errors,
+        }))
+    }
+}
+
+fn entry_json(entry: &WalkEntry) -> Result<Value> {
+    let mut value = json!({
+        "path": entry.path.display().to_string(),
+        "relative": entry.relative,
+        "type": entry.file_type.name(),
+        "depth": entry.depth,
+    });
+    if let (Value::Object(map), Value::Object(stats)) = (
+        &mut value,
+        FileStats::from_metadata(&entry.metadata)?.to_json(),
+    ) {
+        map.extend(stats);
+    }
+    Ok(value)
+}
+
+fn is_broken_pipe(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<io::Error>()
+        .is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_handler::FileHandler;
+    use crate::utils::walker::EntryType;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_report_includes_stats() {
+        let dir = TempDir::new().unwrap();
+        FileHandler::write(dir.path().join("a/one.txt"), "12345").unwrap();
+        FileHandler::write(dir.path().join("two.txt"), "12").unwrap();
+
+        let command = FindCommand::new(
+            vec![dir.path().to_path_buf()],
+            WalkOptions::default(),
+            false,
+            "json".into(),
+        )
+        .unwrap();
+        let report = command.report().unwrap();
+        assert_eq!(report["count"], 3);
+        assert_eq!(report["total_size"], 7);
+        assert_eq!(report["entries"][0]["relative"], "a");
+        assert_eq!(report["entries"][0]["type"], "directory");
+        assert_eq!(report["entries"][1]["relative"], "a/one.txt");
+        assert_eq!(report["entries"][1]["depth"], 2);
+        assert_eq!(report["entries"][1]["size"], 5);
+        assert_eq!(report["entries"][1]["is_file"], true);
+        assert!(report["entries"][1]["modified_at"].is_string());
+
+        let options = WalkOptions {
+            types: vec![EntryType::File],
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        let command = FindCommand::new(
+            vec![dir.path().to_path_buf()],
+            options,
+            false,
+            "json".into(),
+        )
+        .unwrap();
+        let report = command.report().unwrap();
+        assert_eq!(report["count"], 1);
+        assert_eq!(report["entries"][0]["relative"], "two.txt");
+    }
+
+    #[test]
+    fn test_rejects_unknown_output_format() {
+        let result = FindCommand::new(vec![], WalkOptions::default(), false, "yaml".into());
+        assert!(result.is_err());
+    }
+}
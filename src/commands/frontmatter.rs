@@ -0,0 +1,344 @@
+use crate::utils::file_handler::{FileHandler, SearchOptions};
+use crate::utils::front_matter::{self, FrontMatter};
+use crate::utils::glob;
+use crate::utils::ruby_json;
+use anyhow::{bail, Result};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+const MARKDOWN_GLOBS: &[&str] = &["*.md", "*.markdown"];
+
+pub enum FrontmatterAction {
+    List,
+    Get(String),
+    Set {
+        assignments: Vec<(String, Value)>,
+        unset: Vec<String>,
+        dry_run: bool,
+    },
+}
+
+impl FrontmatterAction {
+    /// Builds a `Set` action from `KEY=VALUE` arguments. Values are read as
+    /// YAML, so `draft=true` stores a boolean.
+    pub fn set(assignments: &[String], unset: Vec<String>, dry_run: bool) -> Result<Self> {
+        if assignments.is_empty() && unset.is_empty() {
+            bail!("Nothing to change: pass --set KEY=VALUE or --unset KEY");
+        }
+
+        let assignments = assignments
+            .iter()
+            .map(|assignment| match assignment.split_once('=') {
+                Some((key, value)) if !key.is_empty() => {
+                    Ok((key.to_string(), front_matter::parse_value(value)))
+                }
+                _ => bail!("Expected KEY=VALUE, got {:?}", assignment),
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(FrontmatterAction::Set {
+            assignments,
+            unset,
+            dry_run,
+        })
+    }
+}
+
+pub struct FrontmatterCommand {
+    action: FrontmatterAction,
+    paths: Vec<PathBuf>,
+    output_format: String,
+}
+
+impl FrontmatterCommand {
+    pub fn new(
+        action: FrontmatterAction,
+        paths: Vec<PathBuf>,
+        output_format: String,
+    ) -> Result<Self> {
+        if !matches!(output_format.as_str(), "console" | "json") {
+            bail!(
+                "Unknown output format: {:?} (expected console or json)",
+                output_format
+            );
+        }
+        let paths = if paths.is_empty() {
+            vec![PathBuf::from(".")]
+        } else {
+            paths
+        };
+
+        Ok(Self {
+            action,
+            paths,
+            output_format,
+        })
+    }
+
+    pub fn execute(&self) -> Result<()> {
+        let report = self.report()?;
+        if self.output_format == "json" {
+            println!("{}", ruby_json::pretty_generate(&report)?);
+            return Ok(());
+        }
+
+        match &self.action {
+            FrontmatterAction::List => {
+                for file in report["files"].as_array().into_iter().flatten() {
+                    println!("{}", file["path"].as_str().unwrap_or_default());
+                    if let Some(data) = file["front_matter"].as_object() {
+                        for (key, value) in data {
+                            println!("  {}: {}", key, display_value(value));
+                        }
+                    }
+                }
+            }
+            FrontmatterAction::Get(_) => {
+                for file in report["files"].as_array().into_iter().flatten() {
+                    println!(
+                        "{}: {}",
+                        file["path"].as_str().unwrap_or_default(),
+                        display_value(&file["value"])
+                    );
+                }
+            }
+            FrontmatterAction::Set { dry_run, .. } => {
+                let verb = if *dry_run { "Would update" } else { "Updated" };
+                let updated = report["updated"].as_array().cloned().unwrap_or_default();
+                for path in &updated {
+                    println!("{} {}", verb, path.as_str().unwrap_or_default());
+                }
+                println!(
+                    "{} {} of {} files",
+                    verb,
+                    updated.len(),
+                    report["files_checked"]
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The structured results behind `--output json` and `--template`.
+    /// For `set` this applies the changes (unless it is a dry run).
+    pub fn report(&self) -> Result<Value> {
+        let files = self.markdown_files()?;
+
+        match &self.action {
+            FrontmatterAction::List => {
+                let mut entries = Vec::new();
+                for path in &files {
+                    let doc = FileHandler::read_front_matter(path)?;
+                    entries.push(json!({
+                        "path": path.display().to_string(),
+                        "front_matter": doc.data,
+                    }));
+                }
+                Ok(json!({ "files": entries }))
+            }
+            FrontmatterAction::Get(key) => {
+                let mut entries = Vec::new();
+                for path in &files {
+                    let doc = FileHandler::read_front_matter(path)?;
+                    if let Some(value) = doc.get(key) {
+                        entries.push(json!({
+                            "path": path.display().to_string(),
+                            "value": value,
+                        }));
+                    }
+                }
+                Ok(json!({ "key": key, "files": entries }))
+            }
+            FrontmatterAction::Set {
+                assignments,
+                unset,
+                dry_run,
+            } => {
+                // Check every file before writing any, so a bad file can't
+                // leave the set half-applied.
+                let mut updated = Vec::new();
+                let mut errors = Vec::new();
+                for path in &files {
+                    let result = FileHandler::read_front_matter(path)
+                        .and_then(|mut doc| apply(&mut doc, assignments, unset));
+                    match result {
+                        Ok(true) => updated.push(path),
+                        Ok(false) => {}
+                        Err(e) => errors.push(format!("  {}: {:#}", path.display(), e)),
+                    }
+                }
+                if !errors.is_empty() {
+                    bail!(
+                        "No files were changed; {} of {} files could not be updated:\n{}",
+                        errors.len(),
+                        files.len(),
+                        errors.join("\n")
+                    );
+                }
+
+                if !*dry_run {
+                    for path in &updated {
+                        FileHandler::update_front_matter(path, |doc| {
+                            apply(doc, assignments, unset)
+                        })?;
+                    }
+                }
+                let updated: Vec<String> =
+                    updated.iter().map(|p| p.display().to_string()).collect();
+                Ok(json!({
+                    "dry_run": dry_run,
+                    "files_checked": files.len(),
+                    "updated": updated,
+                }))
+            }
+        }
+    }
+
+    /// Files named directly are used as-is; directories contribute their
+    /// Markdown files.
+    fn markdown_files(&self) -> Result<Vec<PathBuf>> {
+        let patterns: Vec<String> = MARKDOWN_GLOBS.iter().map(|g| g.to_string()).collect();
+        let options = SearchOptions {
+            include: glob::compile_all(&patterns)?,
+            ..Default::default()
+        };
+        FileHandler::list_files(&self.paths, &options)
+    }
+}
+
+/// Applies the edits and reports whether anything changed, so untouched
+/// files are never rewritten.
+fn apply(doc: &mut FrontMatter, assignments: &[(String, Value)], unset: &[String]) -> Result<bool> {
+    let before = doc.data.clone();
+    for (key, value) in assignments {
+        doc.set(key, value.clone())?;
+    }
+    for key in unset {
+        doc.remove(key);
+    }
+    Ok(doc.data != before)
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn docs() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        FileHandler::write(
+            dir.path().join("index.md"),
+            "---\ntitle: Home\ndraft: true\n---\n# Home\n",
+        )
+        .unwrap();
+        FileHandler::write(
+            dir.path().join("guides/setup.md"),
+            "---\ntitle: Setup\n---\nSteps.\n",
+        )
+        .unwrap();
+        FileHandler::write(dir.path().join("guides/notes.txt"), "---\nx: 1\n---\n").unwrap();
+        dir
+    }
+
+    fn run(dir: &TempDir, action: FrontmatterAction) -> Value {
+        FrontmatterCommand::new(action, vec![dir.path().to_path_buf()], "json".into())
+            .unwrap()
+            .report()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_list_and_get() {
+        let dir = docs();
+
+        let list = run(&dir, FrontmatterAction::List);
+        let files = list["files"].as_array().unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files[0]["path"].as_str().unwrap().ends_with("setup.md"));
+        assert_eq!(files[1]["front_matter"]["draft"], json!(true));
+
+        let get = run(&dir, FrontmatterAction::Get("draft".into()));
+        assert_eq!(get["files"].as_array().unwrap().len(), 1);
+        assert_eq!(get["files"][0]["value"], json!(true));
+    }
+
+    #[test]
+    fn test_bulk_set() {
+        let dir = docs();
+        let action = FrontmatterAction::set(
+            &["nav.section=guides".to_string(), "title=Home".to_string()],
+            vec!["draft".to_string()],
+            false,
+        )
+        .unwrap();
+
+        let report = run(&dir, action);
+        assert_eq!(report["files_checked"], json!(2));
+        assert_eq!(
+            fs::read_to_string(dir.path().join("index.md")).unwrap(),
+            "---\ntitle: Home\nnav:\n  section: guides\n---\n# Home\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("guides/setup.md")).unwrap(),
+            "---\ntitle: Home\nnav:\n  section: guides\n---\nSteps.\n"
+        );
+
+        // Re-running changes nothing.
+        let action = FrontmatterAction::set(&["title=Home".to_string()], vec![], false).unwrap();
+        assert_eq!(run(&dir, action)["updated"], json!([]));
+    }
+
+    #[test]
+    fn test_dry_run_and_bad_arguments() {
+        let dir = docs();
+        let action = FrontmatterAction::set(&["draft=false".to_string()], vec![], true).unwrap();
+
+        assert_eq!(run(&dir, action)["updated"].as_array().unwrap().len(), 2);
+        assert!(fs::read_to_string(dir.path().join("index.md"))
+            .unwrap()
+            .contains("draft: true"));
+
+        assert!(FrontmatterAction::set(&["novalue".to_string()], vec![], false).is_err());
+        assert!(FrontmatterAction::set(&[], vec![], false).is_err());
+    }
+
+    #[test]
+    fn test_bulk_set_changes_nothing_when_a_file_fails() {
+        let dir = docs();
+        FileHandler::write(
+            dir.path().join("guides/nav.md"),
+            "---\nnav: top\n---\nNav.\n",
+        )
+        .unwrap();
+        let before = fs::read_to_string(dir.path().join("index.md")).unwrap();
+        let action =
+            FrontmatterAction::set(&["nav.section=guides".to_string()], vec![], false).unwrap();
+
+        let error = FrontmatterCommand::new(action, vec![dir.path().to_path_buf()], "json".into())
+            .unwrap()
+            .report()
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("1 of 3 files"), "{}", error);
+        assert!(error.contains("nav.md"), "{}", error);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("index.md")).unwrap(),
+            before
+        );
+    }
+
+    #[test]
+    fn test_rejects_unknown_output_format() {
+        let result = FrontmatterCommand::new(FrontmatterAction::List, vec![], "yaml".into());
+        assert!(result.is_err());
+    }
+}
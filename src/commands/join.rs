@@ -0,0 +1,261 @@
+use crate::commands::split::Manifest;
+use crate::utils::byte_size;
+use crate::utils::file_handler::{FileError, FileHandler};
+use crate::utils::hashing::{HashAlgorithm, Hasher};
+use anyhow::{bail, Result};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+
+pub struct JoinCommand {
+    manifest: PathBuf,
+    output: Option<PathBuf>,
+    force: bool,
+}
+
+impl JoinCommand {
+    pub fn new(manifest: PathBuf, output: Option<PathBuf>, force: bool) -> Self {
+        Self {
+            manifest,
+            output,
+            force,
+        }
+    }
+
+    pub fn execute(&self) -> Result<()> {
+        let (output, manifest) = self.join()?;
+
+        println!("Verified {} chunks", manifest.chunks.len());
+        println!(
+            "Wrote {} ({})",
+            output.display(),
+            byte_size::format(manifest.size)
+        );
+
+        Ok(())
+    }
+
+    /// Verifies every chunk, then reassembles them. The output is written
+    /// with `FileHandler::atomic_write_with` and only committed once the
+    /// checksum of the whole file matches the manifest.
+    pub fn join(&self) -> Result<(PathBuf, Manifest)> {
+        let manifest: Manifest = FileHandler::read_json(&self.manifest)?;
+        let dir = self.manifest.parent().unwrap_or(Path::new(""));
+        let algorithm: HashAlgorithm = manifest.algorithm.parse()?;
+
+        check_file_name("source", &manifest.source)?;
+        for chunk in &manifest.chunks {
+            check_file_name("chunk", &chunk.file)?;
+        }
+        self.verify_chunks(&manifest, algorithm, dir)?;
+
+        let output = self
+            .output
+            .clone()
+            .unwrap_or_else(|| dir.join(&manifest.source));
+        if output.exists() && !self.force {
+            bail!(
+                "{} already exists (use --force to overwrite)",
+                output.display()
+            );
+        }
+
+        FileHandler::atomic_write_with(&output, |out| {
+            let checksum = write_joined(&manifest, dir, algorithm, out)?;
+            if checksum != manifest.checksum {
+                bail!(FileError::ChecksumMismatch(
+                    manifest.source.clone(),
+                    manifest.checksum.clone(),
+                    checksum
+                ));
+            }
+            Ok(())
+        })?;
+
+        Ok((output, manifest))
+    }
+
+    fn verify_chunks(
+        &self,
+        manifest: &Manifest,
+        algorithm: HashAlgorithm,
+        dir: &Path,
+    ) -> Result<()> {
+        let mut missing = Vec::new();
+        let mut corrupted = Vec::new();
+
+        for chunk in &manifest.chunks {
+            let path = dir.join(&chunk.file);
+            if !path.is_file() {
+                missing.push(chunk.file.as_str());
+                continue;
+            }
+
+            // Cheap size check first; only hash chunks that could match.
+            let intact = fs::metadata(&path)?.len() == chunk.size
+                && FileHandler::checksum(&path, algorithm)? == chunk.checksum;
+            if !intact {
+                corrupted.push(chunk.file.as_str());
+            }
+        }
+
+        if missing.is_empty() && corrupted.is_empty() {
+            return Ok(());
+        }
+
+        let mut problems = Vec::new();
+        if !missing.is_empty() {
+            problems.push(format!("missing chunks: {}", missing.join(", ")));
+        }
+        if !corrupted.is_empty() {
+            problems.push(format!("corrupted chunks: {}", corrupted.join(", ")));
+        }
+        bail!("Cannot join {}: {}", manifest.source, problems.join("; "))
+    }
+}
+
+/// Manifests name files next to themselves; anything else (an absolute
+/// path, a `..`, a subdirectory) could read or write outside that directory.
+fn check_file_name(kind: &str, name: &str) -> Result<()> {
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Ok(()),
+        _ => bail!(
+            "Invalid manifest: {} {:?} is not a plain file name",
+            kind,
+            name
+        ),
+    }
+}
+
+/// Copies the chunks into `out`, skipping repeated headers, and returns the
+/// checksum of everything written.
+fn write_joined(
+    manifest: &Manifest,
+    dir: &Path,
+    algorithm: HashAlgorithm,
+    out: &mut dyn Write,
+) -> Result<String> {
+    let mut hasher = Hasher::new(algorithm);
+    let mut buffer = vec![0u8; 64 * 1024];
+
+    for (index, chunk) in manifest.chunks.iter().enumerate() {
+        let mut input = File::open(dir.join(&chunk.file))?;
+        if index > 0 {
+            input.seek(SeekFrom::Start(manifest.header_bytes))?;
+        }
+        loop {
+            let read = input.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+            out.write_all(&buffer[..read])?;
+        }
+    }
+
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::split::{SplitCommand, SplitMode};
+    use tempfile::TempDir;
+
+    fn split(dir: &TempDir, content: &str, mode: SplitMode, limit: u64) -> PathBuf {
+        let path = dir.path().join("export.csv");
+        fs::write(&path, content).unwrap();
+        SplitCommand::new(path, mode, limit, Some(dir.path().join("parts")))
+            .split()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let csv = "id,name\n1,a\n2,\"b\nc\"\n3,d\n4,e";
+
+        for (mode, limit) in [
+            (SplitMode::Bytes, 5),
+            (SplitMode::Lines, 2),
+            (SplitMode::Records, 1),
+        ] {
+            let manifest = split(&dir, csv, mode, limit);
+            let output = dir.path().join(format!("joined-{:?}.csv", mode));
+
+            JoinCommand::new(manifest, Some(output.clone()), false)
+                .join()
+                .unwrap();
+            assert_eq!(fs::read_to_string(&output).unwrap(), csv, "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn test_detects_missing_and_corrupted_chunks() {
+        let dir = TempDir::new().unwrap();
+        let manifest = split(&dir, "a\nb\nc\nd\n", SplitMode::Lines, 1);
+        let parts = dir.path().join("parts");
+        fs::remove_file(parts.join("export.002.csv")).unwrap();
+        fs::write(parts.join("export.004.csv"), "x\n").unwrap();
+
+        let output = dir.path().join("out.csv");
+        let error = JoinCommand::new(manifest, Some(output.clone()), false)
+            .join()
+            .unwrap_err()
+            .to_string();
+
+        assert_eq!(
+            error,
+            "Cannot join export.csv: missing chunks: export.002.csv; \
+             corrupted chunks: export.004.csv"
+        );
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn test_rejects_paths_outside_the_manifest_directory() {
+        let dir = TempDir::new().unwrap();
+        let manifest_path = split(&dir, "a\nb\n", SplitMode::Lines, 1);
+        let original = FileHandler::read(&manifest_path).unwrap();
+
+        for (source, chunk) in [
+            ("../escaped.csv", None),
+            ("/tmp/escaped.csv", None),
+            ("export.csv", Some("../export.001.csv")),
+            ("export.csv", Some("sub/export.001.csv")),
+        ] {
+            let mut manifest: Manifest = serde_json::from_str(&original).unwrap();
+            manifest.source = source.to_string();
+            if let Some(chunk) = chunk {
+                manifest.chunks[0].file = chunk.to_string();
+            }
+            FileHandler::write_json(&manifest_path, &manifest, true).unwrap();
+
+            let error = JoinCommand::new(manifest_path.clone(), None, false)
+                .join()
+                .unwrap_err()
+                .to_string();
+            assert!(error.starts_with("Invalid manifest"), "{}", error);
+        }
+        assert!(!dir.path().join("escaped.csv").exists());
+    }
+
+    #[test]
+    fn test_refuses_to_overwrite() {
+        let dir = TempDir::new().unwrap();
+        let manifest = split(&dir, "data", SplitMode::Bytes, 2);
+        let output = dir.path().join("existing");
+        fs::write(&output, "keep").unwrap();
+
+        assert!(
+            JoinCommand::new(manifest.clone(), Some(output.clone()), false)
+                .join()
+                .is_err()
+        );
+        JoinCommand::new(manifest, Some(output.clone()), true)
+            .join()
+            .unwrap();
+        assert_eq!(fs::read_to_string(output).unwrap(), "data");
+    }
+}
@@ -0,0 +1,617 @@
+use crate::utils::csv_dialect::CsvOptions;
+use crate::utils::file_handler::{FileError, FileHandler};
+use crate::utils::ruby_json;
+use crate::utils::table::{pad, truncate, Alignment, Table, TableFormat};
+use crate::utils::xlsx::{self, SheetOptions};
+use anyhow::{bail, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const HISTOGRAM_WIDTH: usize = 30;
+
+pub struct ProfileCommand {
+    file: PathBuf,
+    output_format: String,
+    top: usize,
+    bins: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    Int,
+    Float,
+    Bool,
+    Date,
+    String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ColumnProfile {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub column_type: ColumnType,
+    pub count: usize,
+    pub null_count: usize,
+    pub empty_count: usize,
+    pub distinct_count: usize,
+    pub min: Option<Value>,
+    pub max: Option<Value>,
+    pub mean: Option<f64>,
+    pub median: Option<f64>,
+    pub top_values: Vec<ValueCount>,
+    pub histogram: Vec<HistogramBin>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValueCount {
+    pub value: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistogramBin {
+    pub start: f64,
+    pub end: f64,
+    pub count: usize,
+}
+
+#[derive(Debug)]
+struct Dataset {
+    columns: Vec<String>,
+    rows: Vec<HashMap<String, Option<String>>>,
+}
+
+impl ProfileCommand {
+    pub fn new(file: PathBuf, output_format: String, top: usize, bins: usize) -> Result<Self> {
+        if !matches!(
+            output_format.as_str(),
+            "console" | "json" | "markdown" | "html"
+        ) {
+            bail!(
+                "Unknown output format: {:?} (expected console, json, markdown or html)",
+                output_format
+            );
+        }
+
+        Ok(Self {
+            file,
+            output_format,
+            top,
+            bins: bins.max(1),
+        })
+    }
+
+    /// The structured profile behind `--output json` and `--template`.
+    pub fn report(&self) -> Result<Value> {
+        let dataset = load_dataset(&self.file)?;
+        Ok(self.profile_json(&dataset, &self.profile(&dataset)))
+    }
+
+    pub fn execute(&self) -> Result<()> {
+        let dataset = load_dataset(&self.file)?;
+        let profiles = self.profile(&dataset);
+
+        match self.output_format.as_str() {
+            "json" => self.output_json(&dataset, &profiles)?,
+            "markdown" | "html" => {
+                let format: TableFormat = self.output_format.parse()?;
+                println!("{}", summary_table(&profiles).render(format));
+            }
+            _ => self.output_console(&dataset, &profiles),
+        }
+
+        Ok(())
+    }
+
+    fn profile(&self, dataset: &Dataset) -> Vec<ColumnProfile> {
+        dataset
+            .columns
+            .iter()
+            .map(|column| {
+                let values: Vec<Option<&str>> = dataset
+                    .rows
+                    .iter()
+                    .map(|row| row.get(column).and_then(|v| v.as_deref()))
+                    .collect();
+                self.profile_column(column, &values)
+            })
+            .collect()
+    }
+
+    fn profile_column(&self, name: &str, values: &[Option<&str>]) -> ColumnProfile {
+        let null_count = values.iter().filter(|v| v.is_none()).count();
+        let present: Vec<&str> = values.iter().flatten().copied().collect();
+        let empty_count = present.iter().filter(|v| v.trim().is_empty()).count();
+        let filled: Vec<&str> = present
+            .iter()
+            .copied()
+            .filter(|v| !v.trim().is_empty())
+            .collect();
+
+        let column_type = infer_type(&filled);
+
+        let mut frequencies: HashMap<&str, usize> = HashMap::new();
+        for value in &filled {
+            *frequencies.entry(value).or_insert(0) += 1;
+        }
+        let distinct_count = frequencies.len();
+
+        let mut top_values: Vec<ValueCount> = frequencies
+            .into_iter()
+            .map(|(value, count)| ValueCount {
+                value: value.to_string(),
+                count,
+            })
+            .collect();
+        top_values.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        top_values.truncate(self.top);
+
+        let mut profile = ColumnProfile {
+            name: name.to_string(),
+            column_type,
+            count: values.len(),
+            null_count,
+            empty_count,
+            distinct_count,
+            min: None,
+            max: None,
+            mean: None,
+            median: None,
+            top_values,
+            histogram: Vec::new(),
+        };
+
+        match column_type {
+            ColumnType::Int | ColumnType::Float => {
+                let mut numbers: Vec<f64> = filled
+                    .iter()
+                    .filter_map(|v| v.trim().parse::<f64>().ok())
+                    .collect();
+                numbers.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+                if let (Some(&min), Some(&max)) = (numbers.first(), numbers.last()) {
+                    // Integers keep their exact value rather than going
+                    // through f64.
+                    let ints: Vec<i128> = filled.iter().filter_map(|v| parse_int(v)).collect();
+                    match (ints.iter().min(), ints.iter().max()) {
+                        (Some(&min), Some(&max)) if column_type == ColumnType::Int => {
+                            profile.min = Some(int_value(min));
+                            profile.max = Some(int_value(max));
+                        }
+                        _ => {
+                            profile.min = Some(json!(min));
+                            profile.max = Some(json!(max));
+                        }
+                    }
+                    profile.mean = Some(numbers.iter().sum::<f64>() / numbers.len() as f64);
+                    profile.median = Some(median(&numbers));
+                    profile.histogram = histogram(&numbers, self.bins);
+                }
+            }
+            ColumnType::Date => {
+                let mut dates: Vec<(NaiveDateTime, &str)> = filled
+                    .iter()
+                    .filter_map(|v| parse_date(v).map(|d| (d, *v)))
+                    .collect();
+                dates.sort_by_key(|(date, _)| *date);
+                profile.min = dates.first().map(|(_, v)| json!(v));
+                profile.max = dates.last().map(|(_, v)| json!(v));
+            }
+            ColumnType::Bool | ColumnType::String => {
+                profile.min = filled.iter().min().map(|v| json!(v));
+                profile.max = filled.iter().max().map(|v| json!(v));
+            }
+        }
+
+        profile
+    }
+
+    fn output_console(&self, dataset: &Dataset, profiles: &[ColumnProfile]) {
+        println!("File:     {}", self.file.display());
+        println!("Rows:     {}", dataset.rows.len());
+        println!("Columns:  {}", dataset.columns.len());
+        println!();
+        println!("{}", summary_table(profiles).render_console());
+
+        for profile in profiles {
+            if profile.top_values.is_empty() && profile.histogram.is_empty() {
+                continue;
+            }
+
+            println!("\n{} ({:?})", profile.name, profile.column_type);
+
+            if !profile.top_values.is_empty() {
+                println!("  Top values:");
+                for top in &profile.top_values {
+                    println!(
+                        "    {} {}",
+                        pad(&truncate(&top.value, 30), 30, Alignment::Left),
+                        top.count
+                    );
+                }
+            }
+
+            if !profile.histogram.is_empty() {
+                println!("  Histogram:");
+                let peak = profile.histogram.iter().map(|b| b.count).max().unwrap_or(0);
+                for bin in &profile.histogram {
+                    let width = if peak == 0 {
+                        0
+                    } else {
+                        (bin.count * HISTOGRAM_WIDTH).div_ceil(peak)
+                    };
+                    println!(
+                        "    [{:>12.2}, {:>12.2}) {:<width$} {}",
+                        bin.start,
+                        bin.end,
+                        "█".repeat(width),
+                        bin.count,
+                        width = HISTOGRAM_WIDTH
+                    );
+                }
+            }
+        }
+    }
+
+    fn profile_json(&self, dataset: &Dataset, profiles: &[ColumnProfile]) -> Value {
+        json!({
+            "file": self.file.display().to_string(),
+            "rows": dataset.rows.len(),
+            "columns": profiles,
+        })
+    }
+
+    fn output_json(&self, dataset: &Dataset, profiles: &[ColumnProfile]) -> Result<()> {
+        let output = self.profile_json(dataset, profiles);
+        println!("{}", ruby_json::pretty_generate(&output)?);
+        Ok(())
+    }
+}
+
+fn load_dataset(path: &Path) -> Result<Dataset> {
+    if !path.exists() {
+        bail!(FileError::NotFound(path.display().to_string()));
+    }
+
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    match extension.as_str() {
+        "csv" | "tsv" | "tab" => load_csv(path),
+        "json" => load_json(path),
+        "xlsx" => load_xlsx(path),
+        _ => bail!(FileError::UnsupportedFormat(path.display().to_string())),
+    }
+}
+
+/// Reads CSV or TSV with the delimiter sniffed, so semicolon-separated
+/// exports profile correctly too.
+fn load_csv(path: &Path) -> Result<Dataset> {
+    let table = FileHandler::read_csv_table_with(path, &CsvOptions::detect(path)?)?;
+    let rows = table
+        .rows
+        .into_iter()
+        .map(|row| {
+            table
+                .headers
+                .iter()
+                .cloned()
+                .zip(row.into_iter().map(Some))
+                .collect()
+        })
+        .collect();
+
+    Ok(Dataset {
+        columns: table.headers,
+        rows,
+    })
+}
+
+fn load_xlsx(path: &Path) -> Result<Dataset> {
+    let bytes = std::fs::read(path)?;
+    let sheet = xlsx::read_sheet(&bytes, &SheetOptions::default())
+        .map_err(|e| FileError::InvalidXlsx(format!("{:#}", e)))?;
+
+    let rows = sheet
+        .rows
+        .into_iter()
+        .map(|row| {
+            sheet
+                .headers
+                .iter()
+                .cloned()
+                .zip(row.into_iter().map(|v| (!v.is_empty()).then_some(v)))
+                .collect()
+        })
+        .collect();
+
+    Ok(Dataset {
+        columns: sheet.headers,
+        rows,
+    })
+}
+
+fn load_json(path: &Path) -> Result<Dataset> {
+    let content = std::fs::read_to_string(path)?;
+    let data: Value =
+        serde_json::from_str(&content).map_err(|e| FileError::InvalidJson(e.to_string()))?;
+
+    let Some(items) = data.as_array() else {
+        bail!(FileError::InvalidJson(
+            "expected an array of objects".to_string()
+        ));
+    };
+
+    let mut columns: Vec<String> = Vec::new();
+    let mut rows = Vec::new();
+    for item in items {
+        let Some(object) = item.as_object() else {
+            bail!(FileError::InvalidJson(
+                "expected an array of objects".to_string()
+            ));
+        };
+
+        let mut row = HashMap::new();
+        for (key, value) in object {
+            if !columns.contains(key) {
+                columns.push(key.clone());
+            }
+            let cell = match value {
+                Value::Null => None,
+                Value::String(s) => Some(s.clone()),
+                other => Some(other.to_string()),
+            };
+            row.insert(key.clone(), cell);
+        }
+        rows.push(row);
+    }
+
+    Ok(Dataset { columns, rows })
+}
+
+fn infer_type(values: &[&str]) -> ColumnType {
+    if values.is_empty() {
+        return ColumnType::String;
+    }
+
+    let all = |f: fn(&str) -> bool| values.iter().all(|v| f(v.trim()));
+
+    if all(|v| parse_int(v).is_some()) {
+        ColumnType::Int
+    } else if all(|v| v.parse::<f64>().is_ok_and(f64::is_finite)) {
+        ColumnType::Float
+    } else if all(|v| v.eq_ignore_ascii_case("true") || v.eq_ignore_ascii_case("false")) {
+        ColumnType::Bool
+    } else if all(|v| parse_date(v).is_some()) {
+        ColumnType::Date
+    } else {
+        ColumnType::String
+    }
+}
+
+fn parse_date(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return date.and_hms_opt(0, 0, 0);
+    }
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.naive_utc());
+    }
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok()
+}
+
+/// Parses anything that fits an i64 or a u64.
+fn parse_int(value: &str) -> Option<i128> {
+    let value = value.trim();
+    value
+        .parse::<i64>()
+        .map(i128::from)
+        .or_else(|_| value.parse::<u64>().map(i128::from))
+        .ok()
+}
+
+fn int_value(n: i128) -> Value {
+    match i64::try_from(n) {
+        Ok(n) => json!(n),
+        Err(_) => json!(n as u64),
+    }
+}
+
+fn median(sorted: &[f64]) -> f64 {
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+fn histogram(sorted: &[f64], bins: usize) -> Vec<HistogramBin> {
+    let (Some(&min), Some(&max)) = (sorted.first(), sorted.last()) else {
+        return Vec::new();
+    };
+
+    if min == max {
+        return vec![HistogramBin {
+            start: min,
+            end: max,
+            count: sorted.len(),
+        }];
+    }
+
+    let width = (max - min) / bins as f64;
+    let mut result: Vec<HistogramBin> = (0..bins)
+        .map(|i| HistogramBin {
+            start: min + width * i as f64,
+            end: min + width * (i + 1) as f64,
+            count: 0,
+        })
+        .collect();
+
+    for value in sorted {
+        let index = (((value - min) / width) as usize).min(bins - 1);
+        result[index].count += 1;
+    }
+
+    result
+}
+
+fn summary_table(profiles: &[ColumnProfile]) -> Table {
+    let mut table = Table::new(vec![
+        "Column", "Type", "Count", "Nulls", "Empty", "Distinct", "Min", "Max", "Mean", "Median",
+    ]);
+    for column in 2..=5 {
+        table = table.with_alignment(column, Alignment::Right);
+    }
+    for column in 8..=9 {
+        table = table.with_alignment(column, Alignment::Right);
+    }
+
+    let float = |v: Option<f64>| v.map(|v| format!("{:.4}", v)).unwrap_or_default();
+    for profile in profiles {
+        table.add_row(vec![
+            profile.name.clone(),
+            format!("{:?}", profile.column_type).to_lowercase(),
+            profile.count.to_string(),
+            profile.null_count.to_string(),
+            profile.empty_count.to_string(),
+            profile.distinct_count.to_string(),
+            profile.min.as_ref().map(display_value).unwrap_or_default(),
+            profile.max.as_ref().map(display_value).unwrap_or_default(),
+            float(profile.mean),
+            float(profile.median),
+        ]);
+    }
+
+    table
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_handler::FileHandler;
+    use tempfile::TempDir;
+
+    fn command() -> ProfileCommand {
+        ProfileCommand::new(PathBuf::from("unused"), "console".to_string(), 3, 4).unwrap()
+    }
+
+    #[test]
+    fn test_infer_type() {
+        assert_eq!(infer_type(&["1", "2", "-3"]), ColumnType::Int);
+        assert_eq!(infer_type(&["1", "2.5"]), ColumnType::Float);
+        assert_eq!(infer_type(&["true", "FALSE"]), ColumnType::Bool);
+        assert_eq!(
+            infer_type(&["2025-01-15", "2025-01-15T10:00:00Z"]),
+            ColumnType::Date
+        );
+        assert_eq!(infer_type(&["1", "abc"]), ColumnType::String);
+        assert_eq!(infer_type(&[]), ColumnType::String);
+    }
+
+    #[test]
+    fn test_numeric_column_profile() {
+        let values = [Some("1"), Some("2"), Some("2"), Some("5"), None, Some("")];
+        let profile = command().profile_column("n", &values);
+
+        assert_eq!(profile.column_type, ColumnType::Int);
+        assert_eq!(profile.count, 6);
+        assert_eq!(profile.null_count, 1);
+        assert_eq!(profile.empty_count, 1);
+        assert_eq!(profile.distinct_count, 3);
+        assert_eq!(profile.min, Some(json!(1)));
+        assert_eq!(profile.max, Some(json!(5)));
+        assert_eq!(profile.mean, Some(2.5));
+        assert_eq!(profile.median, Some(2.0));
+        assert_eq!(profile.top_values[0].value, "2");
+        assert_eq!(profile.top_values[0].count, 2);
+        assert_eq!(profile.histogram.len(), 4);
+        assert_eq!(profile.histogram.iter().map(|b| b.count).sum::<usize>(), 4);
+    }
+
+    #[test]
+    fn test_large_integers_stay_exact() {
+        let values = [Some("9007199254740993"), Some("-9007199254740993")];
+        let profile = command().profile_column("id", &values);
+        assert_eq!(profile.column_type, ColumnType::Int);
+        assert_eq!(profile.min, Some(json!(-9007199254740993i64)));
+        assert_eq!(profile.max, Some(json!(9007199254740993i64)));
+
+        let profile = command().profile_column("n", &[Some("1"), Some("18446744073709551615")]);
+        assert_eq!(profile.column_type, ColumnType::Int);
+        assert_eq!(profile.max, Some(json!(u64::MAX)));
+    }
+
+    #[test]
+    fn test_date_column_profile() {
+        let values = [Some("2025-03-01"), Some("2024-12-31"), Some("2025-01-15")];
+        let profile = command().profile_column("d", &values);
+
+        assert_eq!(profile.column_type, ColumnType::Date);
+        assert_eq!(profile.min, Some(json!("2024-12-31")));
+        assert_eq!(profile.max, Some(json!("2025-03-01")));
+        assert!(profile.mean.is_none());
+        assert!(profile.histogram.is_empty());
+    }
+
+    #[test]
+    fn test_load_csv_keeps_header_order() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.csv");
+        FileHandler::write(&path, "zeta,alpha\n1,a\n2,b\n").unwrap();
+
+        let dataset = load_dataset(&path).unwrap();
+        assert_eq!(dataset.columns, vec!["zeta", "alpha"]);
+        assert_eq!(dataset.rows.len(), 2);
+    }
+
+    #[test]
+    fn test_load_json_array() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.json");
+        FileHandler::write(&path, r#"[{"a": 1, "b": null}, {"a": 2.5, "c": true}]"#).unwrap();
+
+        let dataset = load_dataset(&path).unwrap();
+        assert_eq!(dataset.columns.len(), 3);
+        assert_eq!(dataset.rows[0].get("b"), Some(&None));
+        assert!(!dataset.rows[1].contains_key("b"));
+    }
+
+    #[test]
+    fn test_load_rejects_non_array_json() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.json");
+        FileHandler::write(&path, r#"{"a": 1}"#).unwrap();
+
+        assert!(load_dataset(&path).is_err());
+    }
+
+    #[test]
+    fn test_execute_outputs() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("data.csv");
+        FileHandler::write(&path, "id,name,score\n1,Ann,9.5\n2,Bob,7\n3,,8\n").unwrap();
+
+        for format in ["console", "json", "markdown", "html"] {
+            let cmd = ProfileCommand::new(path.clone(), format.to_string(), 5, 10).unwrap();
+            assert!(cmd.execute().is_ok());
+        }
+        assert!(ProfileCommand::new(path, "yaml".to_string(), 5, 10).is_err());
+    }
+}
@@ -0,0 +1,322 @@
+use crate::utils::file_handler::{FileHandler, FileMatches, SearchOptions, SearchResults};
+use crate::utils::ruby_json;
+use crate::utils::table::color_enabled;
+use anyhow::{bail, Result};
+use colored::*;
+use regex::{Regex, RegexBuilder};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+pub struct SearchCommand {
+    regex: Regex,
+    paths: Vec<PathBuf>,
+    options: SearchOptions,
+    count: bool,
+    output_format: String,
+    color: bool,
+}
+
+impl SearchCommand {
+    pub fn new(
+        pattern: &str,
+        ignore_case: bool,
+        paths: Vec<PathBuf>,
+        options: SearchOptions,
+        count: bool,
+        output_format: String,
+        color: &str,
+    ) -> Result<Self> {
+        if !matches!(output_format.as_str(), "console" | "json") {
+            bail!(
+                "Unknown output format: {:?} (expected console or json)",
+                output_format
+            );
+        }
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(ignore_case)
+            .build()?;
+        let paths = if paths.is_empty() {
+            vec![PathBuf::from(".")]
+        } else {
+            paths
+        };
+        let color = color_enabled(color);
+
+        Ok(Self {
+            regex,
+            paths,
+            options,
+            count,
+            output_format,
+            color,
+        })
+    }
+
+    /// Prints the matches and returns whether any path couldn't be read,
+    /// so the caller can exit non-zero like grep.
+    pub fn execute(&self) -> Result<bool> {
+        if self.color {
+            colored::control::set_override(true);
+        }
+
+        let results = FileHandler::search(&self.paths, &self.regex, &self.options)?;
+
+        let output = match (self.output_format.as_str(), self.count) {
+            ("json", _) => ruby_json::pretty_generate(&self.json_report(&results))? + "\n",
+            (_, true) => self.render_counts(&results),
+            _ => self.render_matches(&results),
+        };
+        print!("{}", output);
+        for error in &results.errors {
+            eprintln!("search: {}", error);
+        }
+
+        Ok(!results.errors.is_empty())
+    }
+
+    /// The structured results behind `--output json` and `--template`.
+    pub fn report(&self) -> Result<Value> {
+        let results = FileHandler::search(&self.paths, &self.regex, &self.options)?;
+        Ok(self.json_report(&results))
+    }
+
+    fn render_counts(&self, results: &SearchResults) -> String {
+        results
+            .files
+            .iter()
+            .map(|file| format!("{}:{}\n", self.path_label(file), file.matches.len()))
+            .collect()
+    }
+
+    fn render_matches(&self, results: &SearchResults) -> String {
+        let mut out = String::new();
+        let mut first_block = true;
+
+        for file in &results.files {
+            let path = self.path_label(file);
+            // Last line number printed for this file, so overlapping context
+            // is only shown once.
+            let mut printed = 0;
+
+            for hit in &file.matches {
+                let first_line = hit.line_number - hit.before.len();
+                if self.options.context > 0 && (printed == 0 || first_line > printed + 1) {
+                    if !first_block {
+                        out.push_str("--\n");
+                    }
+                    first_block = false;
+                }
+
+                for (offset, line) in hit.before.iter().enumerate() {
+                    let number = first_line + offset;
+                    if number > printed {
+                        out.push_str(&format!("{}-{}-{}\n", path, self.line_label(number), line));
+                    }
+                }
+
+                out.push_str(&format!(
+                    "{}:{}:{}:{}\n",
+                    path,
+                    self.line_label(hit.line_number),
+                    hit.column,
+                    self.highlight(&hit.line, &hit.ranges)
+                ));
+
+                for (offset, line) in hit.after.iter().enumerate() {
+                    let number = hit.line_number + 1 + offset;
+                    out.push_str(&format!("{}-{}-{}\n", path, self.line_label(number), line));
+                }
+                printed = hit.line_number + hit.after.len();
+            }
+        }
+
+        out
+    }
+
+    fn json_report(&self, results: &SearchResults) -> Value {
+        let files: Vec<Value> = results
+            .files
+            .iter()
+            .map(|file| {
+                let path = file.path.display().to_string();
+                if self.count {
+                    return json!({ "path": path, "count": file.matches.len() });
+                }
+
+                let matches: Vec<Value> = file
+                    .matches
+                    .iter()
+                    .map(|hit| {
+                        let submatches: Vec<Value> = hit
+                            .ranges
+                            .iter()
+                            .map(|&(start, end)| {
+                                json!({ "start": start, "end": end, "text": &hit.line[start..end] })
+                            })
+                            .collect();
+                        json!({
+                            "line": hit.line_number,
+                            "column": hit.column,
+                            "text": hit.line,
+                            "submatches": submatches,
+                            "before": hit.before,
+                            "after": hit.after,
+                        })
+                    })
+                    .collect();
+
+                json!({ "path": path, "matches": matches })
+            })
+            .collect();
+
+        json!({
+            "pattern": self.regex.as_str(),
+            "files_searched": results.files_searched,
+            "files_matched": results.files.len(),
+            "binary_skipped": results.binary_skipped,
+            "total_matches": results.files.iter().map(|f| f.matches.len()).sum::<usize>(),
+            "results": files,
+            "errors": results.errors,
+        })
+    }
+
+    fn highlight(&self, line: &str, ranges: &[(usize, usize)]) -> String {
+        if !self.color {
+            return line.to_string();
+        }
+
+        let mut out = String::new();
+        let mut last = 0;
+        for &(start, end) in ranges {
+            out.push_str(&line[last..start]);
+            out.push_str(&line[start..end].red().bold().to_string());
+            last = end;
+        }
+        out.push_str(&line[last..]);
+        out
+    }
+
+    fn path_label(&self, file: &FileMatches) -> String {
+        let path = file.path.display().to_string();
+        if self.color {
+            path.magenta().to_string()
+        } else {
+            path
+        }
+    }
+
+    fn line_label(&self, number: usize) -> String {
+        if self.color {
+            number.to_string().green().to_string()
+        } else {
+            number.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::glob;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn command(dir: &TempDir, pattern: &str, context: usize, count: bool) -> SearchCommand {
+        SearchCommand::new(
+            pattern,
+            false,
+            vec![dir.path().to_path_buf()],
+            SearchOptions {
+                include: Vec::new(),
+                exclude: glob::compile_all(&["*.log".to_string()]).unwrap(),
+                context,
+            },
+            count,
+            "console".to_string(),
+            "never",
+        )
+        .unwrap()
+    }
+
+    fn fixture() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("a.txt"),
+            "one\ntwo\nthree\nfour\nfive\nsix\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("skip.log"), "two\n").unwrap();
+        fs::write(dir.path().join("bin.dat"), b"two\0\x01\x02").unwrap();
+        dir
+    }
+
+    fn search(command: &SearchCommand) -> SearchResults {
+        FileHandler::search(&command.paths, &command.regex, &command.options).unwrap()
+    }
+
+    #[test]
+    fn test_matches_with_columns() {
+        let dir = fixture();
+        let command = command(&dir, "o", 0, false);
+
+        let results = search(&command);
+        assert_eq!(results.binary_skipped, 1);
+        let output = command.render_matches(&results);
+        let path = dir.path().join("a.txt").display().to_string();
+        assert_eq!(
+            output,
+            format!("{p}:1:1:one\n{p}:2:3:two\n{p}:4:2:four\n", p = path)
+        );
+    }
+
+    #[test]
+    fn test_context_lines() {
+        let dir = fixture();
+        let command = command(&dir, "^(two|six)$", 1, false);
+
+        let output = command.render_matches(&search(&command));
+        let lines: Vec<&str> = output
+            .lines()
+            .map(|l| l.rsplit(['/', '\\']).next().unwrap())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "a.txt-1-one",
+                "a.txt:2:1:two",
+                "a.txt-3-three",
+                "--",
+                "a.txt-5-five",
+                "a.txt:6:1:six"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_count_and_json() {
+        let dir = fixture();
+        let command = command(&dir, "e", 0, true);
+
+        let results = search(&command);
+        assert!(command.render_counts(&results).ends_with("a.txt:3\n"));
+
+        let report = command.json_report(&results);
+        assert_eq!(report["files_matched"], 1);
+        assert_eq!(report["total_matches"], 3);
+        assert_eq!(report["results"][0]["count"], 3);
+    }
+
+    #[test]
+    fn test_rejects_unknown_output_format() {
+        let result = SearchCommand::new(
+            "x",
+            false,
+            vec![],
+            SearchOptions::default(),
+            false,
+            "yaml".to_string(),
+            "never",
+        );
+        assert!(result.is_err());
+    }
+}
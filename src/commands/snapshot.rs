@@ -0,0 +1,436 @@
+use crate::utils::file_handler::{FileError, FileHandler, SearchOptions};
+use crate::utils::glob;
+use crate::utils::hashing::{HashAlgorithm, Hasher};
+use crate::utils::ruby_json;
+use anyhow::{bail, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+const SNAPSHOT_VERSION: u32 = 1;
+const HASH_ALGORITHM: HashAlgorithm = HashAlgorithm::Sha256;
+
+/// The recorded state of a directory tree, written by `snapshot create`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub created_at: String,
+    pub algorithm: String,
+    /// Root of a Merkle tree over the directory structure: equal roots mean
+    /// identical paths, modes and contents.
+    pub merkle_root: String,
+    pub file_count: usize,
+    pub total_size: u64,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    pub files: Vec<SnapshotFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    /// Relative to the snapshot root, always with `/` separators.
+    pub path: String,
+    pub size: u64,
+    pub mode: String,
+    pub mtime: String,
+    pub hash: String,
+}
+
+/// Differences between a snapshot and the directory as it is now.
+#[derive(Debug, Default, PartialEq)]
+pub struct Drift {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+    /// Path with the recorded and current mode.
+    pub mode_changed: Vec<(String, String, String)>,
+}
+
+impl Drift {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && self.mode_changed.is_empty()
+    }
+}
+
+pub enum SnapshotAction {
+    /// Write the snapshot to a file, or stdout when `None`.
+    Create {
+        output: Option<PathBuf>,
+        exclude: Vec<String>,
+    },
+    Verify {
+        snapshot: PathBuf,
+        output_format: String,
+    },
+}
+
+pub struct SnapshotCommand {
+    dir: PathBuf,
+    action: SnapshotAction,
+}
+
+impl SnapshotCommand {
+    pub fn new(dir: PathBuf, action: SnapshotAction) -> Self {
+        Self { dir, action }
+    }
+
+    /// Returns `true` when `verify` found drift.
+    pub fn execute(&self) -> Result<bool> {
+        match &self.action {
+            SnapshotAction::Create { output, exclude } => {
+                let snapshot = create(&self.dir, exclude, output.as_deref())?;
+                match output {
+                    Some(path) => {
+                        FileHandler::write_json(path, &snapshot, true)?;
+                        println!(
+                            "Recorded {} files ({} bytes) in {}",
+                            snapshot.file_count,
+                            snapshot.total_size,
+                            path.display()
+                        );
+                        println!("Merkle root: {}", snapshot.merkle_root);
+                    }
+                    None => println!("{}", ruby_json::pretty_generate(&snapshot)?),
+                }
+                Ok(false)
+            }
+            SnapshotAction::Verify {
+                snapshot,
+                output_format,
+            } => {
+                let report = self.verify_report(snapshot)?;
+                if output_format == "json" {
+                    println!("{}", ruby_json::pretty_generate(&report)?);
+                } else {
+                    print!("{}", render_report(&report));
+                }
+                Ok(report["matches"] == false)
+            }
+        }
+    }
+
+    /// The structured results behind `--template`.
+    pub fn report(&self) -> Result<Value> {
+        match &self.action {
+            SnapshotAction::Create { output, exclude } => {
+                let snapshot = create(&self.dir, exclude, output.as_deref())?;
+                if let Some(path) = output {
+                    FileHandler::write_json(path, &snapshot, true)?;
+                }
+                Ok(serde_json::to_value(snapshot)?)
+            }
+            SnapshotAction::Verify { snapshot, .. } => self.verify_report(snapshot),
+        }
+    }
+
+    fn verify_report(&self, snapshot_path: &Path) -> Result<Value> {
+        let snapshot: Snapshot = FileHandler::read_json(snapshot_path)?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            bail!(
+                "Unsupported snapshot version {} in {}",
+                snapshot.version,
+                snapshot_path.display()
+            );
+        }
+
+        let current = create(&self.dir, &snapshot.exclude, Some(snapshot_path))?;
+        let drift = compare(&snapshot, &current);
+
+        Ok(json!({
+            "dir": self.dir.display().to_string(),
+            "snapshot": snapshot_path.display().to_string(),
+            "matches": drift.is_empty() && snapshot.merkle_root == current.merkle_root,
+            "expected_root": snapshot.merkle_root,
+            "actual_root": current.merkle_root,
+            "file_count": current.file_count,
+            "added": drift.added,
+            "removed": drift.removed,
+            "modified": drift.modified,
+            "mode_changed": drift
+                .mode_changed
+                .iter()
+                .map(|(path, from, to)| json!({"path": path, "from": from, "to": to}))
+                .collect::<Vec<_>>(),
+        }))
+    }
+}
+
+/// Records every file under `dir`. `skip` (the snapshot file itself, when
+/// it's written inside the tree) is left out.
+pub fn create(dir: &Path, exclude: &[String], skip: Option<&Path>) -> Result<Snapshot> {
+    if !dir.is_dir() {
+        bail!(FileError::NotFound(dir.display().to_string()));
+    }
+
+    let options = SearchOptions {
+        exclude: glob::compile_all(exclude)?,
+        ..Default::default()
+    };
+    let skip = skip.and_then(|p| p.canonicalize().ok());
+    let paths: Vec<PathBuf> = FileHandler::list_files(&[dir], &options)?
+        .into_iter()
+        .filter(|p| skip.is_none() || p.canonicalize().ok() != skip)
+        .collect();
+
+    let jobs: Vec<(PathBuf, HashAlgorithm)> =
+        paths.iter().map(|p| (p.clone(), HASH_ALGORITHM)).collect();
+    let hashes = FileHandler::checksum_files(&jobs);
+
+    let mut files = Vec::with_capacity(paths.len());
+    for (path, hash) in paths.iter().zip(hashes) {
+        let stats = FileHandler::stats(path)?;
+        files.push(SnapshotFile {
+            path: relative_path(dir, path),
+            size: stats.size,
+            mode: stats.permissions,
+            mtime: DateTime::<Utc>::from(stats.modified_at)
+                .to_rfc3339_opts(SecondsFormat::Millis, true),
+            hash: hash?,
+        });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(Snapshot {
+        version: SNAPSHOT_VERSION,
+        created_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        algorithm: HASH_ALGORITHM.name().to_string(),
+        merkle_root: merkle_root(&files),
+        file_count: files.len(),
+        total_size: files.iter().map(|f| f.size).sum(),
+        exclude: exclude.to_vec(),
+        files,
+    })
+}
+
+/// Compares by path. Modification times are ignored, since copying a tree
+/// to a server rarely preserves them.
+pub fn compare(expected: &Snapshot, actual: &Snapshot) -> Drift {
+    let before: HashMap<&str, &SnapshotFile> = expected
+        .files
+        .iter()
+        .map(|f| (f.path.as_str(), f))
+        .collect();
+    let after: HashMap<&str, &SnapshotFile> =
+        actual.files.iter().map(|f| (f.path.as_str(), f)).collect();
+
+    let mut drift = Drift::default();
+    for file in &actual.files {
+        match before.get(file.path.as_str()) {
+            None => drift.added.push(file.path.clone()),
+            Some(old) => {
+                if old.hash != file.hash || old.size != file.size {
+                    drift.modified.push(file.path.clone());
+                }
+                if old.mode != file.mode {
+                    drift.mode_changed.push((
+                        file.path.clone(),
+                        old.mode.clone(),
+                        file.mode.clone(),
+                    ));
+                }
+            }
+        }
+    }
+    drift.removed = expected
+        .files
+        .iter()
+        .filter(|f| !after.contains_key(f.path.as_str()))
+        .map(|f| f.path.clone())
+        .collect();
+
+    drift
+}
+
+/// Hashes the tree bottom-up like a git tree object: a directory's hash
+/// covers the sorted `mode type hash name` lines of its children.
+fn merkle_root(files: &[SnapshotFile]) -> String {
+    #[derive(Default)]
+    struct Dir<'a> {
+        files: BTreeMap<&'a str, &'a SnapshotFile>,
+        dirs: BTreeMap<&'a str, Dir<'a>>,
+    }
+
+    fn hash_dir(dir: &Dir) -> String {
+        let mut hasher = Hasher::new(HASH_ALGORITHM);
+        let mut entries: Vec<(&str, String)> = dir
+            .files
+            .iter()
+            .map(|(name, file)| (*name, format!("{} file {}", file.mode, file.hash)))
+            .chain(
+                dir.dirs
+                    .iter()
+                    .map(|(name, child)| (*name, format!("- dir {}", hash_dir(child)))),
+            )
+            .collect();
+        entries.sort();
+        for (name, entry) in entries {
+            hasher.update(entry.as_bytes());
+            hasher.update(b"\t");
+            hasher.update(name.as_bytes());
+            hasher.update(b"\n");
+        }
+        hasher.finalize()
+    }
+
+    let mut root = Dir::default();
+    for file in files {
+        let mut dir = &mut root;
+        let mut parts: Vec<&str> = file.path.split('/').collect();
+        let name = parts.pop().unwrap_or_default();
+        for part in parts {
+            dir = dir.dirs.entry(part).or_default();
+        }
+        dir.files.insert(name, file);
+    }
+
+    hash_dir(&root)
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn render_report(report: &Value) -> String {
+    let list = |key: &str| -> Vec<String> {
+        report[key]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect()
+    };
+
+    let mut out = String::new();
+    for path in list("added") {
+        out.push_str(&format!("+ {}\n", path));
+    }
+    for path in list("removed") {
+        out.push_str(&format!("- {}\n", path));
+    }
+    for path in list("modified") {
+        out.push_str(&format!("M {}\n", path));
+    }
+    for change in report["mode_changed"].as_array().into_iter().flatten() {
+        out.push_str(&format!(
+            "P {} ({} -> {})\n",
+            change["path"].as_str().unwrap_or_default(),
+            change["from"].as_str().unwrap_or_default(),
+            change["to"].as_str().unwrap_or_default()
+        ));
+    }
+
+    if report["matches"] == true {
+        out.push_str(&format!(
+            "Snapshot matches ({} files, merkle root {})\n",
+            report["file_count"],
+            report["actual_root"].as_str().unwrap_or_default()
+        ));
+    } else {
+        let count = |key: &str| report[key].as_array().map_or(0, Vec::len);
+        out.push_str(&format!(
+            "Drift detected: {} added, {} removed, {} modified, {} permission changed\n",
+            count("added"),
+            count("removed"),
+            count("modified"),
+            count("mode_changed")
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn tree() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        FileHandler::write(dir.path().join("app/main.js"), "console.log(1)").unwrap();
+        FileHandler::write(dir.path().join("app/lib/util.js"), "export {}").unwrap();
+        FileHandler::write(dir.path().join("README"), "docs").unwrap();
+        FileHandler::write(dir.path().join("cache/tmp.bin"), "junk").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_create_records_files() {
+        let dir = tree();
+        let snapshot = create(dir.path(), &["cache".to_string()], None).unwrap();
+
+        let paths: Vec<&str> = snapshot.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["README", "app/lib/util.js", "app/main.js"]);
+        assert_eq!(snapshot.total_size, 27);
+        assert_eq!(snapshot.files[0].hash.len(), 64);
+        assert_eq!(snapshot.merkle_root.len(), 64);
+    }
+
+    #[test]
+    fn test_merkle_root_tracks_content_and_paths() {
+        let dir = tree();
+        let first = create(dir.path(), &[], None).unwrap();
+        assert_eq!(
+            create(dir.path(), &[], None).unwrap().merkle_root,
+            first.merkle_root
+        );
+
+        fs::write(dir.path().join("app/lib/util.js"), "export {x}").unwrap();
+        let changed = create(dir.path(), &[], None).unwrap();
+        assert_ne!(changed.merkle_root, first.merkle_root);
+
+        fs::write(dir.path().join("app/lib/util.js"), "export {}").unwrap();
+        fs::rename(dir.path().join("README"), dir.path().join("README.md")).unwrap();
+        let renamed = create(dir.path(), &[], None).unwrap();
+        assert_ne!(renamed.merkle_root, first.merkle_root);
+    }
+
+    #[test]
+    fn test_verify_reports_drift() {
+        let dir = tree();
+        let snapshot_path = dir.path().join("snapshot.json");
+        let command = SnapshotCommand::new(
+            dir.path().to_path_buf(),
+            SnapshotAction::Create {
+                output: Some(snapshot_path.clone()),
+                exclude: vec![],
+            },
+        );
+        command.report().unwrap();
+
+        let verify = SnapshotCommand::new(
+            dir.path().to_path_buf(),
+            SnapshotAction::Verify {
+                snapshot: snapshot_path,
+                output_format: "json".into(),
+            },
+        );
+        assert_eq!(verify.report().unwrap()["matches"], true);
+
+        fs::write(dir.path().join("app/main.js"), "console.log(2)").unwrap();
+        fs::remove_file(dir.path().join("README")).unwrap();
+        fs::write(dir.path().join("new.txt"), "hi").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let script = dir.path().join("app/lib/util.js");
+            fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let report = verify.report().unwrap();
+        assert_eq!(report["matches"], false);
+        assert_eq!(report["added"], json!(["new.txt"]));
+        assert_eq!(report["removed"], json!(["README"]));
+        assert_eq!(report["modified"], json!(["app/main.js"]));
+        #[cfg(unix)]
+        assert_eq!(report["mode_changed"][0]["to"], "755");
+        assert!(render_report(&report).starts_with("+ new.txt\n- README\nM app/main.js\n"));
+    }
+}
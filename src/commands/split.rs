@@ -0,0 +1,341 @@
+use crate::utils::byte_size;
+use crate::utils::csv_dialect::CsvOptions;
+use crate::utils::file_handler::{FileError, FileHandler};
+use crate::utils::hashing::HashAlgorithm;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+pub const CHECKSUM_ALGORITHM: HashAlgorithm = HashAlgorithm::Sha256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SplitMode {
+    Bytes,
+    Lines,
+    Records,
+}
+
+/// Written next to the chunks as `<source>.manifest.json` and read back by
+/// `join`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub source: String,
+    pub mode: SplitMode,
+    pub size: u64,
+    pub algorithm: String,
+    pub checksum: String,
+    /// Length of the CSV header repeated at the start of every chunk after
+    /// the first; `join` strips it again. Zero for byte and line splits.
+    pub header_bytes: u64,
+    pub chunks: Vec<ChunkEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChunkEntry {
+    pub file: String,
+    pub size: u64,
+    pub checksum: String,
+}
+
+pub struct SplitCommand {
+    file: PathBuf,
+    mode: SplitMode,
+    limit: u64,
+    out_dir: Option<PathBuf>,
+}
+
+impl SplitCommand {
+    pub fn new(file: PathBuf, mode: SplitMode, limit: u64, out_dir: Option<PathBuf>) -> Self {
+        Self {
+            file,
+            mode,
+            limit,
+            out_dir,
+        }
+    }
+
+    pub fn execute(&self) -> Result<()> {
+        let manifest_path = self.split()?;
+        let manifest: Manifest = FileHandler::read_json(&manifest_path)?;
+
+        for chunk in &manifest.chunks {
+            println!("Created {} ({})", chunk.file, byte_size::format(chunk.size));
+        }
+        println!(
+            "Wrote {} chunks, manifest: {}",
+            manifest.chunks.len(),
+            manifest_path.display()
+        );
+
+        Ok(())
+    }
+
+    /// Writes the chunks and manifest, returning the manifest path.
+    pub fn split(&self) -> Result<PathBuf> {
+        if !self.file.is_file() {
+            bail!(FileError::NotFound(self.file.display().to_string()));
+        }
+        if self.limit == 0 {
+            bail!("Chunk limit must be greater than zero");
+        }
+
+        let size = fs::metadata(&self.file)?.len();
+        let (header_bytes, ranges) = match self.mode {
+            SplitMode::Bytes => (0, byte_ranges(size, self.limit)),
+            SplitMode::Lines => (0, line_ranges(&self.file, self.limit)?),
+            SplitMode::Records => record_ranges(&self.file, self.limit)?,
+        };
+
+        let out_dir = match &self.out_dir {
+            Some(dir) => dir.clone(),
+            None => self
+                .file
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+        };
+        fs::create_dir_all(&out_dir)?;
+
+        let mut source = File::open(&self.file)?;
+        let mut chunks = Vec::new();
+        for (index, &(start, end)) in ranges.iter().enumerate() {
+            let name = chunk_name(&self.file, index + 1, ranges.len());
+            let path = out_dir.join(&name);
+            let mut out = File::create(&path)
+                .map_err(|e| FileError::WriteError(path.display().to_string(), e.to_string()))?;
+
+            if index > 0 && header_bytes > 0 {
+                copy_range(&mut source, &mut out, 0, header_bytes)?;
+            }
+            copy_range(&mut source, &mut out, start, end)?;
+            out.flush()?;
+
+            chunks.push(ChunkEntry {
+                file: name,
+                size: fs::metadata(&path)?.len(),
+                checksum: FileHandler::checksum(&path, CHECKSUM_ALGORITHM)?,
+            });
+        }
+
+        let manifest = Manifest {
+            source: file_name(&self.file),
+            mode: self.mode,
+            size,
+            algorithm: CHECKSUM_ALGORITHM.name().to_string(),
+            checksum: FileHandler::checksum(&self.file, CHECKSUM_ALGORITHM)?,
+            header_bytes,
+            chunks,
+        };
+
+        let manifest_path = out_dir.join(format!("{}.manifest.json", manifest.source));
+        FileHandler::write_json(&manifest_path, &manifest, true)?;
+
+        Ok(manifest_path)
+    }
+}
+
+pub fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// `export.csv` becomes `export.001.csv`, so CSV chunks stay openable as CSV.
+fn chunk_name(source: &Path, number: usize, total: usize) -> String {
+    let width = total.to_string().len().max(3);
+    let stem = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    match source.extension() {
+        Some(ext) => format!(
+            "{}.{:0width$}.{}",
+            stem,
+            number,
+            ext.to_string_lossy(),
+            width = width
+        ),
+        None => format!("{}.{:0width$}", stem, number, width = width),
+    }
+}
+
+fn copy_range(source: &mut File, out: &mut File, start: u64, end: u64) -> Result<()> {
+    source.seek(SeekFrom::Start(start))?;
+    let copied = io::copy(&mut Read::by_ref(source).take(end - start), out)?;
+    if copied != end - start {
+        bail!("Source file changed while splitting");
+    }
+    Ok(())
+}
+
+/// Splits `[0, size)` into ranges of at most `limit` bytes. An empty file
+/// still gets one (empty) chunk so it can be joined back.
+fn byte_ranges(size: u64, limit: u64) -> Vec<(u64, u64)> {
+    if size == 0 {
+        return vec![(0, 0)];
+    }
+
+    (0..size.div_ceil(limit))
+        .map(|i| (i * limit, ((i + 1) * limit).min(size)))
+        .collect()
+}
+
+fn line_ranges(path: &Path, limit: u64) -> Result<Vec<(u64, u64)>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut buffer = Vec::new();
+    let mut ranges = Vec::new();
+    let (mut start, mut offset, mut lines) = (0, 0, 0);
+
+    loop {
+        buffer.clear();
+        let read = reader.read_until(b'\n', &mut buffer)? as u64;
+        if read == 0 {
+            break;
+        }
+        offset += read;
+        lines += 1;
+        if lines == limit {
+            ranges.push((start, offset));
+            start = offset;
+            lines = 0;
+        }
+    }
+    if start < offset || ranges.is_empty() {
+        ranges.push((start, offset));
+    }
+
+    Ok(ranges)
+}
+
+/// Cuts on CSV record boundaries (so quoted newlines stay intact) and
+/// returns the header length along with the record ranges. The dialect is
+/// detected the same way `convert` and `profile` read the file.
+fn record_ranges(path: &Path, limit: u64) -> Result<(u64, Vec<(u64, u64)>)> {
+    let options = CsvOptions {
+        flexible: true,
+        ..CsvOptions::detect(path)?
+    };
+    let file = File::open(path)
+        .map_err(|e| FileError::ReadError(path.display().to_string(), e.to_string()))?;
+    let mut reader = options.reader(file);
+    reader
+        .byte_headers()
+        .map_err(|e| FileError::InvalidCsv(e.to_string()))?;
+    let header_bytes = reader.position().byte();
+
+    let size = fs::metadata(path)?.len();
+    let mut ranges = Vec::new();
+    let mut record = csv::ByteRecord::new();
+    let (mut start, mut records) = (0, 0);
+
+    while reader
+        .read_byte_record(&mut record)
+        .map_err(|e| FileError::InvalidCsv(e.to_string()))?
+    {
+        records += 1;
+        if records == limit {
+            let end = reader.position().byte();
+            ranges.push((start, end));
+            start = end;
+            records = 0;
+        }
+    }
+    if start < size || ranges.is_empty() {
+        ranges.push((start, size));
+    }
+
+    Ok((header_bytes, ranges))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn split(dir: &TempDir, name: &str, content: &str, mode: SplitMode, limit: u64) -> Manifest {
+        let path = dir.path().join(name);
+        fs::write(&path, content).unwrap();
+        let out = dir.path().join("chunks");
+
+        let manifest = SplitCommand::new(path, mode, limit, Some(out))
+            .split()
+            .unwrap();
+        FileHandler::read_json(manifest).unwrap()
+    }
+
+    fn chunk(dir: &TempDir, entry: &ChunkEntry) -> String {
+        fs::read_to_string(dir.path().join("chunks").join(&entry.file)).unwrap()
+    }
+
+    #[test]
+    fn test_split_by_bytes() {
+        let dir = TempDir::new().unwrap();
+        let manifest = split(&dir, "data.bin", "0123456789", SplitMode::Bytes, 4);
+
+        let sizes: Vec<u64> = manifest.chunks.iter().map(|c| c.size).collect();
+        assert_eq!(sizes, vec![4, 4, 2]);
+        assert_eq!(manifest.chunks[0].file, "data.001.bin");
+        assert_eq!(chunk(&dir, &manifest.chunks[2]), "89");
+        assert_eq!(manifest.size, 10);
+        assert_eq!(manifest.checksum.len(), 64);
+    }
+
+    #[test]
+    fn test_split_by_lines() {
+        let dir = TempDir::new().unwrap();
+        let manifest = split(&dir, "log", "a\nb\nc\nd\ne", SplitMode::Lines, 2);
+
+        assert_eq!(manifest.chunks.len(), 3);
+        assert_eq!(manifest.chunks[0].file, "log.001");
+        assert_eq!(chunk(&dir, &manifest.chunks[1]), "c\nd\n");
+        assert_eq!(chunk(&dir, &manifest.chunks[2]), "e");
+    }
+
+    #[test]
+    fn test_split_by_records_repeats_header() {
+        let dir = TempDir::new().unwrap();
+        let csv = "id,note\n1,\"multi\nline\"\n2,b\n3,c\n";
+        let manifest = split(&dir, "export.csv", csv, SplitMode::Records, 2);
+
+        assert_eq!(manifest.header_bytes, 8);
+        assert_eq!(manifest.chunks.len(), 2);
+        assert_eq!(
+            chunk(&dir, &manifest.chunks[0]),
+            "id,note\n1,\"multi\nline\"\n2,b\n"
+        );
+        assert_eq!(chunk(&dir, &manifest.chunks[1]), "id,note\n3,c\n");
+    }
+
+    #[test]
+    fn test_split_by_records_uses_the_file_dialect() {
+        let dir = TempDir::new().unwrap();
+        let csv = "id;note\n1;\"multi\nline\"\n2;b\n3;c\n";
+        let manifest = split(&dir, "export.csv", csv, SplitMode::Records, 2);
+        assert_eq!(
+            chunk(&dir, &manifest.chunks[0]),
+            "id;note\n1;\"multi\nline\"\n2;b\n"
+        );
+        assert_eq!(chunk(&dir, &manifest.chunks[1]), "id;note\n3;c\n");
+
+        let tsv = "id\tnote\n1\t\"a\tb\nc\"\n2\td\n";
+        let manifest = split(&dir, "export.tsv", tsv, SplitMode::Records, 1);
+        assert_eq!(manifest.chunks.len(), 2);
+        assert_eq!(
+            chunk(&dir, &manifest.chunks[0]),
+            "id\tnote\n1\t\"a\tb\nc\"\n"
+        );
+    }
+
+    #[test]
+    fn test_empty_file() {
+        let dir = TempDir::new().unwrap();
+        let manifest = split(&dir, "empty.txt", "", SplitMode::Bytes, 10);
+
+        assert_eq!(manifest.chunks.len(), 1);
+        assert_eq!(manifest.chunks[0].size, 0);
+    }
+}
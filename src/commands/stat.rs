@@ -0,0 +1,226 @@
+use crate::utils::byte_size;
+use crate::utils::file_handler::{FileHandler, FileStats};
+use crate::utils::ruby_json;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Local};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+pub struct StatCommand {
+    paths: Vec<PathBuf>,
+    dereference: bool,
+    output_format: String,
+}
+
+impl StatCommand {
+    pub fn new(paths: Vec<PathBuf>, dereference: bool, output_format: String) -> Result<Self> {
+        if !matches!(output_format.as_str(), "console" | "json") {
+            bail!(
+                "Unknown output format: {:?} (expected console or json)",
+                output_format
+            );
+        }
+
+        Ok(Self {
+            paths,
+            dereference,
+            output_format,
+        })
+    }
+
+    /// Prints each path's details. Returns whether any path couldn't be
+    /// statted, so the caller can exit non-zero like `stat`.
+    pub fn execute(&self) -> Result<bool> {
+        if self.output_format == "json" {
+            let report = self.report()?;
+            println!("{}", ruby_json::pretty_generate(&report)?);
+            return Ok(!report["errors"].as_array().is_some_and(|e| e.is_empty()));
+        }
+
+        let mut failed = false;
+        let mut first = true;
+        for (path, stats) in self.stat_all() {
+            match stats {
+                Ok(stats) => {
+                    if !first {
+                        println!();
+                    }
+                    first = false;
+                    print!("{}", render(&path, &stats));
+                }
+                Err(e) => {
+                    eprintln!("stat: {}", e);
+                    failed = true;
+                }
+            }
+        }
+        Ok(failed)
+    }
+
+    /// The structured results behind `--output json` and `--template`.
+    pub fn report(&self) -> Result<Value> {
+        let mut files = Vec::new();
+        let mut errors = Vec::new();
+        for (path, stats) in self.stat_all() {
+            match stats {
+                Ok(stats) => {
+                    let mut entry = json!({"path": path.display().to_string()});
+                    if let (Value::Object(map), Value::Object(fields)) =
+                        (&mut entry, stats.to_json())
+                    {
+                        map.insert("type".into(), json!(stats.kind()));
+                        map.extend(fields);
+                    }
+                    files.push(entry);
+                }
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        Ok(json!({"files": files, "errors": errors}))
+    }
+
+    fn stat_all(&self) -> Vec<(PathBuf, Result<FileStats>)> {
+        self.paths
+            .iter()
+            .map(|path| {
+                let stats = if self.dereference {
+                    FileHandler::stats(path)
+                } else {
+                    FileHandler::symlink_stats(path)
+                };
+                (path.clone(), stats)
+            })
+            .collect()
+    }
+}
+
+fn render(path: &std::path::Path, stats: &FileStats) -> String {
+    let optional = |value: Option<u64>| value.map_or("-".to_string(), |v| v.to_string());
+    let name = match &stats.symlink_target {
+        Some(target) => format!("{} -> {}", path.display(), target.display()),
+        None => path.display().to_string(),
+    };
+    let kind = match stats.kind() {
+        "file" if stats.size == 0 => "regular empty file",
+        "file" => "regular file",
+        "symlink" => "symbolic link",
+        other => other,
+    };
+
+    let mut out = String::new();
+    out.push_str(&format!("    File: {}\n", name));
+    out.push_str(&format!("    Type: {}\n", kind));
+    out.push_str(&format!(
+        "    Size: {} ({})\n",
+        stats.size,
+        byte_size::format(stats.size)
+    ));
+    out.push_str(&format!(
+        "  Access: {}  Uid: {}  Gid: {}\n",
+        stats.permissions,
+        optional(stats.uid.map(u64::from)),
+        optional(stats.gid.map(u64::from))
+    ));
+    out.push_str(&format!(
+        "   Inode: {}  Links: {}  Device: {}\n",
+        optional(stats.inode),
+        optional(stats.hard_links),
+        optional(stats.device)
+    ));
+    if let Some(content_type) = &stats.content_type {
+        out.push_str(&format!(" Content: {}\n", content_type));
+    }
+    out.push_str(&format!(
+        "Modified: {}\n",
+        local_time(Some(stats.modified_at))
+    ));
+    out.push_str(&format!("Accessed: {}\n", local_time(stats.accessed_at)));
+    out.push_str(&format!(" Created: {}\n", local_time(stats.created_at)));
+    out
+}
+
+fn local_time(time: Option<SystemTime>) -> String {
+    time.map_or("-".to_string(), |t| {
+        DateTime::<Local>::from(t)
+            .format("%Y-%m-%d %H:%M:%S %z")
+            .to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_report() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("data.json");
+        FileHandler::write(&file, "{\"a\": 1}").unwrap();
+
+        let command = StatCommand::new(
+            vec![file.clone(), dir.path().join("missing")],
+            false,
+            "json".into(),
+        )
+        .unwrap();
+        let report = command.report().unwrap();
+        let entry = &report["files"][0];
+        assert_eq!(entry["type"], "file");
+        assert_eq!(entry["size"], 8);
+        assert_eq!(entry["content_type"], "application/json");
+        assert_eq!(entry["is_symlink"], false);
+        assert_eq!(report["errors"].as_array().unwrap().len(), 1);
+
+        let rendered = render(&file, &FileHandler::stats(&file).unwrap());
+        assert!(
+            rendered.contains("    Type: regular file\n"),
+            "{}",
+            rendered
+        );
+        assert!(
+            rendered.contains(" Content: application/json\n"),
+            "{}",
+            rendered
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks() {
+        let dir = TempDir::new().unwrap();
+        let target = dir.path().join("target.gz");
+        std::fs::write(&target, b"\x1f\x8b\x08\x00").unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        let dangling = dir.path().join("dangling");
+        std::os::unix::fs::symlink(dir.path().join("gone"), &dangling).unwrap();
+
+        let report = StatCommand::new(vec![link.clone(), dangling], false, "json".into())
+            .unwrap()
+            .report()
+            .unwrap();
+        assert_eq!(report["files"][0]["type"], "symlink");
+        assert_eq!(report["files"][0]["content_type"], "inode/symlink");
+        assert_eq!(
+            report["files"][0]["symlink_target"],
+            target.display().to_string()
+        );
+        assert_eq!(report["files"][1]["type"], "symlink");
+
+        let report = StatCommand::new(vec![link], true, "json".into())
+            .unwrap()
+            .report()
+            .unwrap();
+        assert_eq!(report["files"][0]["type"], "file");
+        assert_eq!(report["files"][0]["is_symlink"], true);
+        assert_eq!(report["files"][0]["content_type"], "application/gzip");
+        assert_eq!(report["files"][0]["hard_links"], 1);
+    }
+
+    #[test]
+    fn test_rejects_unknown_output_format() {
+        assert!(StatCommand::new(vec![PathBuf::from(".")], false, "yaml".into()).is_err());
+    }
+}
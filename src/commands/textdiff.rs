@@ -0,0 +1,387 @@
+use crate::utils::file_handler::FileHandler;
+use crate::utils::http_fetcher::HttpFetcher;
+use crate::utils::line_diff::{self, Algorithm, Edit, Hunk};
+use crate::utils::ruby_json;
+use crate::utils::table::{color_enabled, pad, terminal_width, truncate, Alignment};
+use anyhow::{bail, Result};
+use colored::*;
+use serde_json::{json, Value};
+use std::io::Read;
+
+const DEFAULT_SIDE_BY_SIDE_WIDTH: usize = 130;
+const NO_NEWLINE_MARKER: &str = "\\ No newline at end of file";
+
+pub struct TextDiffCommand {
+    old: String,
+    new: String,
+    output_format: String,
+    context: usize,
+    algorithm: Algorithm,
+    color: bool,
+}
+
+/// Both inputs split into lines, each keeping its trailing newline so that
+/// a missing final newline shows up as a difference.
+struct Inputs {
+    old: Vec<String>,
+    new: Vec<String>,
+}
+
+impl TextDiffCommand {
+    pub fn new(
+        old: String,
+        new: String,
+        output_format: String,
+        context: usize,
+        algorithm: Algorithm,
+        color: &str,
+    ) -> Result<Self> {
+        if !matches!(output_format.as_str(), "unified" | "side-by-side" | "json") {
+            bail!(
+                "Unknown output format: {:?} (expected unified, side-by-side or json)",
+                output_format
+            );
+        }
+        let color = color_enabled(color);
+
+        Ok(Self {
+            old,
+            new,
+            output_format,
+            context,
+            algorithm,
+            color,
+        })
+    }
+
+    /// Prints the diff and returns whether the inputs differ.
+    pub fn execute(&self) -> Result<bool> {
+        if self.color {
+            colored::control::set_override(true);
+        }
+
+        let (inputs, hunks) = self.diff()?;
+
+        let output = match self.output_format.as_str() {
+            "json" => ruby_json::pretty_generate(&self.json_report(&inputs, &hunks))? + "\n",
+            "side-by-side" => self.render_side_by_side(&inputs, &hunks),
+            _ => self.render_unified(&inputs, &hunks),
+        };
+        print!("{}", output);
+
+        Ok(!hunks.is_empty())
+    }
+
+    /// The structured diff behind `--output json` and `--template`.
+    pub fn report(&self) -> Result<Value> {
+        let (inputs, hunks) = self.diff()?;
+        Ok(self.json_report(&inputs, &hunks))
+    }
+
+    fn diff(&self) -> Result<(Inputs, Vec<Hunk>)> {
+        let inputs = Inputs {
+            old: split_lines(&read_input(&self.old)?),
+            new: split_lines(&read_input(&self.new)?),
+        };
+        let edits = line_diff::diff(&inputs.old, &inputs.new, self.algorithm);
+        let hunks = line_diff::hunks(&edits, self.context);
+        Ok((inputs, hunks))
+    }
+
+    fn render_unified(&self, inputs: &Inputs, hunks: &[Hunk]) -> String {
+        if hunks.is_empty() {
+            return String::new();
+        }
+
+        let mut out = String::new();
+        out.push_str(&self.paint(&format!("--- {}", self.old), |s| s.bold()));
+        out.push('\n');
+        out.push_str(&self.paint(&format!("+++ {}", self.new), |s| s.bold()));
+        out.push('\n');
+
+        for hunk in hunks {
+            out.push_str(&self.paint(&hunk.header(), |s| s.cyan()));
+            out.push('\n');
+
+            for edit in &hunk.edits {
+                let (prefix, line) = match *edit {
+                    Edit::Equal(i, _) => (' ', &inputs.old[i]),
+                    Edit::Delete(i) => ('-', &inputs.old[i]),
+                    Edit::Insert(j) => ('+', &inputs.new[j]),
+                };
+                let text = format!("{}{}", prefix, line.strip_suffix('\n').unwrap_or(line));
+                let text = match prefix {
+                    '-' => self.paint(&text, |s| s.red()),
+                    '+' => self.paint(&text, |s| s.green()),
+                    _ => text,
+                };
+                out.push_str(&text);
+                out.push('\n');
+                if !line.ends_with('\n') {
+                    out.push_str(NO_NEWLINE_MARKER);
+                    out.push('\n');
+                }
+            }
+        }
+
+        out
+    }
+
+    fn render_side_by_side(&self, inputs: &Inputs, hunks: &[Hunk]) -> String {
+        let width = terminal_width().unwrap_or(DEFAULT_SIDE_BY_SIDE_WIDTH);
+        let column = width.saturating_sub(3) / 2;
+        let cell = |line: Option<&String>| {
+            let text = line.map(|l| l.trim_end_matches('\n')).unwrap_or("");
+            pad(&truncate(text, column), column, Alignment::Left)
+        };
+
+        let mut out = String::new();
+        for hunk in hunks {
+            out.push_str(&self.paint(&hunk.header(), |s| s.cyan()));
+            out.push('\n');
+
+            for (old, new) in pair_changes(&hunk.edits) {
+                let (gutter, left, right) = match (old, new) {
+                    (Some(i), Some(j)) if inputs.old[i] == inputs.new[j] => {
+                        (" ", cell(Some(&inputs.old[i])), cell(Some(&inputs.new[j])))
+                    }
+                    (Some(i), Some(j)) => (
+                        "|",
+                        self.paint(&cell(Some(&inputs.old[i])), |s| s.red()),
+                        self.paint(&cell(Some(&inputs.new[j])), |s| s.green()),
+                    ),
+                    (Some(i), None) => (
+                        "<",
+                        self.paint(&cell(Some(&inputs.old[i])), |s| s.red()),
+                        cell(None),
+                    ),
+                    (None, Some(j)) => (
+                        ">",
+                        cell(None),
+                        self.paint(&cell(Some(&inputs.new[j])), |s| s.green()),
+                    ),
+                    (None, None) => continue,
+                };
+                out.push_str(format!("{} {} {}", left, gutter, right).trim_end());
+                out.push('\n');
+            }
+        }
+
+        out
+    }
+
+    fn json_report(&self, inputs: &Inputs, hunks: &[Hunk]) -> Value {
+        let hunks: Vec<Value> = hunks
+            .iter()
+            .map(|hunk| {
+                let lines: Vec<Value> = hunk
+                    .edits
+                    .iter()
+                    .map(|edit| {
+                        let (op, old_line, new_line, text) = match *edit {
+                            Edit::Equal(i, j) => {
+                                ("equal", Some(i + 1), Some(j + 1), &inputs.old[i])
+                            }
+                            Edit::Delete(i) => ("delete", Some(i + 1), None, &inputs.old[i]),
+                            Edit::Insert(j) => ("insert", None, Some(j + 1), &inputs.new[j]),
+                        };
+                        json!({
+                            "op": op,
+                            "old_line": old_line,
+                            "new_line": new_line,
+                            "text": text.strip_suffix('\n').unwrap_or(text),
+                            "newline": text.ends_with('\n'),
+                        })
+                    })
+                    .collect();
+
+                json!({
+                    "old_start": hunk.old_start,
+                    "old_lines": hunk.old_len,
+                    "new_start": hunk.new_start,
+                    "new_lines": hunk.new_len,
+                    "lines": lines,
+                })
+            })
+            .collect();
+
+        json!({
+            "old": self.old,
+            "new": self.new,
+            "algorithm": format!("{:?}", self.algorithm).to_lowercase(),
+            "identical": hunks.is_empty(),
+            "hunks": hunks,
+        })
+    }
+
+    fn paint(&self, text: &str, style: impl Fn(&str) -> ColoredString) -> String {
+        if self.color {
+            style(text).to_string()
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// Reads a file, an http(s) URL, or stdin for `-`.
+fn read_input(input: &str) -> Result<String> {
+    if input == "-" {
+        let mut content = String::new();
+        std::io::stdin().read_to_string(&mut content)?;
+        return Ok(content);
+    }
+
+    let path = HttpFetcher::new().resolve(input)?;
+    FileHandler::read(path)
+}
+
+fn split_lines(content: &str) -> Vec<String> {
+    content.split_inclusive('\n').map(String::from).collect()
+}
+
+/// Lines up a hunk's edits as side-by-side rows: runs of deletions and
+/// insertions are paired up, with any surplus shown on one side only.
+fn pair_changes(edits: &[Edit]) -> Vec<(Option<usize>, Option<usize>)> {
+    let mut rows = Vec::new();
+    let mut deleted = Vec::new();
+    let mut inserted = Vec::new();
+
+    let flush = |rows: &mut Vec<_>, deleted: &mut Vec<usize>, inserted: &mut Vec<usize>| {
+        for k in 0..deleted.len().max(inserted.len()) {
+            rows.push((deleted.get(k).copied(), inserted.get(k).copied()));
+        }
+        deleted.clear();
+        inserted.clear();
+    };
+
+    for edit in edits {
+        match *edit {
+            Edit::Equal(i, j) => {
+                flush(&mut rows, &mut deleted, &mut inserted);
+                rows.push((Some(i), Some(j)));
+            }
+            Edit::Delete(i) => deleted.push(i),
+            Edit::Insert(j) => inserted.push(j),
+        }
+    }
+    flush(&mut rows, &mut deleted, &mut inserted);
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn command(dir: &TempDir, old: &str, new: &str, format: &str) -> (TextDiffCommand, Inputs) {
+        let old_path = dir.path().join("old.txt");
+        let new_path = dir.path().join("new.txt");
+        fs::write(&old_path, old).unwrap();
+        fs::write(&new_path, new).unwrap();
+
+        let command = TextDiffCommand::new(
+            old_path.display().to_string(),
+            new_path.display().to_string(),
+            format.to_string(),
+            3,
+            Algorithm::Myers,
+            "never",
+        )
+        .unwrap();
+        let inputs = Inputs {
+            old: split_lines(old),
+            new: split_lines(new),
+        };
+        (command, inputs)
+    }
+
+    fn hunks(inputs: &Inputs) -> Vec<Hunk> {
+        line_diff::hunks(
+            &line_diff::diff(&inputs.old, &inputs.new, Algorithm::Myers),
+            3,
+        )
+    }
+
+    #[test]
+    fn test_unified_output() {
+        let dir = TempDir::new().unwrap();
+        let (command, inputs) = command(&dir, "a\nb\nc\n", "a\nB\nc\nd", "unified");
+
+        let output = command.render_unified(&inputs, &hunks(&inputs));
+        let body: Vec<&str> = output.lines().skip(2).collect();
+        assert_eq!(
+            body,
+            vec![
+                "@@ -1,3 +1,4 @@",
+                " a",
+                "-b",
+                "+B",
+                " c",
+                "+d",
+                "\\ No newline at end of file"
+            ]
+        );
+        assert!(output.starts_with("--- "));
+    }
+
+    #[test]
+    fn test_identical_inputs() {
+        let dir = TempDir::new().unwrap();
+        let (command, inputs) = command(&dir, "same\n", "same\n", "unified");
+
+        assert!(hunks(&inputs).is_empty());
+        assert_eq!(command.render_unified(&inputs, &[]), "");
+        assert!(!command.execute().unwrap());
+    }
+
+    #[test]
+    fn test_execute_reports_differences() {
+        let dir = TempDir::new().unwrap();
+        let (command, _) = command(&dir, "a\n", "b\n", "json");
+
+        assert!(command.execute().unwrap());
+    }
+
+    #[test]
+    fn test_side_by_side_output() {
+        let dir = TempDir::new().unwrap();
+        let (command, inputs) = command(&dir, "keep\nold\ngone\n", "keep\nnew\n", "side-by-side");
+
+        let output = command.render_side_by_side(&inputs, &hunks(&inputs));
+        let rows: Vec<&str> = output.lines().collect();
+        assert_eq!(rows[0], "@@ -1,3 +1,2 @@");
+        assert!(rows[1].starts_with("keep ") && rows[1].ends_with("   keep"));
+        assert!(rows[2].contains(" | new"));
+        assert!(rows[3].starts_with("gone") && rows[3].ends_with('<'));
+    }
+
+    #[test]
+    fn test_json_report() {
+        let dir = TempDir::new().unwrap();
+        let (command, inputs) = command(&dir, "a\nb\n", "a\nc\n", "json");
+
+        let report = command.json_report(&inputs, &hunks(&inputs));
+        assert_eq!(report["identical"], false);
+        assert_eq!(report["algorithm"], "myers");
+        let lines = report["hunks"][0]["lines"].as_array().unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1]["op"], "delete");
+        assert_eq!(lines[1]["old_line"], 2);
+        assert_eq!(lines[2]["text"], "c");
+    }
+
+    #[test]
+    fn test_rejects_unknown_output_format() {
+        let result = TextDiffCommand::new(
+            "a".into(),
+            "b".into(),
+            "context".into(),
+            3,
+            Algorithm::Myers,
+            "never",
+        );
+        assert!(result.is_err());
+    }
+}
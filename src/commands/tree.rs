@@ -0,0 +1,261 @@
+use crate::utils::byte_size;
+use crate::utils::dir_tree::DirNode;
+use crate::utils::ruby_json;
+use crate::utils::walker::{EntryType, WalkOptions};
+use anyhow::{bail, Result};
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::PathBuf;
+
+pub struct TreeCommand {
+    path: PathBuf,
+    options: WalkOptions,
+    depth: Option<usize>,
+    sizes: bool,
+    dirs_first: bool,
+    output_format: String,
+}
+
+impl TreeCommand {
+    pub fn new(
+        path: PathBuf,
+        options: WalkOptions,
+        depth: Option<usize>,
+        sizes: bool,
+        dirs_first: bool,
+        output_format: String,
+    ) -> Result<Self> {
+        if !matches!(output_format.as_str(), "console" | "json") {
+            bail!(
+                "Unknown output format: {:?} (expected console or json)",
+                output_format
+            );
+        }
+        Ok(Self {
+            path,
+            options,
+            depth,
+            sizes,
+            dirs_first,
+            output_format,
+        })
+    }
+
+    /// Prints the hierarchy. Returns whether any directory couldn't be read.
+    pub fn execute(&self) -> Result<bool> {
+        let (root, errors) = self.build()?;
+        if self.output_format == "json" {
+            println!(
+                "{}",
+                ruby_json::pretty_generate(&self.json_report(&root, &errors))?
+            );
+        } else {
+            print!("{}", self.render(&root));
+        }
+
+        for error in &errors {
+            eprintln!("tree: {}", error);
+        }
+        Ok(!errors.is_empty())
+    }
+
+    /// The structured results behind `--output json` and `--template`.
+    pub fn report(&self) -> Result<Value> {
+        let (root, errors) = self.build()?;
+        Ok(self.json_report(&root, &errors))
+    }
+
+    fn build(&self) -> Result<(DirNode, Vec<String>)> {
+        let (mut root, errors) = DirNode::build(&self.path, &self.options)?;
+        root.sort(self.dirs_first);
+        Ok((root, errors))
+    }
+
+    fn render(&self, root: &DirNode) -> String {
+        let mut out = String::new();
+        if self.sizes {
+            out.push_str(&format!("[{:>10}]  ", byte_size::format(root.size)));
+        }
+        out.push_str(&root.name);
+        out.push('\n');
+
+        let mut counts = (0, 0);
+        self.render_children(root, "", &mut out, &mut counts);
+
+        let (dirs, files) = counts;
+        out.push_str(&format!(
+            "\n{} {}, {} {}",
+            dirs,
+            if dirs == 1 {
+                "directory"
+            } else {
+                "directories"
+            },
+            files,
+            if files == 1 { "file" } else { "files" }
+        ));
+        if self.sizes {
+            out.push_str(&format!(", {} total", byte_size::format(root.size)));
+        }
+        out.push('\n');
+        out
+    }
+
+    fn render_children(
+        &self,
+        node: &DirNode,
+        prefix: &str,
+        out: &mut String,
+        counts: &mut (usize, usize),
+    ) {
+        for (index, child) in node.children.iter().enumerate() {
+            let last = index + 1 == node.children.len();
+            out.push_str(prefix);
+            out.push_str(if last { "└── " } else { "├── " });
+            if self.sizes {
+                out.push_str(&format!("[{:>10}]  ", byte_size::format(child.size)));
+            }
+            out.push_str(&child.name);
+            if child.file_type == EntryType::Symlink {
+                if let Ok(target) = fs::read_link(&child.path) {
+                    out.push_str(&format!(" -> {}", target.display()));
+                }
+            }
+            out.push('\n');
+
+            if child.is_dir() {
+                counts.0 += 1;
+                if self.expands(child) {
+                    let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+                    self.render_children(child, &prefix, out, counts);
+                }
+            } else {
+                counts.1 += 1;
+            }
+        }
+    }
+
+    /// Whether a directory's contents are within the depth limit.
+    fn expands(&self, node: &DirNode) -> bool {
+        self.depth.is_none_or(|limit| node.depth < limit)
+    }
+
+    fn json_report(&self, root: &DirNode, errors: &[String]) -> Value {
+        let (dirs, files) = self.count_shown(root);
+        json!({
+            "path": self.path.display().to_string(),
+            "directories": dirs,
+            "files": files,
+            "size": root.size,
+            "tree": self.node_json(root),
+            "errors": errors,
+        })
+    }
+
+    fn node_json(&self, node: &DirNode) -> Value {
+        let mut map = Map::new();
+        map.insert("name".into(), json!(node.name));
+        map.insert("type".into(), json!(node.file_type.name()));
+        map.insert("size".into(), json!(node.size));
+        if node.is_dir() {
+            map.insert("files".into(), json!(node.file_count));
+            if self.expands(node) {
+                let children: Vec<Value> =
+                    node.children.iter().map(|c| self.node_json(c)).collect();
+                map.insert("children".into(), Value::Array(children));
+            }
+        }
+        Value::Object(map)
+    }
+
+    fn count_shown(&self, node: &DirNode) -> (usize, usize) {
+        let mut counts = (0, 0);
+        if !self.expands(node) {
+            return counts;
+        }
+        for child in &node.children {
+            if child.is_dir() {
+                let (dirs, files) = self.count_shown(child);
+                counts.0 += 1 + dirs;
+                counts.1 += files;
+            } else {
+                counts.1 += 1;
+            }
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_handler::FileHandler;
+    use tempfile::TempDir;
+
+    fn sample() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        FileHandler::write(dir.path().join("b.txt"), "12").unwrap();
+        FileHandler::write(dir.path().join("a/one.txt"), "1234").unwrap();
+        FileHandler::write(dir.path().join("a/deep/two.txt"), "1").unwrap();
+        FileHandler::write(dir.path().join("c/three.txt"), "").unwrap();
+        dir
+    }
+
+    fn command(dir: &TempDir, depth: Option<usize>, sizes: bool, dirs_first: bool) -> TreeCommand {
+        TreeCommand::new(
+            dir.path().to_path_buf(),
+            WalkOptions::default(),
+            depth,
+            sizes,
+            dirs_first,
+            "console".into(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_render() {
+        let dir = sample();
+        let tree = command(&dir, None, false, false);
+        let (root, _) = tree.build().unwrap();
+        let rendered = tree.render(&root);
+        let body = rendered.split_once('\n').unwrap().1;
+        assert_eq!(
+            body,
+            "├── a\n│   ├── deep\n│   │   └── two.txt\n│   └── one.txt\n├── b.txt\n└── c\n    └── three.txt\n\n3 directories, 4 files\n"
+        );
+    }
+
+    #[test]
+    fn test_depth_sizes_and_dirs_first() {
+        let dir = sample();
+        let tree = command(&dir, Some(1), true, true);
+        let (root, _) = tree.build().unwrap();
+        let rendered = tree.render(&root);
+        let body = rendered.split_once('\n').unwrap().1;
+        assert_eq!(
+            body,
+            "├── [       5 B]  a\n├── [       0 B]  c\n└── [       2 B]  b.txt\n\n2 directories, 1 file, 7 B total\n"
+        );
+
+        let report = tree.report().unwrap();
+        assert_eq!(report["directories"], 2);
+        assert_eq!(report["files"], 1);
+        assert_eq!(report["tree"]["children"][0]["name"], "a");
+        assert_eq!(report["tree"]["children"][0]["files"], 2);
+        assert!(report["tree"]["children"][0].get("children").is_none());
+    }
+
+    #[test]
+    fn test_rejects_unknown_output_format() {
+        let result = TreeCommand::new(
+            PathBuf::from("."),
+            WalkOptions::default(),
+            None,
+            false,
+            false,
+            "yaml".into(),
+        );
+        assert!(result.is_err());
+    }
+}
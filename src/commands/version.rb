@@ -1,4 +1,5 @@
 require 'json'
+require_relative '../utils/table'
 
 module BasicCli
   module Commands
@@ -36,16 +37,16 @@ module BasicCli
       end
 
       def display_formatted(info)
-        puts '╔═══════════════════════════════════════════════════════════╗'
-        puts '║                       BasicCli                            ║'
-        puts '╠═══════════════════════════════════════════════════════════╣'
-        puts "║ Version:      #{info[:version].ljust(44)} ║"
-        puts "║ Build Date:   #{info[:build_date].ljust(44)} ║"
-        puts "║ Ruby Version: #{info[:ruby_version].ljust(44)} ║"
-        puts "║ Platform:     #{info[:platform].ljust(44)} ║"
-        puts '╠═══════════════════════════════════════════════════════════╣'
-        puts "║ #{info[:description].center(57)} ║"
-        puts '╚═══════════════════════════════════════════════════════════╝'
+        puts version_table(info).render_console
+      end
+
+      def version_table(info)
+        table = Utils::Table.new(['', ''], title: 'BasicCli', max_cell_width: 60)
+        table.add_row(['Version', info[:version]])
+        table.add_row(['Build Date', info[:build_date]])
+        table.add_row(['Ruby Version', info[:ruby_version]])
+        table.add_row(['Platform', info[:platform]])
+        table.add_row(['Description', info[:description]])
       end
     end
   end
@@ -1,3 +1,5 @@
+use crate::utils::ruby_json;
+use crate::utils::table::Table;
 use anyhow::Result;
 use serde_json::json;
 
@@ -17,7 +19,7 @@ impl VersionCommand {
         let version_info = self.build_version_info();
 
         if self.json_output {
-            println!("{}", serde_json::to_string_pretty(&version_info)?);
+            println!("{}", ruby_json::pretty_generate(&version_info)?);
         } else {
             self.display_formatted(&version_info);
         }
@@ -25,7 +27,7 @@ impl VersionCommand {
         Ok(())
     }
 
-    fn build_version_info(&self) -> serde_json::Value {
+    pub fn build_version_info(&self) -> serde_json::Value {
         json!({
             "name": "BasicCli",
             "version": Self::VERSION,
@@ -38,28 +40,22 @@ impl VersionCommand {
     }
 
     fn display_formatted(&self, info: &serde_json::Value) {
-        println!("╔═══════════════════════════════════════════════════════════╗");
-        println!("║                    BasicCli (Rust)                        ║");
-        println!("╠═══════════════════════════════════════════════════════════╣");
-        println!(
-            "║ Version:      {:44} ║",
-            info["version"].as_str().unwrap_or("")
-        );
-        println!(
-            "║ Build Date:   {:44} ║",
-            info["build_date"].as_str().unwrap_or("")
-        );
-        println!(
-            "║ Rust Version: {:44} ║",
-            info["ruby_version"].as_str().unwrap_or("")
-        );
-        println!(
-            "║ Platform:     {:44} ║",
-            info["platform"].as_str().unwrap_or("")
-        );
-        println!("╠═══════════════════════════════════════════════════════════╣");
-        println!("║ {:^57} ║", info["description"].as_str().unwrap_or(""));
-        println!("╚═══════════════════════════════════════════════════════════╝");
+        println!("{}", self.version_table(info).render_console());
+    }
+
+    fn version_table(&self, info: &serde_json::Value) -> Table {
+        let field = |key: &str| info[key].as_str().unwrap_or("").to_string();
+
+        let mut table = Table::new(vec!["", ""])
+            .with_title("BasicCli (Rust)")
+            .with_max_cell_width(60);
+        table.add_row(vec!["Version".to_string(), field("version")]);
+        table.add_row(vec!["Build Date".to_string(), field("build_date")]);
+        table.add_row(vec!["Rust Version".to_string(), field("ruby_version")]);
+        table.add_row(vec!["Platform".to_string(), field("platform")]);
+        table.add_row(vec!["Description".to_string(), field("description")]);
+
+        table
     }
 }
 
@@ -101,4 +97,19 @@ mod tests {
         assert!(info["description"].is_string());
         assert!(info["repository"].is_string());
     }
+
+    #[test]
+    fn test_version_table() {
+        let cmd = VersionCommand::new(false);
+        let info = cmd.build_version_info();
+        let output = cmd
+            .version_table(&info)
+            .with_max_width(None)
+            .render_console();
+
+        assert!(output.contains("BasicCli (Rust)"));
+        assert!(output.contains("│ Version"));
+        assert!(output.contains("1.0.0"));
+        assert!(output.contains("Polyglot Transpilation Development Reference Implementation"));
+    }
 }
@@ -0,0 +1,77 @@
+use anyhow::{bail, Result};
+
+const UNITS: [&str; 5] = ["K", "M", "G", "T", "P"];
+
+/// Parses sizes such as `512`, `64K`, `1.5M` or `2GB`. Bare and `iB`
+/// suffixes are binary (`10M` = 10 MiB), `B` suffixes decimal (`10MB` =
+/// 10,000,000), matching GNU coreutils.
+pub fn parse(input: &str) -> Result<u64> {
+    let trimmed = input.trim();
+    let split = trimmed
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(trimmed.len());
+    let (number, suffix) = trimmed.split_at(split);
+
+    let Ok(value) = number.parse::<f64>() else {
+        bail!("Invalid size: {:?}", input);
+    };
+
+    let suffix = suffix.trim().to_uppercase();
+    let multiplier = if suffix.is_empty() || suffix == "B" {
+        1.0
+    } else {
+        let unit = &suffix[..1];
+        let Some(power) = UNITS.iter().position(|u| *u == unit) else {
+            bail!("Invalid size unit in {:?}", input);
+        };
+        let base: f64 = match &suffix[1..] {
+            "" | "IB" => 1024.0,
+            "B" => 1000.0,
+            _ => bail!("Invalid size unit in {:?}", input),
+        };
+        base.powi(power as i32 + 1)
+    };
+
+    Ok((value * multiplier).round() as u64)
+}
+
+/// Formats a byte count with binary units, e.g. `1.5 MiB`.
+pub fn format(bytes: u64) -> String {
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{:.1} {}iB", value, UNITS[unit - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("512").unwrap(), 512);
+        assert_eq!(parse("64K").unwrap(), 65_536);
+        assert_eq!(parse("1.5m").unwrap(), 1_572_864);
+        assert_eq!(parse("2MiB").unwrap(), 2_097_152);
+        assert_eq!(parse("2MB").unwrap(), 2_000_000);
+        assert_eq!(parse("1G").unwrap(), 1 << 30);
+        assert!(parse("ten").is_err());
+        assert!(parse("10X").is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format(0), "0 B");
+        assert_eq!(format(1023), "1023 B");
+        assert_eq!(format(1536), "1.5 KiB");
+        assert_eq!(format(5 * 1024 * 1024), "5.0 MiB");
+    }
+}
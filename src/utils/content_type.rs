@@ -0,0 +1,161 @@
+//! Content type sniffing from a file's leading bytes, reported as MIME
+//! types the way `file --mime-type` does.
+//!
+//! Binary formats are recognized by their magic numbers. Text formats (JSON,
+//! XML, HTML, YAML) are recognized by how they start, not validated.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes read from the start of a file for sniffing.
+pub const SAMPLE_SIZE: usize = 8192;
+
+/// (offset, magic, MIME type), checked in order.
+const SIGNATURES: &[(usize, &[u8], &str)] = &[
+    (0, b"\x1f\x8b", "application/gzip"),
+    (0, b"PK\x03\x04", "application/zip"),
+    (0, b"PK\x05\x06", "application/zip"),
+    (0, b"\x89PNG\r\n\x1a\n", "image/png"),
+    (0, b"\xff\xd8\xff", "image/jpeg"),
+    (0, b"GIF87a", "image/gif"),
+    (0, b"GIF89a", "image/gif"),
+    (0, b"%PDF-", "application/pdf"),
+    (0, b"\x7fELF", "application/x-elf"),
+    (0, b"\xfe\xed\xfa\xce", "application/x-mach-binary"),
+    (0, b"\xfe\xed\xfa\xcf", "application/x-mach-binary"),
+    (0, b"\xce\xfa\xed\xfe", "application/x-mach-binary"),
+    (0, b"\xcf\xfa\xed\xfe", "application/x-mach-binary"),
+    (0, b"MZ", "application/vnd.microsoft.portable-executable"),
+    (0, b"\0asm", "application/wasm"),
+    (0, b"BZh", "application/x-bzip2"),
+    (0, b"\xfd7zXZ\0", "application/x-xz"),
+    (0, b"\x28\xb5\x2f\xfd", "application/zstd"),
+    (0, b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (0, b"SQLite format 3\0", "application/vnd.sqlite3"),
+    (0, b"OggS", "audio/ogg"),
+    (0, b"fLaC", "audio/flac"),
+    (0, b"ID3", "audio/mpeg"),
+    (257, b"ustar", "application/x-tar"),
+];
+
+/// Sniffs the content type of `bytes`, the start of a file.
+pub fn detect(bytes: &[u8]) -> &'static str {
+    if bytes.is_empty() {
+        return "inode/x-empty";
+    }
+    for (offset, magic, mime) in SIGNATURES {
+        if bytes.get(*offset..offset + magic.len()) == Some(*magic) {
+            return mime;
+        }
+    }
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" {
+        match &bytes[8..12] {
+            b"WEBP" => return "image/webp",
+            b"WAVE" => return "audio/wav",
+            _ => {}
+        }
+    }
+
+    if bytes.contains(&0) {
+        return "application/octet-stream";
+    }
+    // A sample may end mid-character; only the start needs to be UTF-8.
+    let text = match std::str::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) if e.valid_up_to() + 4 > bytes.len() => {
+            std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or_default()
+        }
+        Err(_) => return "application/octet-stream",
+    };
+    detect_text(text)
+}
+
+/// Reads the start of `path` and sniffs it.
+pub fn detect_file(path: &Path) -> std::io::Result<&'static str> {
+    let mut sample = Vec::with_capacity(SAMPLE_SIZE);
+    File::open(path)?
+        .take(SAMPLE_SIZE as u64)
+        .read_to_end(&mut sample)?;
+    Ok(detect(&sample))
+}
+
+fn detect_text(text: &str) -> &'static str {
+    let trimmed = text.trim_start_matches('\u{feff}').trim_start();
+    let lower: String = trimmed.chars().take(64).collect::<String>().to_lowercase();
+
+    if looks_like_json(trimmed) {
+        "application/json"
+    } else if lower.starts_with("<!doctype html") || lower.starts_with("<html") {
+        "text/html"
+    } else if lower.starts_with("<svg") {
+        "image/svg+xml"
+    } else if lower.starts_with("<?xml") {
+        if text.contains("<svg") {
+            "image/svg+xml"
+        } else {
+            "application/xml"
+        }
+    } else if trimmed.starts_with("%YAML") || trimmed.starts_with("---\n") {
+        "application/yaml"
+    } else if trimmed.starts_with("#!") {
+        "text/x-script"
+    } else {
+        "text/plain"
+    }
+}
+
+/// An opening bracket followed by something that can start a JSON value,
+/// so a TOML or INI `[section]` header isn't taken for an array.
+fn looks_like_json(text: &str) -> bool {
+    let mut chars = text.chars();
+    let next = |chars: std::str::Chars| chars.as_str().trim_start().chars().next();
+    match chars.next() {
+        Some('{') => matches!(next(chars), Some('"' | '}')),
+        Some('[') => matches!(
+            next(chars),
+            Some('{' | '[' | ']' | '"' | '-' | '0'..='9' | 't' | 'f' | 'n')
+        ),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_magic_numbers() {
+        assert_eq!(detect(b"\x1f\x8b\x08\x00rest"), "application/gzip");
+        assert_eq!(detect(b"PK\x03\x04\x14\x00"), "application/zip");
+        assert_eq!(detect(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), "image/png");
+        assert_eq!(detect(b"%PDF-1.7\n"), "application/pdf");
+        assert_eq!(detect(b"\x7fELF\x02\x01\x01"), "application/x-elf");
+        assert_eq!(detect(b"RIFF\0\0\0\0WEBPVP8 "), "image/webp");
+
+        let mut tar = vec![0u8; 512];
+        tar[..8].copy_from_slice(b"file.txt");
+        tar[257..263].copy_from_slice(b"ustar\0");
+        assert_eq!(detect(&tar), "application/x-tar");
+    }
+
+    #[test]
+    fn test_text_formats() {
+        assert_eq!(detect(b"  {\"a\": 1}"), "application/json");
+        assert_eq!(detect(b"\xef\xbb\xbf[1, 2]"), "application/json");
+        assert_eq!(detect(b"[package]\nname = \"x\"\n"), "text/plain");
+        assert_eq!(detect(b"<?xml version=\"1.0\"?><root/>"), "application/xml");
+        assert_eq!(detect(b"<!DOCTYPE html><html>"), "text/html");
+        assert_eq!(detect(b"---\nkey: value\n"), "application/yaml");
+        assert_eq!(detect(b"#!/bin/sh\necho hi\n"), "text/x-script");
+        assert_eq!(detect("plain caf\u{e9}".as_bytes()), "text/plain");
+        assert_eq!(detect(b""), "inode/x-empty");
+        assert_eq!(detect(b"ab\0cd"), "application/octet-stream");
+        assert_eq!(
+            detect(b"\xff\xfe\xfa garbage text"),
+            "application/octet-stream"
+        );
+        // Truncated in the middle of a multibyte character.
+        assert_eq!(detect(&"caf\u{e9}".as_bytes()[..4]), "text/plain");
+    }
+}
@@ -0,0 +1,315 @@
+//! CSV dialects: delimiter, quoting, comments and header handling, plus
+//! sniffing the delimiter, quote and header row from a sample the way
+//! Python's `csv.Sniffer` does. Comment lines are never guessed: a `#`
+//! header cell is data.
+
+use anyhow::{anyhow, bail, Result};
+use csv::{QuoteStyle, ReaderBuilder, Trim, WriterBuilder};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes read from the start of a file for sniffing.
+const SNIFF_SIZE: u64 = 64 * 1024;
+/// Records looked at when sniffing.
+const SNIFF_ROWS: usize = 50;
+const DELIMITERS: [u8; 4] = [b',', b'\t', b';', b'|'];
+
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    pub delimiter: u8,
+    pub quote: u8,
+    /// Only used when writing.
+    pub quote_style: QuoteStyle,
+    /// Lines starting with this byte are skipped when reading.
+    pub comment: Option<u8>,
+    /// Without a header row, columns are named `column_1`, `column_2`, ...
+    pub has_headers: bool,
+    /// Strip whitespace around fields (and header names) when reading.
+    pub trim: bool,
+    /// Allow rows of different lengths. Short rows are padded with empty
+    /// fields and extra fields get generated column names.
+    pub flexible: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+            quote_style: QuoteStyle::Necessary,
+            comment: None,
+            has_headers: true,
+            trim: false,
+            flexible: false,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl CsvOptions {
+    pub fn tsv() -> Self {
+        Self {
+            delimiter: b'\t',
+            ..Self::default()
+        }
+    }
+
+    /// Defaults for a file name: tab-separated for `.tsv` and `.tab`,
+    /// comma-separated otherwise.
+    pub fn for_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        match extension.as_str() {
+            "tsv" | "tab" => Self::tsv(),
+            _ => Self::default(),
+        }
+    }
+
+    /// `for_path` defaults with the delimiter and quote character sniffed
+    /// from the start of the file.
+    pub fn detect(path: &Path) -> Result<Self> {
+        let mut options = Self::for_path(path);
+        options.sniff_dialect(&read_sample(path)?);
+        Ok(options)
+    }
+
+    /// Guesses the whole dialect, including whether there is a header row.
+    pub fn sniff(sample: &[u8]) -> Self {
+        let mut options = Self::default();
+        options.sniff_dialect(sample);
+        options.has_headers = options.sniff_header(sample);
+        options
+    }
+
+    /// Sets the delimiter and quote character from `sample`, keeping the
+    /// other settings. Leaves the delimiter alone if nothing fits.
+    pub fn sniff_dialect(&mut self, sample: &[u8]) {
+        let sample = complete_lines(sample);
+        if sample.iter().all(u8::is_ascii_whitespace) {
+            return;
+        }
+
+        self.quote = sniff_quote(sample);
+
+        // The delimiter giving the most consistent field count (more than
+        // one field) wins; ties go to the earlier candidate.
+        let mut best: Option<(usize, usize, u8)> = None;
+        for delimiter in DELIMITERS {
+            let candidate = Self {
+                delimiter,
+                has_headers: false,
+                flexible: true,
+                ..self.clone()
+            };
+            let counts: Vec<usize> = candidate
+                .reader(sample)
+                .records()
+                .take(SNIFF_ROWS)
+                .map_while(|record| record.ok())
+                .map(|record| record.len())
+                .collect();
+            let Some((fields, frequency)) = mode(&counts) else {
+                continue;
+            };
+            if fields > 1 && best.is_none_or(|(f, n, _)| (frequency, fields) > (f, n)) {
+                best = Some((frequency, fields, delimiter));
+            }
+        }
+        if let Some((_, _, delimiter)) = best {
+            self.delimiter = delimiter;
+        }
+    }
+
+    /// Votes per column: when the values below the first row are all
+    /// numeric, a non-numeric first cell suggests a header and a numeric
+    /// one suggests data. No evidence either way means a header.
+    fn sniff_header(&self, sample: &[u8]) -> bool {
+        let reader = Self {
+            has_headers: false,
+            flexible: true,
+            ..self.clone()
+        }
+        .reader(complete_lines(sample));
+        let rows: Vec<csv::StringRecord> = reader
+            .into_records()
+            .take(SNIFF_ROWS)
+            .map_while(|record| record.ok())
+            .collect();
+        let Some((first, rest)) = rows.split_first() else {
+            return true;
+        };
+
+        let is_number = |s: &str| s.trim().parse::<f64>().is_ok();
+        let mut votes = 0i32;
+        for (column, cell) in first.iter().enumerate() {
+            let values: Vec<&str> = rest
+                .iter()
+                .filter_map(|row| row.get(column))
+                .filter(|v| !v.trim().is_empty())
+                .collect();
+            if !values.is_empty() && values.iter().all(|v| is_number(v)) {
+                votes += if is_number(cell) { -1 } else { 1 };
+            }
+        }
+        votes >= 0
+    }
+
+    pub fn reader<R: Read>(&self, reader: R) -> csv::Reader<R> {
+        ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .comment(self.comment)
+            .has_headers(self.has_headers)
+            .trim(if self.trim { Trim::All } else { Trim::None })
+            .flexible(self.flexible)
+            .from_reader(reader)
+    }
+
+    pub fn writer<W: std::io::Write>(&self, writer: W) -> csv::Writer<W> {
+        WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .quote_style(self.quote_style)
+            .from_writer(writer)
+    }
+}
+
+/// Parses a `--delimiter` value: a single character, `\t`, or a name
+/// (`tab`, `comma`, `semicolon`, `pipe`, `space`).
+pub fn parse_delimiter(input: &str) -> Result<u8> {
+    let byte = match input.to_lowercase().as_str() {
+        "tab" | "\\t" | "\t" => b'\t',
+        "comma" => b',',
+        "semicolon" => b';',
+        "pipe" => b'|',
+        "space" => b' ',
+        _ => parse_char(input).map_err(|_| {
+            anyhow!(
+                "Invalid delimiter: {:?} (expected a single ASCII character or tab, comma, semicolon, pipe, space)",
+                input
+            )
+        })?,
+    };
+    Ok(byte)
+}
+
+/// Parses a single ASCII character option, such as `--comment`.
+pub fn parse_char(input: &str) -> Result<u8> {
+    match input.as_bytes() {
+        [byte] if byte.is_ascii() => Ok(*byte),
+        _ => bail!("Expected a single ASCII character, got {:?}", input),
+    }
+}
+
+/// Parses a `--quote-style` value.
+pub fn parse_quote_style(input: &str) -> Result<QuoteStyle> {
+    match input.to_lowercase().replace('_', "-").as_str() {
+        "necessary" => Ok(QuoteStyle::Necessary),
+        "always" => Ok(QuoteStyle::Always),
+        "non-numeric" => Ok(QuoteStyle::NonNumeric),
+        "never" => Ok(QuoteStyle::Never),
+        _ => bail!(
+            "Invalid quote style: {:?} (expected necessary, always, non-numeric or never)",
+            input
+        ),
+    }
+}
+
+fn read_sample(path: &Path) -> Result<Vec<u8>> {
+    let mut sample = Vec::new();
+    File::open(path)?
+        .take(SNIFF_SIZE)
+        .read_to_end(&mut sample)?;
+    Ok(sample)
+}
+
+/// Drops a trailing partial line, unless the sample is a single line.
+fn complete_lines(sample: &[u8]) -> &[u8] {
+    match sample.iter().rposition(|&b| b == b'\n') {
+        Some(end) => &sample[..=end],
+        None => sample,
+    }
+}
+
+/// Single quotes win only if they open fields more often than double
+/// quotes do.
+fn sniff_quote(sample: &[u8]) -> u8 {
+    let opening = |quote: u8| {
+        sample
+            .windows(2)
+            .filter(|w| w[1] == quote && (DELIMITERS.contains(&w[0]) || w[0] == b'\n'))
+            .count()
+            + usize::from(sample.first() == Some(&quote))
+    };
+    if opening(b'\'') > opening(b'"') {
+        b'\''
+    } else {
+        b'"'
+    }
+}
+
+/// The most common value and how often it occurs (ties go to the larger
+/// value).
+fn mode(values: &[usize]) -> Option<(usize, usize)> {
+    let mut best: Option<(usize, usize)> = None;
+    for &value in values {
+        let count = values.iter().filter(|&&v| v == value).count();
+        if best.is_none_or(|(v, c)| (count, value) > (c, v)) {
+            best = Some((value, count));
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_delimiters() {
+        assert_eq!(CsvOptions::sniff(b"a,b,c\n1,2,3\n").delimiter, b',');
+        assert_eq!(CsvOptions::sniff(b"a\tb\n1\t2\n").delimiter, b'\t');
+        // A European Excel export: semicolons with decimal commas.
+        let european = "Name;Preis;Menge\nApfel;1,20;3\nBirne;0,95;10\n";
+        assert_eq!(CsvOptions::sniff(european.as_bytes()).delimiter, b';');
+        // Commas inside quotes don't count.
+        let quoted = "name|note\nAda|\"a, b, c\"\nGrace|\"d, e\"\n";
+        assert_eq!(CsvOptions::sniff(quoted.as_bytes()).delimiter, b'|');
+        // Nothing fits: keep the default.
+        assert_eq!(CsvOptions::sniff(b"just words\n").delimiter, b',');
+    }
+
+    #[test]
+    fn test_sniff_quote_and_header() {
+        let options = CsvOptions::sniff(b"'a','b'\n'1','x'\n");
+        assert_eq!(options.quote, b'\'');
+        // A `#` header cell is not a comment.
+        let options = CsvOptions::sniff(b"#,name,score\n1,Ada,9\n2,Bob,7\n");
+        assert_eq!(options.comment, None);
+        assert!(options.has_headers);
+
+        assert!(CsvOptions::sniff(b"id,price\n1,2.5\n2,3.75\n").has_headers);
+        assert!(!CsvOptions::sniff(b"1,2.5\n2,3.75\n3,1\n").has_headers);
+        assert!(CsvOptions::sniff(b"name,city\nAda,London\n").has_headers);
+    }
+
+    #[test]
+    fn test_parse_options() {
+        assert_eq!(parse_delimiter("tab").unwrap(), b'\t');
+        assert_eq!(parse_delimiter("\\t").unwrap(), b'\t');
+        assert_eq!(parse_delimiter(";").unwrap(), b';');
+        assert!(parse_delimiter(";;").is_err());
+        assert!(matches!(
+            parse_quote_style("non_numeric").unwrap(),
+            QuoteStyle::NonNumeric
+        ));
+        assert!(parse_quote_style("sometimes").is_err());
+
+        assert_eq!(CsvOptions::for_path(Path::new("a.TSV")).delimiter, b'\t');
+        assert_eq!(CsvOptions::for_path(Path::new("a.csv")).delimiter, b',');
+    }
+}
@@ -0,0 +1,204 @@
+//! An in-memory directory hierarchy with subtree sizes, built from one
+//! `Walker` pass. Shared by `tree` and `du`.
+//!
+//! Sizes are apparent sizes (file lengths, like `du --apparent-size`);
+//! directories themselves count as zero.
+
+use super::walker::{EntryType, WalkOptions, Walker};
+use anyhow::{bail, Result};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct DirNode {
+    pub name: String,
+    pub path: PathBuf,
+    /// `/`-separated path below the root; empty for the root itself.
+    pub relative: String,
+    pub depth: usize,
+    pub file_type: EntryType,
+    /// The entry's own size, or the whole subtree's for a directory.
+    pub size: u64,
+    /// Files in the subtree (1 for a file).
+    pub file_count: usize,
+    pub dir_count: usize,
+    pub children: Vec<DirNode>,
+}
+
+impl DirNode {
+    /// Walks `root` completely. `options.max_depth` is ignored so directory
+    /// sizes always cover everything below them; callers limit depth when
+    /// presenting. Unreadable subdirectories are returned as messages.
+    pub fn build(root: &Path, options: &WalkOptions) -> Result<(DirNode, Vec<String>)> {
+        if !root.is_dir() {
+            bail!("Not a directory: {}", root.display());
+        }
+
+        let walker = Walker::new(WalkOptions {
+            max_depth: None,
+            min_depth: None,
+            ..options.clone()
+        });
+        // Open directories, root first; entries arrive depth-first, so an
+        // entry at depth d closes everything deeper than d - 1.
+        let mut stack = vec![DirNode::new(
+            root.display().to_string(),
+            root.to_path_buf(),
+            String::new(),
+            0,
+            EntryType::Dir,
+            0,
+        )];
+        let errors = walker.visit(&[root], |entry| {
+            close_to(&mut stack, entry.depth);
+            let name = entry
+                .path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let size = match entry.file_type {
+                EntryType::Dir => 0,
+                _ => entry.metadata.len(),
+            };
+            let node = DirNode::new(
+                name,
+                entry.path,
+                entry.relative,
+                entry.depth,
+                entry.file_type,
+                size,
+            );
+            if node.file_type == EntryType::Dir {
+                stack.push(node);
+            } else {
+                stack.last_mut().unwrap().add_child(node);
+            }
+            Ok(())
+        })?;
+        close_to(&mut stack, 1);
+
+        Ok((stack.pop().unwrap(), errors))
+    }
+
+    fn new(
+        name: String,
+        path: PathBuf,
+        relative: String,
+        depth: usize,
+        file_type: EntryType,
+        size: u64,
+    ) -> Self {
+        Self {
+            name,
+            path,
+            relative,
+            depth,
+            file_type,
+            size,
+            file_count: usize::from(file_type != EntryType::Dir),
+            dir_count: 0,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.file_type == EntryType::Dir
+    }
+
+    fn add_child(&mut self, child: DirNode) {
+        self.size += child.size;
+        self.file_count += child.file_count;
+        self.dir_count += child.dir_count + usize::from(child.is_dir());
+        self.children.push(child);
+    }
+
+    /// Every directory in the subtree, this one included, outermost first.
+    pub fn directories(&self) -> Vec<&DirNode> {
+        let mut dirs = Vec::new();
+        let mut pending = vec![self];
+        while let Some(node) = pending.pop() {
+            if node.is_dir() {
+                dirs.push(node);
+                pending.extend(node.children.iter().rev());
+            }
+        }
+        dirs
+    }
+
+    /// Orders children recursively: directories first when `dirs_first`,
+    /// then by name (the walker's order).
+    pub fn sort(&mut self, dirs_first: bool) {
+        if dirs_first {
+            self.children.sort_by_key(|c| !c.is_dir());
+        }
+        for child in &mut self.children {
+            child.sort(dirs_first);
+        }
+    }
+}
+
+/// Pops open directories until the top is at `depth - 1`, attaching each
+/// to its parent.
+fn close_to(stack: &mut Vec<DirNode>, depth: usize) {
+    while stack.len() > depth {
+        let done = stack.pop().unwrap();
+        stack.last_mut().unwrap().add_child(done);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_handler::FileHandler;
+    use crate::utils::glob;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_build_aggregates_sizes() {
+        let dir = TempDir::new().unwrap();
+        for (path, size) in [
+            ("a/b/big.bin", 1000),
+            ("a/small.txt", 10),
+            ("c/skip/x.bin", 500),
+            ("c/y.txt", 5),
+            ("z.txt", 1),
+        ] {
+            FileHandler::write(dir.path().join(path), &"x".repeat(size)).unwrap();
+        }
+
+        let options = WalkOptions {
+            exclude: glob::compile_all(&["skip".into()]).unwrap(),
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        let (root, errors) = DirNode::build(dir.path(), &options).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(root.size, 1016);
+        assert_eq!((root.file_count, root.dir_count), (4, 3));
+
+        let names: Vec<&str> = root.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["a", "c", "z.txt"]);
+        assert_eq!(root.children[0].size, 1010);
+        assert_eq!(root.children[0].children[0].relative, "a/b");
+        assert_eq!(root.children[0].children[0].size, 1000);
+
+        let dirs: Vec<&str> = root
+            .directories()
+            .iter()
+            .map(|d| d.relative.as_str())
+            .collect();
+        assert_eq!(dirs, ["", "a", "a/b", "c"]);
+        assert!(DirNode::build(&dir.path().join("z.txt"), &options).is_err());
+    }
+
+    #[test]
+    fn test_dirs_first() {
+        let dir = TempDir::new().unwrap();
+        FileHandler::write(dir.path().join("a.txt"), "").unwrap();
+        FileHandler::write(dir.path().join("b/c.txt"), "").unwrap();
+
+        let (mut root, _) = DirNode::build(dir.path(), &WalkOptions::default()).unwrap();
+        root.sort(true);
+        let names: Vec<&str> = root.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["b", "a.txt"]);
+    }
+}
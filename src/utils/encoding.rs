@@ -0,0 +1,392 @@
+use anyhow::{bail, Context, Result};
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, Engine, GeneralPurpose, GeneralPurposeConfig};
+use percent_encoding::{percent_decode, percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+/// Input is read in chunks of this size; it is a multiple of both the base64
+/// (3 byte) and base32 (5 byte) block sizes.
+const CHUNK_SIZE: usize = 15 * 4096;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// RFC 3986 unreserved characters are left as-is; everything else is escaped.
+const URL_ESCAPED: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+// Padding is written on encode but optional on decode, so blobs that lost
+// their trailing `=` still round-trip.
+const BASE64_CONFIG: GeneralPurposeConfig =
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+const BASE64_STANDARD: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, BASE64_CONFIG);
+const BASE64_URL_SAFE: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, BASE64_CONFIG);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Base64,
+    Base64Url,
+    Base32,
+    Hex,
+    Url,
+}
+
+impl FromStr for Encoding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "base64" | "b64" => Ok(Encoding::Base64),
+            "base64url" | "base64-url" => Ok(Encoding::Base64Url),
+            "base32" | "b32" => Ok(Encoding::Base32),
+            "hex" | "base16" => Ok(Encoding::Hex),
+            "url" | "percent" => Ok(Encoding::Url),
+            _ => bail!(
+                "Unsupported encoding: {} (expected base64, base64url, base32, hex, or url)",
+                s
+            ),
+        }
+    }
+}
+
+impl Encoding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Base64 => "base64",
+            Encoding::Base64Url => "base64url",
+            Encoding::Base32 => "base32",
+            Encoding::Hex => "hex",
+            Encoding::Url => "url",
+        }
+    }
+
+    /// Raw bytes per encoded group; chunks are split on this boundary so
+    /// padding only ever appears at the very end of the output.
+    fn block_size(&self) -> usize {
+        match self {
+            Encoding::Base64 | Encoding::Base64Url => 3,
+            Encoding::Base32 => 5,
+            Encoding::Hex | Encoding::Url => 1,
+        }
+    }
+
+    fn encode_chunk(&self, bytes: &[u8]) -> String {
+        match self {
+            Encoding::Base64 => BASE64_STANDARD.encode(bytes),
+            Encoding::Base64Url => BASE64_URL_SAFE.encode(bytes),
+            Encoding::Base32 => base32_encode(bytes),
+            Encoding::Hex => bytes.iter().map(|b| format!("{:02x}", b)).collect(),
+            Encoding::Url => percent_encode(bytes, URL_ESCAPED).to_string(),
+        }
+    }
+
+    /// Length of the prefix of `text` that can be decoded without seeing
+    /// what follows it.
+    fn decodable_len(&self, text: &[u8]) -> usize {
+        match self {
+            Encoding::Base64 | Encoding::Base64Url => text.len() - text.len() % 4,
+            Encoding::Base32 => text.len() - text.len() % 8,
+            Encoding::Hex => text.len() - text.len() % 2,
+            Encoding::Url => {
+                let tail = text.len().saturating_sub(2);
+                match text[tail..].iter().position(|&b| b == b'%') {
+                    Some(offset) => tail + offset,
+                    None => text.len(),
+                }
+            }
+        }
+    }
+
+    fn decode_chunk(&self, text: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Encoding::Base64 => Ok(BASE64_STANDARD.decode(text)?),
+            Encoding::Base64Url => Ok(BASE64_URL_SAFE.decode(text)?),
+            Encoding::Base32 => base32_decode(text),
+            Encoding::Hex => hex_decode(text),
+            Encoding::Url => Ok(percent_decode(text).collect()),
+        }
+    }
+
+    /// Whether a byte is layout (line breaks, indentation) rather than data
+    /// when decoding. Percent-encoded text can't contain raw spaces, but
+    /// pasted URLs often pick up line breaks.
+    fn is_layout(&self, byte: u8) -> bool {
+        match self {
+            Encoding::Url => byte == b'\n' || byte == b'\r',
+            _ => byte.is_ascii_whitespace(),
+        }
+    }
+}
+
+/// Encodes everything from `reader` into `writer`, wrapping the output every
+/// `wrap` characters (0 disables wrapping). A trailing newline is written
+/// after non-empty output.
+pub fn encode<R: Read, W: Write>(
+    encoding: Encoding,
+    mut reader: R,
+    mut writer: W,
+    wrap: usize,
+) -> Result<()> {
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut pending = Vec::new();
+    let mut column = 0;
+    let mut written = false;
+
+    let mut emit = |text: String, writer: &mut W| -> Result<()> {
+        written |= !text.is_empty();
+        if wrap == 0 {
+            writer.write_all(text.as_bytes())?;
+            return Ok(());
+        }
+
+        // Encoded output is ASCII, so bytes and characters line up.
+        let mut rest = text.as_bytes();
+        while !rest.is_empty() {
+            let take = (wrap - column).min(rest.len());
+            writer.write_all(&rest[..take])?;
+            column += take;
+            rest = &rest[take..];
+            if column == wrap {
+                writer.write_all(b"\n")?;
+                column = 0;
+            }
+        }
+        Ok(())
+    };
+
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        pending.extend_from_slice(&buffer[..read]);
+
+        let usable = pending.len() - pending.len() % encoding.block_size();
+        emit(encoding.encode_chunk(&pending[..usable]), &mut writer)?;
+        pending.drain(..usable);
+    }
+    emit(encoding.encode_chunk(&pending), &mut writer)?;
+
+    if written && (wrap == 0 || column > 0) {
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Decodes everything from `reader` into `writer`. Line breaks (and, except
+/// for URL encoding, any whitespace) in the input are ignored.
+pub fn decode<R: Read, W: Write>(encoding: Encoding, mut reader: R, mut writer: W) -> Result<()> {
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut pending = Vec::new();
+    let invalid = || format!("Invalid {} input", encoding.name());
+
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        pending.extend(
+            buffer[..read]
+                .iter()
+                .copied()
+                .filter(|&b| !encoding.is_layout(b)),
+        );
+
+        let usable = encoding.decodable_len(&pending);
+        let bytes = encoding
+            .decode_chunk(&pending[..usable])
+            .with_context(invalid)?;
+        writer.write_all(&bytes)?;
+        pending.drain(..usable);
+    }
+
+    let bytes = encoding.decode_chunk(&pending).with_context(invalid)?;
+    writer.write_all(&bytes)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+
+    for block in bytes.chunks(5) {
+        let mut group = [0u8; 5];
+        group[..block.len()].copy_from_slice(block);
+        let bits = group.iter().fold(0u64, |acc, &b| acc << 8 | b as u64);
+
+        // 1..=5 input bytes produce 2, 4, 5, 7 or 8 significant characters.
+        let significant = (block.len() * 8).div_ceil(5);
+        for i in 0..8 {
+            if i < significant {
+                let index = (bits >> (35 - i * 5)) & 0x1f;
+                out.push(BASE32_ALPHABET[index as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+
+    out
+}
+
+fn base32_decode(text: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() / 8 * 5);
+
+    for block in text.chunks(8) {
+        let data: Vec<u8> = block.iter().copied().take_while(|&b| b != b'=').collect();
+        if block[data.len()..].iter().any(|&b| b != b'=') {
+            bail!("unexpected padding in base32 group");
+        }
+        let byte_count = match data.len() {
+            0 => continue,
+            2 => 1,
+            4 => 2,
+            5 => 3,
+            7 => 4,
+            8 => 5,
+            n => bail!("truncated base32 group of {} characters", n),
+        };
+
+        let mut bits = 0u64;
+        for &c in &data {
+            let value = match c.to_ascii_uppercase() {
+                c @ b'A'..=b'Z' => c - b'A',
+                c @ b'2'..=b'7' => c - b'2' + 26,
+                _ => bail!("invalid base32 character {:?}", c as char),
+            };
+            bits = bits << 5 | value as u64;
+        }
+        bits <<= 5 * (8 - data.len());
+
+        for i in 0..byte_count {
+            out.push((bits >> (32 - i * 8)) as u8);
+        }
+    }
+
+    Ok(out)
+}
+
+fn hex_decode(text: &[u8]) -> Result<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        bail!("odd number of hex digits");
+    }
+
+    let digit = |c: u8| match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => bail!("invalid hex character {:?}", c as char),
+    };
+
+    text.chunks(2)
+        .map(|pair| Ok(digit(pair[0])? << 4 | digit(pair[1])?))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_str(encoding: Encoding, input: &[u8], wrap: usize) -> String {
+        let mut out = Vec::new();
+        encode(encoding, input, &mut out, wrap).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn decode_bytes(encoding: Encoding, input: &str) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        decode(encoding, input.as_bytes(), &mut out)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn test_rfc4648_vectors() {
+        let cases = [
+            ("", "", "", ""),
+            ("f", "Zg==", "MY======", "66"),
+            ("fo", "Zm8=", "MZXQ====", "666f"),
+            ("foo", "Zm9v", "MZXW6===", "666f6f"),
+            ("foob", "Zm9vYg==", "MZXW6YQ=", "666f6f62"),
+            ("fooba", "Zm9vYmE=", "MZXW6YTB", "666f6f6261"),
+            ("foobar", "Zm9vYmFy", "MZXW6YTBOI======", "666f6f626172"),
+        ];
+
+        for (raw, base64, base32, hex) in cases {
+            for (encoding, expected) in [
+                (Encoding::Base64, base64),
+                (Encoding::Base32, base32),
+                (Encoding::Hex, hex),
+            ] {
+                let encoded = encode_str(encoding, raw.as_bytes(), 0);
+                assert_eq!(encoded.trim_end(), expected, "{:?} {:?}", encoding, raw);
+                assert_eq!(decode_bytes(encoding, expected).unwrap(), raw.as_bytes());
+            }
+        }
+    }
+
+    #[test]
+    fn test_url_safe_and_percent() {
+        assert_eq!(encode_str(Encoding::Base64Url, &[0xfb, 0xff], 0), "-_8=\n");
+        assert_eq!(
+            decode_bytes(Encoding::Base64Url, "-_8").unwrap(),
+            [0xfb, 0xff]
+        );
+
+        assert_eq!(
+            encode_str(Encoding::Url, "a b&c=ü~".as_bytes(), 0),
+            "a%20b%26c%3D%C3%BC~\n"
+        );
+        assert_eq!(
+            decode_bytes(Encoding::Url, "a%20b%26c%3D%C3%BC~\n").unwrap(),
+            "a b&c=ü~".as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_wrapping_and_whitespace() {
+        let encoded = encode_str(Encoding::Base64, b"hello world, hello!", 8);
+        assert_eq!(encoded, "aGVsbG8g\nd29ybGQs\nIGhlbGxv\nIQ==\n");
+        assert_eq!(
+            decode_bytes(Encoding::Base64, &encoded).unwrap(),
+            b"hello world, hello!"
+        );
+        assert_eq!(encode_str(Encoding::Hex, b"ab", 4), "6162\n");
+    }
+
+    #[test]
+    fn test_streams_across_chunks() {
+        let data: Vec<u8> = (0..CHUNK_SIZE * 2 + 7)
+            .map(|i| (i * 31 % 251) as u8)
+            .collect();
+
+        for encoding in [
+            Encoding::Base64,
+            Encoding::Base64Url,
+            Encoding::Base32,
+            Encoding::Hex,
+            Encoding::Url,
+        ] {
+            let encoded = encode_str(encoding, &data, 76);
+            assert_eq!(
+                decode_bytes(encoding, &encoded).unwrap(),
+                data,
+                "{:?}",
+                encoding
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(decode_bytes(Encoding::Base64, "Zm9v!").is_err());
+        assert!(decode_bytes(Encoding::Base32, "MZX").is_err());
+        assert!(decode_bytes(Encoding::Hex, "abc").is_err());
+        assert!("rot13".parse::<Encoding>().is_err());
+    }
+}
@@ -1,12 +1,23 @@
+use super::content_type;
+use super::csv_dialect::CsvOptions;
+use super::front_matter::FrontMatter;
+use super::glob::Glob;
+use super::hashing::{self, HashAlgorithm};
+use super::walker::{EntryType, WalkOptions, Walker};
+use super::xlsx::{self, SheetOptions};
+use super::{marshal, psych_yaml, ruby_json};
 use anyhow::{bail, Context, Result};
 use csv::{Reader, Writer};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use sha1::Sha1;
-use sha2::{Digest, Sha256, Sha512};
-use std::collections::HashMap;
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
-use std::io::Read;
-use std::path::Path;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -30,11 +41,23 @@ pub enum FileError {
     #[error("Invalid CSV: {0}")]
     InvalidCsv(String),
 
+    #[error("Invalid Excel workbook: {0}")]
+    InvalidXlsx(String),
+
+    #[error("Invalid Marshal data: {0}")]
+    InvalidMarshal(String),
+
     #[error("Unsupported format: {0}")]
     UnsupportedFormat(String),
 
+    #[error("Checksum mismatch for {0}: expected {1}, got {2}")]
+    ChecksumMismatch(String, String, String),
+
     #[error("File operation failed: {0}")]
     OperationFailed(String),
+
+    #[error("Timed out after {1:?} waiting for a lock on {0}")]
+    LockTimeout(String, Duration),
 }
 
 #[allow(dead_code)]
@@ -83,6 +106,34 @@ impl FileHandler {
         Self::write(path, &json)
     }
 
+    /// Reads, edits and atomically rewrites a JSON file under an exclusive
+    /// lock on `<path>.lock`, so concurrent updates aren't lost. A missing or
+    /// empty file starts from `T::default()`. The result is written with
+    /// `ruby_json::pretty_generate`, like Ruby's `JSON.pretty_generate`.
+    pub fn update_json<T, P, F, R>(path: P, edit: F) -> Result<R>
+    where
+        T: for<'de> Deserialize<'de> + Serialize + Default,
+        P: AsRef<Path>,
+        F: FnOnce(&mut T) -> Result<R>,
+    {
+        let path = path.as_ref();
+        let _lock = lock_for_update(path)?;
+        let content = match fs::read_to_string(path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            result => result
+                .map_err(|e| FileError::ReadError(path.display().to_string(), e.to_string()))?,
+        };
+        let mut data = if content.trim().is_empty() {
+            T::default()
+        } else {
+            serde_json::from_str(&content).map_err(|e| FileError::InvalidJson(e.to_string()))?
+        };
+
+        let result = edit(&mut data)?;
+        Self::atomic_write(path, &ruby_json::pretty_generate(&data)?)?;
+        Ok(result)
+    }
+
     pub fn read_yaml<T, P>(path: P) -> Result<T>
     where
         T: for<'de> Deserialize<'de>,
@@ -92,66 +143,257 @@ impl FileHandler {
         serde_yaml::from_str(&content).map_err(|e| FileError::InvalidYaml(e.to_string()).into())
     }
 
+    /// Writes YAML byte-for-byte as Ruby's `FileHandler.write` (`to_yaml`)
+    /// would for the same data.
     pub fn write_yaml<T, P>(path: P, data: &T) -> Result<()>
     where
         T: Serialize,
         P: AsRef<Path>,
     {
-        let yaml = serde_yaml::to_string(data)?;
+        let yaml = psych_yaml::dump(data)?;
         Self::write(path, &yaml)
     }
 
-    pub fn read_csv<P>(path: P) -> Result<Vec<HashMap<String, String>>>
+    /// Reads a Markdown file's YAML front matter and body.
+    pub fn read_front_matter<P: AsRef<Path>>(path: P) -> Result<FrontMatter> {
+        let path = path.as_ref();
+        let content = Self::read(path)?;
+        FrontMatter::parse(&content)
+            .map_err(|e| FileError::InvalidYaml(format!("{}: {}", path.display(), e)).into())
+    }
+
+    /// Writes the front matter back, leaving the body exactly as it was read.
+    pub fn write_front_matter<P: AsRef<Path>>(path: P, front_matter: &FrontMatter) -> Result<()> {
+        Self::atomic_write(path, &front_matter.render()?)
+    }
+
+    /// Reads, edits and rewrites a file's front matter under an exclusive
+    /// lock on `<path>.lock`. `edit` returns whether anything changed; the
+    /// file is only written when it did.
+    pub fn update_front_matter<P, F>(path: P, edit: F) -> Result<bool>
     where
         P: AsRef<Path>,
+        F: FnOnce(&mut FrontMatter) -> Result<bool>,
     {
-        let file = File::open(path.as_ref())?;
-        let mut reader = Reader::from_reader(file);
-        let headers = reader.headers()?.clone();
+        let path = path.as_ref();
+        if !path.exists() {
+            bail!(FileError::NotFound(path.display().to_string()));
+        }
+        let _lock = lock_for_update(path)?;
+        let mut front_matter = Self::read_front_matter(path)?;
+        let changed = edit(&mut front_matter)?;
+        if changed {
+            Self::write_front_matter(path, &front_matter)?;
+        }
+        Ok(changed)
+    }
 
-        let mut records = Vec::new();
-        for result in reader.records() {
-            let record = result?;
-            let mut map = HashMap::new();
-            for (i, field) in record.iter().enumerate() {
-                if let Some(header) = headers.get(i) {
-                    map.insert(header.to_string(), field.to_string());
-                }
-            }
-            records.push(map);
+    /// Reads a file written by Ruby's `Marshal.dump` into a JSON value.
+    pub fn read_marshal<P: AsRef<Path>>(path: P) -> Result<Value> {
+        let path = path.as_ref();
+        if !path.exists() {
+            bail!(FileError::NotFound(path.display().to_string()));
         }
 
-        Ok(records)
+        let bytes = fs::read(path).with_context(|| format!("Failed to read file: {:?}", path))?;
+        marshal::load(&bytes).map_err(|e| FileError::InvalidMarshal(e.to_string()).into())
+    }
+
+    /// Reads a CSV file as one map per row, keyed by the header row. Use
+    /// `read_csv_table` to keep the column order.
+    pub fn read_csv<P>(path: P) -> Result<Vec<HashMap<String, String>>>
+    where
+        P: AsRef<Path>,
+    {
+        let table = Self::read_csv_table(path)?;
+        Ok(table
+            .rows
+            .iter()
+            .map(|row| {
+                table
+                    .headers
+                    .iter()
+                    .cloned()
+                    .zip(row.iter().cloned())
+                    .collect()
+            })
+            .collect())
     }
 
+    /// Writes map rows with every key found in any row as a column, sorted
+    /// by name.
     pub fn write_csv<P>(path: P, data: &[HashMap<String, String>]) -> Result<()>
     where
         P: AsRef<Path>,
     {
-        if data.is_empty() {
-            return Self::write(path, "");
-        }
+        Self::write_csv_with(path, data, &CsvHeader::Union)
+    }
 
-        let file = File::create(path.as_ref())?;
-        let mut writer = Writer::from_writer(file);
+    /// Writes map rows with the columns chosen by `header`. Keys missing
+    /// from a row are written as empty fields.
+    pub fn write_csv_with<P>(
+        path: P,
+        data: &[HashMap<String, String>],
+        header: &CsvHeader,
+    ) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        Self::write_csv_table(path, &CsvTable::from_maps(data, header))
+    }
 
-        // Write headers
-        let headers: Vec<_> = data[0].keys().cloned().collect();
-        writer.write_record(&headers)?;
+    /// Reads a CSV file keeping the header row and every record in file
+    /// order, so `write_csv_table` reproduces it. `.tsv` files are read as
+    /// tab-separated.
+    pub fn read_csv_table<P: AsRef<Path>>(path: P) -> Result<CsvTable> {
+        let path = path.as_ref();
+        Self::read_csv_table_with(path, &CsvOptions::for_path(path))
+    }
 
-        // Write data
-        for row in data {
-            let record: Vec<_> = headers
+    /// Reads a CSV file in the given dialect. Without a header row, or for
+    /// fields past the header in a flexible file, columns are named
+    /// `column_1`, `column_2`, ...; short rows are padded with empty fields.
+    pub fn read_csv_table_with<P: AsRef<Path>>(path: P, options: &CsvOptions) -> Result<CsvTable> {
+        let mut reader = csv_reader(path.as_ref(), options)?;
+        let mut headers: Vec<String> = if options.has_headers {
+            reader
+                .headers()
+                .map_err(invalid_csv)?
                 .iter()
-                .map(|h| row.get(h).unwrap_or(&String::new()).clone())
-                .collect();
-            writer.write_record(&record)?;
+                .map(String::from)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        for result in reader.records() {
+            rows.push(
+                result
+                    .map_err(invalid_csv)?
+                    .iter()
+                    .map(String::from)
+                    .collect(),
+            );
+        }
+
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        for i in headers.len()..width {
+            headers.push(format!("column_{}", i + 1));
+        }
+        for row in &mut rows {
+            row.resize(headers.len(), String::new());
         }
 
+        Ok(CsvTable { headers, rows })
+    }
+
+    /// Writes the header row (if any) and then each record, tab-separated
+    /// for `.tsv` files.
+    pub fn write_csv_table<P: AsRef<Path>>(path: P, table: &CsvTable) -> Result<()> {
+        let path = path.as_ref();
+        Self::write_csv_table_with(path, table, &CsvOptions::for_path(path))
+    }
+
+    /// Writes a table with the given delimiter, quote character and quote
+    /// style.
+    pub fn write_csv_table_with<P: AsRef<Path>>(
+        path: P,
+        table: &CsvTable,
+        options: &CsvOptions,
+    ) -> Result<()> {
+        let mut writer = csv_writer(path.as_ref(), options)?;
+        if !table.headers.is_empty() {
+            writer.write_record(&table.headers)?;
+        }
+        for row in &table.rows {
+            writer.write_record(row)?;
+        }
         writer.flush()?;
         Ok(())
     }
 
+    /// Deserializes each record into `T`, matching struct fields to the
+    /// header row by name.
+    pub fn read_csv_as<T, P>(path: P) -> Result<Vec<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let mut reader = csv_reader(path, &CsvOptions::for_path(path))?;
+        reader
+            .deserialize()
+            .map(|record| record.map_err(invalid_csv))
+            .collect()
+    }
+
+    /// Serializes records with a header row of `T`'s field names, in
+    /// declaration order. An empty slice writes an empty file.
+    pub fn write_csv_from<T, P>(path: P, records: &[T]) -> Result<()>
+    where
+        T: Serialize,
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let mut writer = csv_writer(path, &CsvOptions::for_path(path))?;
+        for record in records {
+            writer.serialize(record).map_err(invalid_csv)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads one sheet of an `.xlsx` workbook into the same shape as
+    /// `read_csv`: one map per row, keyed by the header row.
+    pub fn read_xlsx<P>(path: P, options: &SheetOptions) -> Result<Vec<HashMap<String, String>>>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        if !path.exists() {
+            bail!(FileError::NotFound(path.display().to_string()));
+        }
+
+        let bytes = fs::read(path)
+            .map_err(|e| FileError::ReadError(path.display().to_string(), e.to_string()))?;
+        let sheet = xlsx::read_sheet(&bytes, options)
+            .map_err(|e| FileError::InvalidXlsx(format!("{:#}", e)))?;
+
+        Ok(sheet
+            .rows
+            .into_iter()
+            .map(|row| sheet.headers.iter().cloned().zip(row).collect())
+            .collect())
+    }
+
+    /// Writes records as a single-sheet workbook. Columns are sorted by name
+    /// since the maps carry no order.
+    pub fn write_xlsx<P>(path: P, data: &[HashMap<String, String>]) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let headers: Vec<String> = data
+            .iter()
+            .flat_map(|row| row.keys().cloned())
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let rows: Vec<Vec<String>> = data
+            .iter()
+            .map(|row| {
+                headers
+                    .iter()
+                    .map(|h| row.get(h).cloned().unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+
+        let bytes = xlsx::write_workbook("Sheet1", &headers, &rows)?;
+        Self::atomic_write_with(path, |out| Ok(out.write_all(&bytes)?))
+    }
+
     pub fn copy<P, Q>(source: P, destination: Q) -> Result<()>
     where
         P: AsRef<Path>,
@@ -216,49 +458,427 @@ impl FileHandler {
         Ok(metadata.len())
     }
 
-    pub fn checksum<P: AsRef<Path>>(path: P, algorithm: &str) -> Result<String> {
+    /// Hashes the file in fixed-size chunks, so memory use doesn't grow
+    /// with the file.
+    pub fn checksum<P: AsRef<Path>>(path: P, algorithm: HashAlgorithm) -> Result<String> {
+        let mut digests = Self::checksums(path, &[algorithm])?;
+        Ok(digests.remove(0))
+    }
+
+    /// Computes several digests in a single pass over the file, returned in
+    /// the order requested.
+    pub fn checksums<P: AsRef<Path>>(path: P, algorithms: &[HashAlgorithm]) -> Result<Vec<String>> {
+        let path = path.as_ref();
+        if !path.exists() {
+            bail!(FileError::NotFound(path.display().to_string()));
+        }
+
+        let file = File::open(path)
+            .map_err(|e| FileError::ReadError(path.display().to_string(), e.to_string()))?;
+        hashing::digest_reader(file, algorithms)
+            .map_err(|e| FileError::ReadError(path.display().to_string(), e.to_string()).into())
+    }
+
+    /// Hashes many files on a small pool of threads, one file at a time per
+    /// worker. Results come back in input order.
+    pub fn checksum_files(jobs: &[(PathBuf, HashAlgorithm)]) -> Vec<Result<String>> {
+        let workers = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(jobs.len());
+        if workers <= 1 {
+            return jobs
+                .iter()
+                .map(|(path, algorithm)| Self::checksum(path, *algorithm))
+                .collect();
+        }
+
+        let next = AtomicUsize::new(0);
+        let mut results: Vec<(usize, Result<String>)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut done = Vec::new();
+                        loop {
+                            let index = next.fetch_add(1, AtomicOrdering::Relaxed);
+                            let Some((path, algorithm)) = jobs.get(index) else {
+                                break;
+                            };
+                            done.push((index, Self::checksum(path, *algorithm)));
+                        }
+                        done
+                    })
+                })
+                .collect();
+
+            // A worker that panicked would leave its jobs without results,
+            // so re-raise the panic rather than return a short list.
+            handles
+                .into_iter()
+                .flat_map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect()
+        });
+
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
+    /// Stats `path`, following symlinks; `is_symlink` and `symlink_target`
+    /// still describe `path` itself.
+    pub fn stats<P: AsRef<Path>>(path: P) -> Result<FileStats> {
         let path = path.as_ref();
         if !path.exists() {
             bail!(FileError::NotFound(path.display().to_string()));
         }
 
-        let mut file = File::open(path)?;
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)?;
+        let mut stats = FileStats::from_metadata(&fs::metadata(path)?)?;
+        if let Ok(target) = fs::read_link(path) {
+            stats.is_symlink = true;
+            stats.symlink_target = Some(target);
+        }
+        stats.content_type = sniff(path, &stats);
+        Ok(stats)
+    }
+
+    /// Stats `path` itself, like `lstat`: a symlink (even a dangling one)
+    /// is described rather than followed.
+    pub fn symlink_stats<P: AsRef<Path>>(path: P) -> Result<FileStats> {
+        let path = path.as_ref();
+        let metadata = fs::symlink_metadata(path)
+            .map_err(|_| FileError::NotFound(path.display().to_string()))?;
+
+        let mut stats = FileStats::from_metadata(&metadata)?;
+        if stats.is_symlink {
+            stats.symlink_target = Some(fs::read_link(path)?);
+        }
+        stats.content_type = sniff(path, &stats);
+        Ok(stats)
+    }
 
-        let hash = match algorithm {
-            "md5" => {
-                let digest = md5::compute(&buffer);
-                format!("{:x}", digest)
+    /// Searches `paths` line by line for `regex`. Directories are walked
+    /// recursively (filtered by the include/exclude globs); files named
+    /// explicitly are always searched. Binary files are skipped. Like grep,
+    /// paths that can't be read are noted in `errors` and the search goes on.
+    pub fn search<P: AsRef<Path>>(
+        paths: &[P],
+        regex: &Regex,
+        options: &SearchOptions,
+    ) -> Result<SearchResults> {
+        let mut results = SearchResults::default();
+        let mut files = Vec::new();
+        for path in paths {
+            match Self::visit_files(&[path], options, &mut files) {
+                Ok(errors) => results.errors.extend(errors),
+                Err(e) => results.errors.push(e.to_string()),
             }
-            "sha1" => {
-                let mut hasher = Sha1::new();
-                hasher.update(&buffer);
-                format!("{:x}", hasher.finalize())
+        }
+
+        for path in files {
+            match Self::search_file(&path, regex, options.context) {
+                Ok(Some(matches)) => {
+                    results.files_searched += 1;
+                    if !matches.is_empty() {
+                        results.files.push(FileMatches { path, matches });
+                    }
+                }
+                Ok(None) => results.binary_skipped += 1,
+                Err(e) => results.errors.push(e.to_string()),
             }
-            "sha256" => {
-                let mut hasher = Sha256::new();
-                hasher.update(&buffer);
-                format!("{:x}", hasher.finalize())
+        }
+
+        Ok(results)
+    }
+
+    /// Expands `paths` into files: directories are walked (sorted, honouring
+    /// the include/exclude globs), files are taken as given.
+    pub fn list_files<P: AsRef<Path>>(
+        paths: &[P],
+        options: &SearchOptions,
+    ) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let errors = Self::visit_files(paths, options, &mut files)?;
+        if let Some(error) = errors.into_iter().next() {
+            bail!(error);
+        }
+        Ok(files)
+    }
+
+    /// Appends the files under `paths` to `files`, returning the
+    /// subdirectories that couldn't be read.
+    fn visit_files<P: AsRef<Path>>(
+        paths: &[P],
+        options: &SearchOptions,
+        files: &mut Vec<PathBuf>,
+    ) -> Result<Vec<String>> {
+        let walker = Walker::new(WalkOptions {
+            include: options.include.clone(),
+            exclude: options.exclude.clone(),
+            ..Default::default()
+        });
+        walker.visit(paths, |entry| {
+            // Symlinked directories are not followed to avoid cycles, but
+            // links to files are listed.
+            if entry.depth == 0 || (entry.file_type != EntryType::Dir && entry.path.is_file()) {
+                files.push(entry.path);
             }
-            "sha512" => {
-                let mut hasher = Sha512::new();
-                hasher.update(&buffer);
-                format!("{:x}", hasher.finalize())
+            Ok(())
+        })
+    }
+
+    /// Returns the lines of one file matching `regex`, each with up to
+    /// `context` surrounding lines, or `None` if the file looks binary.
+    pub fn search_file<P: AsRef<Path>>(
+        path: P,
+        regex: &Regex,
+        context: usize,
+    ) -> Result<Option<Vec<SearchMatch>>> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)
+            .map_err(|e| FileError::ReadError(path.display().to_string(), e.to_string()))?;
+        if looks_binary(&bytes) {
+            return Ok(None);
+        }
+
+        let text = String::from_utf8_lossy(&bytes);
+        let lines: Vec<&str> = text
+            .split_inclusive('\n')
+            .map(|line| {
+                let line = line.strip_suffix('\n').unwrap_or(line);
+                line.strip_suffix('\r').unwrap_or(line)
+            })
+            .collect();
+
+        let hits: Vec<(usize, Vec<(usize, usize)>)> = lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let ranges: Vec<(usize, usize)> = regex
+                    .find_iter(line)
+                    .filter(|m| !m.is_empty())
+                    .map(|m| (m.start(), m.end()))
+                    .collect();
+                (!ranges.is_empty() || regex.is_match(line)).then_some((index, ranges))
+            })
+            .collect();
+
+        let matches = hits
+            .iter()
+            .enumerate()
+            .map(|(k, (index, ranges))| {
+                // Context never extends over a neighbouring match; that line
+                // is reported as a match in its own right.
+                let previous = k.checked_sub(1).map_or(0, |p| hits[p].0 + 1);
+                let next = hits.get(k + 1).map_or(lines.len(), |(n, _)| *n);
+                let before_start = index.saturating_sub(context).max(previous);
+                let after_end = (index + 1 + context).min(next);
+
+                SearchMatch {
+                    line_number: index + 1,
+                    column: ranges
+                        .first()
+                        .map_or(1, |&(start, _)| lines[*index][..start].chars().count() + 1),
+                    line: lines[*index].to_string(),
+                    ranges: ranges.clone(),
+                    before: lines[before_start..*index]
+                        .iter()
+                        .map(|l| l.to_string())
+                        .collect(),
+                    after: lines[index + 1..after_end]
+                        .iter()
+                        .map(|l| l.to_string())
+                        .collect(),
+                }
+            })
+            .collect();
+
+        Ok(Some(matches))
+    }
+
+    /// Replaces `path` so that readers, and the file system after a crash,
+    /// see either the old content or the new, never a partial write.
+    pub fn atomic_write<P: AsRef<Path>>(path: P, content: &str) -> Result<()> {
+        Self::atomic_write_with(path, |writer| Ok(writer.write_all(content.as_bytes())?))
+    }
+
+    /// Streams new content into a uniquely named temp file next to `path`,
+    /// fsyncs it, renames it over `path` and fsyncs the directory. An
+    /// existing file keeps its mode and, where permitted, its owner and
+    /// group; a symlink keeps pointing at the replaced file. If `write`
+    /// fails, `path` is left untouched and the temp file is removed.
+    pub fn atomic_write_with<P, F>(path: P, write: F) -> Result<()>
+    where
+        P: AsRef<Path>,
+        F: FnOnce(&mut dyn Write) -> Result<()>,
+    {
+        let path = path.as_ref();
+        let target = match fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
             }
-            _ => bail!("Unsupported algorithm: {}", algorithm),
+            _ => path.to_path_buf(),
         };
+        let dir = match target.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        fs::create_dir_all(&dir)?;
+        let write_error =
+            |e: std::io::Error| FileError::WriteError(target.display().to_string(), e.to_string());
+
+        let existing = fs::metadata(&target).ok();
+        let name = target
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut builder = tempfile::Builder::new();
+        let prefix = format!(".{}.", name);
+        builder.prefix(&prefix).suffix(".tmp");
+        // A new file gets the usual 0666 less umask instead of tempfile's 0600.
+        #[cfg(unix)]
+        if existing.is_none() {
+            use std::os::unix::fs::PermissionsExt;
+            builder.permissions(fs::Permissions::from_mode(0o666));
+        }
+        let mut temp = builder.tempfile_in(&dir).map_err(write_error)?;
+
+        let mut writer = BufWriter::new(temp.as_file_mut());
+        write(&mut writer)?;
+        writer.flush().map_err(write_error)?;
+        drop(writer);
 
-        Ok(hash)
+        if let Some(metadata) = &existing {
+            keep_owner_and_mode(temp.as_file(), metadata).map_err(write_error)?;
+        }
+        temp.as_file().sync_all().map_err(write_error)?;
+        temp.persist(&target).map_err(|e| write_error(e.error))?;
+        sync_dir(&dir).map_err(write_error)?;
+
+        Ok(())
     }
+}
 
-    pub fn stats<P: AsRef<Path>>(path: P) -> Result<FileStats> {
-        let path = path.as_ref();
-        if !path.exists() {
-            bail!(FileError::NotFound(path.display().to_string()));
+/// An advisory lock (`flock` on Unix, `LockFileEx` on Windows) held until
+/// dropped. Other processes only notice it if they lock the same file too.
+#[derive(Debug)]
+pub struct FileLock {
+    /// `None` for a nested `lock_for_update` on a file this thread already
+    /// holds exclusively.
+    file: Option<File>,
+    path: PathBuf,
+    /// The key registered in `HELD`, for exclusive locks.
+    held: Option<PathBuf>,
+}
+
+thread_local! {
+    /// Lock files this thread holds exclusively, so `lock_for_update` can
+    /// run inside a lock the caller already took (such as `--lock`) instead
+    /// of waiting on itself.
+    static HELD: RefCell<HashSet<PathBuf>> = RefCell::new(HashSet::new());
+}
+
+#[allow(dead_code)]
+impl FileLock {
+    fn new(file: File, path: &Path, mode: LockMode) -> Self {
+        let held = match mode {
+            LockMode::Exclusive => {
+                let key = held_key(path);
+                HELD.with(|held| held.borrow_mut().insert(key.clone()));
+                Some(key)
+            }
+            LockMode::Shared => None,
+        };
+        Self {
+            file: Some(file),
+            path: path.to_path_buf(),
+            held,
         }
+    }
 
-        let metadata = fs::metadata(path)?;
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        if let Some(key) = &self.held {
+            HELD.with(|held| held.borrow_mut().remove(key));
+        }
+        if let Some(file) = &self.file {
+            let _ = file.unlock();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LockMode {
+    Shared,
+    Exclusive,
+}
+
+#[allow(dead_code)]
+impl FileHandler {
+    /// Waits for an exclusive lock on `path`, creating the file if needed.
+    pub fn lock_exclusive<P: AsRef<Path>>(path: P) -> Result<FileLock> {
+        lock(path.as_ref(), LockMode::Exclusive, None)
+    }
+
+    /// Waits for a shared lock on `path`, creating the file if needed.
+    pub fn lock_shared<P: AsRef<Path>>(path: P) -> Result<FileLock> {
+        lock(path.as_ref(), LockMode::Shared, None)
+    }
+
+    /// Waits at most `timeout` for an exclusive lock, then fails with
+    /// `FileError::LockTimeout`.
+    pub fn lock_exclusive_timeout<P: AsRef<Path>>(path: P, timeout: Duration) -> Result<FileLock> {
+        lock(path.as_ref(), LockMode::Exclusive, Some(timeout))
+    }
+
+    pub fn lock_shared_timeout<P: AsRef<Path>>(path: P, timeout: Duration) -> Result<FileLock> {
+        lock(path.as_ref(), LockMode::Shared, Some(timeout))
+    }
+
+    /// Takes an exclusive lock only if nobody else holds one.
+    pub fn try_lock_exclusive<P: AsRef<Path>>(path: P) -> Result<Option<FileLock>> {
+        try_lock(path.as_ref(), LockMode::Exclusive)
+    }
+
+    pub fn try_lock_shared<P: AsRef<Path>>(path: P) -> Result<Option<FileLock>> {
+        try_lock(path.as_ref(), LockMode::Shared)
+    }
+}
+
+#[derive(Debug)]
+#[allow(dead_code)]
+pub struct FileStats {
+    pub size: u64,
+    pub modified_at: std::time::SystemTime,
+    pub created_at: Option<std::time::SystemTime>,
+    pub accessed_at: Option<std::time::SystemTime>,
+    pub is_directory: bool,
+    pub is_file: bool,
+    pub is_symlink: bool,
+    pub symlink_target: Option<PathBuf>,
+    pub permissions: String,
+    /// Owner, inode, link count and device are `None` off Unix.
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    pub inode: Option<u64>,
+    pub hard_links: Option<u64>,
+    pub device: Option<u64>,
+    /// MIME type sniffed from the content (see `content_type::detect`);
+    /// only filled in by `FileHandler::stats` and `symlink_stats`.
+    pub content_type: Option<String>,
+}
+
+impl FileStats {
+    pub fn from_metadata(metadata: &fs::Metadata) -> Result<Self> {
+        #[cfg(unix)]
+        use std::os::unix::fs::MetadataExt;
 
         Ok(FileStats {
             size: metadata.len(),
@@ -267,6 +887,8 @@ impl FileHandler {
             accessed_at: metadata.accessed().ok(),
             is_directory: metadata.is_dir(),
             is_file: metadata.is_file(),
+            is_symlink: metadata.file_type().is_symlink(),
+            symlink_target: None,
             #[cfg(unix)]
             permissions: {
                 use std::os::unix::fs::PermissionsExt;
@@ -274,35 +896,303 @@ impl FileHandler {
             },
             #[cfg(not(unix))]
             permissions: String::from("N/A"),
+            #[cfg(unix)]
+            uid: Some(metadata.uid()),
+            #[cfg(unix)]
+            gid: Some(metadata.gid()),
+            #[cfg(unix)]
+            inode: Some(metadata.ino()),
+            #[cfg(unix)]
+            hard_links: Some(metadata.nlink()),
+            #[cfg(unix)]
+            device: Some(metadata.dev()),
+            #[cfg(not(unix))]
+            uid: None,
+            #[cfg(not(unix))]
+            gid: None,
+            #[cfg(not(unix))]
+            inode: None,
+            #[cfg(not(unix))]
+            hard_links: None,
+            #[cfg(not(unix))]
+            device: None,
+            content_type: None,
         })
     }
 
-    pub fn atomic_write<P: AsRef<Path>>(path: P, content: &str) -> Result<()> {
-        let path = path.as_ref();
-        let temp_path = path.with_extension(format!("tmp.{}", std::process::id()));
+    /// `directory`, `symlink`, `file` or `other`.
+    pub fn kind(&self) -> &'static str {
+        if self.is_symlink && !self.is_file && !self.is_directory {
+            "symlink"
+        } else if self.is_directory {
+            "directory"
+        } else if self.is_file {
+            "file"
+        } else {
+            "other"
+        }
+    }
+
+    /// Timestamps are RFC 3339 in UTC; unavailable ones are `null`.
+    pub fn to_json(&self) -> Value {
+        let time = |t: &std::time::SystemTime| {
+            chrono::DateTime::<chrono::Utc>::from(*t)
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+        };
+        json!({
+            "size": self.size,
+            "modified_at": time(&self.modified_at),
+            "created_at": self.created_at.as_ref().map(time),
+            "accessed_at": self.accessed_at.as_ref().map(time),
+            "is_directory": self.is_directory,
+            "is_file": self.is_file,
+            "is_symlink": self.is_symlink,
+            "symlink_target": self.symlink_target.as_ref().map(|t| t.display().to_string()),
+            "permissions": self.permissions,
+            "uid": self.uid,
+            "gid": self.gid,
+            "inode": self.inode,
+            "hard_links": self.hard_links,
+            "device": self.device,
+            "content_type": self.content_type,
+        })
+    }
+}
 
-        Self::write(&temp_path, content)?;
-        fs::rename(&temp_path, path)?;
+/// Content type for stats: sniffed for files, `inode/*` otherwise. `None`
+/// if the file can't be read.
+fn sniff(path: &Path, stats: &FileStats) -> Option<String> {
+    let mime = if stats.is_file {
+        content_type::detect_file(path).ok()?
+    } else if stats.is_directory {
+        "inode/directory"
+    } else if stats.is_symlink {
+        "inode/symlink"
+    } else {
+        "inode/x-special"
+    };
+    Some(mime.to_string())
+}
 
-        Ok(())
+/// A CSV file as read: the header row and the records, in file order.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CsvTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// How `write_csv_with` picks columns for map rows.
+#[derive(Debug, Clone, PartialEq)]
+#[allow(dead_code)]
+pub enum CsvHeader {
+    /// Every key found in any row, sorted by name.
+    Union,
+    /// Exactly these columns, in this order; other keys are dropped.
+    Columns(Vec<String>),
+}
+
+#[allow(dead_code)]
+impl CsvTable {
+    pub fn from_maps(data: &[HashMap<String, String>], header: &CsvHeader) -> Self {
+        let headers = match header {
+            CsvHeader::Union => data
+                .iter()
+                .flat_map(|row| row.keys().cloned())
+                .collect::<std::collections::BTreeSet<_>>()
+                .into_iter()
+                .collect(),
+            CsvHeader::Columns(columns) => columns.clone(),
+        };
+        let rows = data
+            .iter()
+            .map(|row| {
+                headers
+                    .iter()
+                    .map(|h| row.get(h).cloned().unwrap_or_default())
+                    .collect()
+            })
+            .collect();
+        Self { headers, rows }
+    }
+
+    /// The field of record `row` under `column`.
+    pub fn get(&self, row: usize, column: &str) -> Option<&str> {
+        let index = self.headers.iter().position(|h| h == column)?;
+        self.rows.get(row)?.get(index).map(String::as_str)
     }
 }
 
+#[derive(Debug, Default, Clone)]
+pub struct SearchOptions {
+    pub include: Vec<Glob>,
+    pub exclude: Vec<Glob>,
+    pub context: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct SearchResults {
+    pub files_searched: usize,
+    pub binary_skipped: usize,
+    pub files: Vec<FileMatches>,
+    /// Paths that couldn't be read.
+    pub errors: Vec<String>,
+}
+
 #[derive(Debug)]
-#[allow(dead_code)]
-pub struct FileStats {
-    pub size: u64,
-    pub modified_at: std::time::SystemTime,
-    pub created_at: Option<std::time::SystemTime>,
-    pub accessed_at: Option<std::time::SystemTime>,
-    pub is_directory: bool,
-    pub is_file: bool,
-    pub permissions: String,
+pub struct FileMatches {
+    pub path: PathBuf,
+    pub matches: Vec<SearchMatch>,
+}
+
+/// A matching line. `column` is the 1-based character position of the
+/// first match, while `ranges` are byte offsets of each match within `line`.
+#[derive(Debug)]
+pub struct SearchMatch {
+    pub line_number: usize,
+    pub column: usize,
+    pub line: String,
+    pub ranges: Vec<(usize, usize)>,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+fn csv_reader(path: &Path, options: &CsvOptions) -> Result<Reader<File>> {
+    if !path.exists() {
+        bail!(FileError::NotFound(path.display().to_string()));
+    }
+    let file = File::open(path)
+        .map_err(|e| FileError::ReadError(path.display().to_string(), e.to_string()))?;
+    Ok(options.reader(file))
+}
+
+fn csv_writer(path: &Path, options: &CsvOptions) -> Result<Writer<File>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = File::create(path)
+        .map_err(|e| FileError::WriteError(path.display().to_string(), e.to_string()))?;
+    Ok(options.writer(file))
+}
+
+fn invalid_csv(error: csv::Error) -> anyhow::Error {
+    FileError::InvalidCsv(error.to_string()).into()
+}
+
+fn open_lock_file(path: &Path) -> Result<File> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(|e| FileError::OperationFailed(format!("lock {}: {}", path.display(), e)).into())
+}
+
+fn lock(path: &Path, mode: LockMode, timeout: Option<Duration>) -> Result<FileLock> {
+    let Some(timeout) = timeout else {
+        let file = open_lock_file(path)?;
+        match mode {
+            LockMode::Shared => file.lock_shared(),
+            LockMode::Exclusive => file.lock(),
+        }
+        .map_err(|e| FileError::OperationFailed(format!("lock {}: {}", path.display(), e)))?;
+        return Ok(FileLock::new(file, path, mode));
+    };
+
+    // Polls with backoff: the platform lock calls can't time out.
+    let deadline = Instant::now() + timeout;
+    let mut delay = Duration::from_millis(5);
+    loop {
+        if let Some(lock) = try_lock(path, mode)? {
+            return Ok(lock);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            bail!(FileError::LockTimeout(path.display().to_string(), timeout));
+        }
+        std::thread::sleep(delay.min(deadline - now));
+        delay = (delay * 2).min(Duration::from_millis(100));
+    }
+}
+
+fn try_lock(path: &Path, mode: LockMode) -> Result<Option<FileLock>> {
+    let file = open_lock_file(path)?;
+    let result = match mode {
+        LockMode::Shared => file.try_lock_shared(),
+        LockMode::Exclusive => file.try_lock(),
+    };
+    match result {
+        Ok(()) => Ok(Some(FileLock::new(file, path, mode))),
+        Err(std::fs::TryLockError::WouldBlock) => Ok(None),
+        Err(std::fs::TryLockError::Error(e)) => {
+            bail!(FileError::OperationFailed(format!(
+                "lock {}: {}",
+                path.display(),
+                e
+            )))
+        }
+    }
+}
+
+/// Locks `<path>.lock` before `path` is read, edited and replaced with
+/// `atomic_write`. A sidecar is used because the rename swaps out the data
+/// file's inode, and with it any lock held on it. Lock files are left in
+/// place: removing one would let two processes lock different inodes.
+fn lock_for_update(path: &Path) -> Result<FileLock> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    let lock_path = path.with_file_name(name);
+    if HELD.with(|held| held.borrow().contains(&held_key(&lock_path))) {
+        return Ok(FileLock {
+            file: None,
+            path: lock_path,
+            held: None,
+        });
+    }
+    lock(&lock_path, LockMode::Exclusive, None)
+}
+
+fn held_key(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Gives a replacement file the original's owner, group and mode. Changing
+/// the owner needs privileges, so a refusal there is not an error.
+fn keep_owner_and_mode(file: &File, metadata: &fs::Metadata) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        match std::os::unix::fs::fchown(file, Some(metadata.uid()), Some(metadata.gid())) {
+            Err(e) if e.kind() != std::io::ErrorKind::PermissionDenied => return Err(e),
+            _ => {}
+        }
+    }
+    // After chown, which may clear setuid and setgid bits.
+    file.set_permissions(metadata.permissions())
+}
+
+/// Makes a rename in `dir` durable. Directories can't be opened for syncing
+/// off Unix, where this is a no-op.
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+/// Treats content with a NUL byte in its first 8 KiB as binary, like grep.
+fn looks_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(8192).any(|&b| b == 0)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::glob;
     use tempfile::TempDir;
 
     #[test]
@@ -338,6 +1228,20 @@ mod tests {
         assert_eq!(data, loaded);
     }
 
+    #[test]
+    fn test_write_yaml() {
+        let dir = TempDir::new().unwrap();
+        let file_path = dir.path().join("test.yml");
+
+        let data = serde_json::json!({"name": "Test", "tags": ["a", "true"]});
+        FileHandler::write_yaml(&file_path, &data).unwrap();
+
+        let content = FileHandler::read(&file_path).unwrap();
+        assert_eq!(content, "---\nname: Test\ntags:\n- a\n- 'true'\n");
+        let loaded: Value = FileHandler::read_yaml(&file_path).unwrap();
+        assert_eq!(loaded, data);
+    }
+
     #[test]
     fn test_copy_file() {
         let dir = TempDir::new().unwrap();
@@ -384,10 +1288,415 @@ mod tests {
 
         FileHandler::write(&file_path, "Hello World").unwrap();
 
-        let sha256 = FileHandler::checksum(&file_path, "sha256").unwrap();
+        let sha256 = FileHandler::checksum(&file_path, HashAlgorithm::Sha256).unwrap();
         assert_eq!(sha256.len(), 64); // SHA256 is 64 hex chars
 
-        let md5 = FileHandler::checksum(&file_path, "md5").unwrap();
+        let md5 = FileHandler::checksum(&file_path, HashAlgorithm::Md5).unwrap();
         assert_eq!(md5.len(), 32); // MD5 is 32 hex chars
+
+        let digests = FileHandler::checksums(
+            &file_path,
+            &[
+                HashAlgorithm::Md5,
+                HashAlgorithm::Blake3,
+                HashAlgorithm::Crc32,
+            ],
+        )
+        .unwrap();
+        assert_eq!(digests[0], md5);
+        assert_eq!(digests[1].len(), 64);
+        assert_eq!(digests[2], "4a17b156");
+    }
+
+    #[test]
+    fn test_csv_table_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("in.csv");
+        let copy = dir.path().join("out.csv");
+        let content = "zeta,alpha,mid\n1,\"a, b\",x\n2,,\"say \"\"hi\"\"\"\n";
+        FileHandler::write(&source, content).unwrap();
+
+        let table = FileHandler::read_csv_table(&source).unwrap();
+        assert_eq!(table.headers, ["zeta", "alpha", "mid"]);
+        assert_eq!(table.get(1, "mid"), Some("say \"hi\""));
+        assert_eq!(table.get(2, "mid"), None);
+
+        FileHandler::write_csv_table(&copy, &table).unwrap();
+        assert_eq!(FileHandler::read(&copy).unwrap(), content);
+    }
+
+    #[test]
+    fn test_write_csv_headers() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("maps.csv");
+        let row = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let data = vec![row(&[("b", "1"), ("a", "2")]), row(&[("c", "3")])];
+
+        FileHandler::write_csv(&path, &data).unwrap();
+        assert_eq!(FileHandler::read(&path).unwrap(), "a,b,c\n2,1,\n,,3\n");
+
+        let columns = CsvHeader::Columns(vec!["c".into(), "a".into()]);
+        FileHandler::write_csv_with(&path, &data, &columns).unwrap();
+        assert_eq!(FileHandler::read(&path).unwrap(), "c,a\n,2\n3,\n");
+    }
+
+    #[test]
+    fn test_typed_csv() {
+        #[derive(Serialize, Deserialize, PartialEq, Debug)]
+        struct Person {
+            name: String,
+            age: u32,
+            email: Option<String>,
+        }
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("people.csv");
+        let people = vec![
+            Person {
+                name: "Ada".into(),
+                age: 36,
+                email: None,
+            },
+            Person {
+                name: "Grace".into(),
+                age: 85,
+                email: Some("grace@example.com".into()),
+            },
+        ];
+
+        FileHandler::write_csv_from(&path, &people).unwrap();
+        assert_eq!(
+            FileHandler::read(&path).unwrap(),
+            "name,age,email\nAda,36,\nGrace,85,grace@example.com\n"
+        );
+        let loaded: Vec<Person> = FileHandler::read_csv_as(&path).unwrap();
+        assert_eq!(loaded, people);
+
+        FileHandler::write(&path, "name,age\nAda,old\n").unwrap();
+        let error = FileHandler::read_csv_as::<Person, _>(&path).unwrap_err();
+        assert!(error.to_string().starts_with("Invalid CSV"), "{}", error);
+    }
+
+    #[test]
+    fn test_csv_dialects() {
+        let dir = TempDir::new().unwrap();
+        let tsv = dir.path().join("data.tsv");
+        let table = CsvTable {
+            headers: vec!["name".into(), "note".into()],
+            rows: vec![vec!["Ada".into(), "a, b".into()]],
+        };
+        FileHandler::write_csv_table(&tsv, &table).unwrap();
+        assert_eq!(FileHandler::read(&tsv).unwrap(), "name\tnote\nAda\ta, b\n");
+        assert_eq!(FileHandler::read_csv_table(&tsv).unwrap(), table);
+
+        let path = dir.path().join("export.csv");
+        FileHandler::write(&path, "# exported\n1; x ;3\n4;y\n5;z;6;7\n").unwrap();
+        let options = CsvOptions {
+            delimiter: b';',
+            comment: Some(b'#'),
+            has_headers: false,
+            trim: true,
+            flexible: true,
+            ..Default::default()
+        };
+        let table = FileHandler::read_csv_table_with(&path, &options).unwrap();
+        assert_eq!(
+            table.headers,
+            ["column_1", "column_2", "column_3", "column_4"]
+        );
+        assert_eq!(table.rows[0], ["1", "x", "3", ""]);
+        assert_eq!(table.rows[1], ["4", "y", "", ""]);
+        assert_eq!(table.rows.len(), 3);
+
+        let strict = CsvOptions {
+            flexible: false,
+            ..options
+        };
+        assert!(FileHandler::read_csv_table_with(&path, &strict).is_err());
+    }
+
+    #[test]
+    fn test_atomic_write() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.json");
+        FileHandler::atomic_write(&path, "{}").unwrap();
+        assert_eq!(FileHandler::read(&path).unwrap(), "{}");
+
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    FileHandler::atomic_write(&path, &format!("{{\"writer\": {}}}", i)).unwrap()
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        let value: Value = FileHandler::read_json(&path).unwrap();
+        assert!(value["writer"].is_u64());
+
+        let error = FileHandler::atomic_write_with(&path, |writer| {
+            writer.write_all(b"{\"trunc")?;
+            bail!("serializer failed")
+        })
+        .unwrap_err();
+        assert_eq!(error.to_string(), "serializer failed");
+        assert_eq!(FileHandler::read_json::<Value, _>(&path).unwrap(), value);
+
+        let names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["config.json"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_atomic_write_keeps_mode_and_links() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("secret.yml");
+        FileHandler::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        let link = dir.path().join("link.yml");
+        std::os::unix::fs::symlink(&path, &link).unwrap();
+
+        FileHandler::atomic_write(&link, "new").unwrap();
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(FileHandler::read(&path).unwrap(), "new");
+        let mode = |p: &Path| fs::metadata(p).unwrap().permissions().mode() & 0o7777;
+        assert_eq!(mode(&path), 0o640);
+
+        // New files get the same mode a plain write would.
+        let plain = dir.path().join("plain.txt");
+        let atomic = dir.path().join("atomic.txt");
+        FileHandler::write(&plain, "").unwrap();
+        FileHandler::atomic_write(&atomic, "").unwrap();
+        assert_eq!(mode(&atomic), mode(&plain));
+    }
+
+    #[test]
+    fn test_locks() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("locks/job.lock");
+
+        let held = FileHandler::lock_exclusive(&path).unwrap();
+        assert_eq!(held.path(), path);
+        assert!(FileHandler::try_lock_exclusive(&path).unwrap().is_none());
+        assert!(FileHandler::try_lock_shared(&path).unwrap().is_none());
+        let error =
+            FileHandler::lock_exclusive_timeout(&path, Duration::from_millis(50)).unwrap_err();
+        assert!(
+            matches!(
+                error.downcast_ref::<FileError>(),
+                Some(FileError::LockTimeout(..))
+            ),
+            "{}",
+            error
+        );
+        drop(held);
+
+        let first = FileHandler::lock_shared(&path).unwrap();
+        let second = FileHandler::lock_shared_timeout(&path, Duration::from_secs(1)).unwrap();
+        assert!(FileHandler::try_lock_exclusive(&path).unwrap().is_none());
+        drop((first, second));
+        assert!(FileHandler::try_lock_exclusive(&path).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_update_json_serializes_writers() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("counter.json");
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..10 {
+                        FileHandler::update_json(&path, |counts: &mut HashMap<String, u64>| {
+                            *counts.entry("runs".into()).or_default() += 1;
+                            Ok(())
+                        })
+                        .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let counts: HashMap<String, u64> = FileHandler::read_json(&path).unwrap();
+        assert_eq!(counts["runs"], 80);
+
+        // Written like Ruby's JSON.pretty_generate.
+        let doc = dir.path().join("doc.json");
+        FileHandler::update_json(&doc, |data: &mut Value| {
+            *data = json!({"ratio": 1.0, "tags": [], "meta": {}});
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(&doc).unwrap(),
+            "{\n  \"ratio\": 1.0,\n  \"tags\": [],\n  \"meta\": {}\n}"
+        );
+    }
+
+    #[test]
+    fn test_update_inside_held_lock() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state.json");
+        let bump = |path: &Path| {
+            FileHandler::update_json(path, |n: &mut u64| {
+                *n += 1;
+                Ok(())
+            })
+            .unwrap()
+        };
+
+        // What `--lock state.json` followed by an update of it does.
+        let global = FileHandler::lock_exclusive(&path).unwrap();
+        bump(&path);
+        drop(global);
+        // Locking the sidecar itself nests as well.
+        let global = FileHandler::lock_exclusive(dir.path().join("state.json.lock")).unwrap();
+        bump(&path);
+        drop(global);
+
+        assert_eq!(FileHandler::read_json::<u64, _>(&path).unwrap(), 2);
+        assert!(
+            FileHandler::try_lock_exclusive(dir.path().join("state.json.lock"))
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_read_xlsx_beyond_sheet_limits() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("huge.xlsx");
+        let mut zip = crate::utils::zip_archive::ZipWriter::new();
+        zip.add(
+            "xl/workbook.xml",
+            br#"<workbook><sheets><sheet name="S" r:id="rId1"/></sheets></workbook>"#,
+        )
+        .unwrap();
+        zip.add(
+            "xl/_rels/workbook.xml.rels",
+            br#"<Relationships><Relationship Id="rId1" Target="worksheets/sheet1.xml"/></Relationships>"#,
+        )
+        .unwrap();
+        zip.add(
+            "xl/worksheets/sheet1.xml",
+            br#"<worksheet><sheetData><row r="4000000000"><c><v>1</v></c></row></sheetData></worksheet>"#,
+        )
+        .unwrap();
+        fs::write(&path, zip.finish()).unwrap();
+
+        let error = FileHandler::read_xlsx(&path, &SheetOptions::default()).unwrap_err();
+        assert!(
+            matches!(
+                error.downcast_ref::<FileError>(),
+                Some(FileError::InvalidXlsx(_))
+            ),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_xlsx_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("export.xlsx");
+
+        let mut row = HashMap::new();
+        row.insert("name".to_string(), "Ada".to_string());
+        row.insert("age".to_string(), "36".to_string());
+        let mut sparse = HashMap::new();
+        sparse.insert("name".to_string(), "Grace".to_string());
+
+        FileHandler::write_xlsx(&path, &[row.clone(), sparse]).unwrap();
+        let records = FileHandler::read_xlsx(&path, &SheetOptions::default()).unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0], row);
+        assert_eq!(records[1]["name"], "Grace");
+        assert_eq!(records[1]["age"], "");
+
+        fs::write(&path, "not a workbook").unwrap();
+        let error = FileHandler::read_xlsx(&path, &SheetOptions::default()).unwrap_err();
+        assert!(error.to_string().starts_with("Invalid Excel workbook"));
+    }
+
+    #[test]
+    fn test_search() {
+        let dir = TempDir::new().unwrap();
+        FileHandler::write(
+            dir.path().join("src/lib.rs"),
+            "fn main() {}\n// TODO: fix\n",
+        )
+        .unwrap();
+        FileHandler::write(dir.path().join("notes.md"), "TODO later\n").unwrap();
+        fs::write(dir.path().join("data.bin"), b"TODO\0").unwrap();
+
+        let regex = Regex::new("TODO").unwrap();
+        let options = SearchOptions {
+            include: glob::compile_all(&["*.rs".to_string()]).unwrap(),
+            ..Default::default()
+        };
+        let results = FileHandler::search(&[dir.path()], &regex, &options).unwrap();
+
+        assert_eq!(results.files_searched, 1);
+        assert_eq!(results.files.len(), 1);
+        let hit = &results.files[0].matches[0];
+        assert_eq!((hit.line_number, hit.column), (2, 4));
+        assert_eq!(hit.ranges, vec![(3, 7)]);
+
+        let all = FileHandler::search(&[dir.path()], &regex, &SearchOptions::default()).unwrap();
+        assert_eq!(all.files.len(), 2);
+        assert_eq!(all.binary_skipped, 1);
+        assert!(all.errors.is_empty());
+    }
+
+    #[test]
+    fn test_search_columns_count_characters() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes.md");
+        fs::write(&path, "café TODO\n").unwrap();
+
+        let results =
+            FileHandler::search(&[&path], &Regex::new("TODO").unwrap(), &Default::default())
+                .unwrap();
+        let hit = &results.files[0].matches[0];
+        assert_eq!(hit.column, 6);
+        assert_eq!(hit.ranges, vec![(6, 10)]);
+    }
+
+    #[test]
+    fn test_search_continues_past_unreadable_paths() {
+        let dir = TempDir::new().unwrap();
+        let notes = dir.path().join("notes.md");
+        fs::write(&notes, "TODO\n").unwrap();
+        let missing = dir.path().join("missing.md");
+
+        let results = FileHandler::search(
+            &[missing.as_path(), notes.as_path()],
+            &Regex::new("TODO").unwrap(),
+            &SearchOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(results.files.len(), 1);
+        assert_eq!(results.errors.len(), 1);
+        assert!(results.errors[0].contains("missing.md"));
     }
 }
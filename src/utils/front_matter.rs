@@ -0,0 +1,317 @@
+//! YAML front matter: a `---` delimited block at the top of a Markdown file.
+//!
+//! The block is parsed with the same YAML semantics as
+//! `FileHandler::read_yaml`. The body is kept byte-for-byte, and so is the
+//! source text of every top-level key that wasn't changed; only changed or
+//! new keys are emitted (via the Psych emitter).
+
+use super::psych_yaml;
+use anyhow::{bail, Result};
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrontMatter {
+    pub data: Map<String, Value>,
+    pub body: String,
+    /// Whether the source had a front matter block at all.
+    pub present: bool,
+    newline: &'static str,
+    /// The data as parsed, to tell which keys were changed.
+    original: Map<String, Value>,
+    segments: Vec<Segment>,
+}
+
+/// A run of source lines: one top-level key with its nested lines, or
+/// leading comments (`key: None`).
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    key: Option<String>,
+    text: String,
+}
+
+impl FrontMatter {
+    /// Splits `content` into metadata and body. Content without an opening
+    /// `---` line has empty metadata and is all body.
+    pub fn parse(content: &str) -> Result<Self> {
+        let text = content.strip_prefix('\u{feff}').unwrap_or(content);
+        let newline = if text.starts_with("---\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+
+        let Some(rest) = text
+            .strip_prefix("---\r\n")
+            .or_else(|| text.strip_prefix("---\n"))
+        else {
+            return Ok(Self {
+                data: Map::new(),
+                body: content.to_string(),
+                present: false,
+                newline,
+                original: Map::new(),
+                segments: Vec::new(),
+            });
+        };
+
+        // The block ends at the first `---` (or `...`) line.
+        let mut offset = 0;
+        let mut block_end = None;
+        for line in rest.split_inclusive('\n') {
+            let trimmed = line.trim_end_matches(['\n', '\r']);
+            if trimmed == "---" || trimmed == "..." {
+                block_end = Some((offset, offset + line.len()));
+                break;
+            }
+            offset += line.len();
+        }
+        let Some((yaml_end, body_start)) = block_end else {
+            bail!("Unterminated front matter block (missing closing ---)");
+        };
+
+        let yaml = &rest[..yaml_end];
+        let data = match serde_yaml::from_str::<Value>(yaml) {
+            Ok(Value::Object(map)) => map,
+            Ok(Value::Null) => Map::new(),
+            Ok(_) => bail!("Front matter must be a YAML mapping"),
+            Err(e) => bail!("Invalid front matter: {}", e),
+        };
+
+        Ok(Self {
+            segments: segments(yaml, &data),
+            original: data.clone(),
+            data,
+            body: rest[body_start..].to_string(),
+            present: true,
+            newline,
+        })
+    }
+
+    /// Reassembles the file. The body is written back untouched, and the
+    /// block keeps the source's line endings.
+    pub fn render(&self) -> Result<String> {
+        let mut block = format!("---{}", self.newline);
+
+        for segment in &self.segments {
+            let Some(key) = &segment.key else {
+                block.push_str(&segment.text);
+                continue;
+            };
+            match self.data.get(key) {
+                Some(value) if self.original.get(key) == Some(value) => {
+                    block.push_str(&segment.text)
+                }
+                Some(value) => block.push_str(&self.emit(key, value)?),
+                None => {}
+            }
+        }
+        for (key, value) in &self.data {
+            if !self.segments.iter().any(|s| s.key.as_ref() == Some(key)) {
+                block.push_str(&self.emit(key, value)?);
+            }
+        }
+
+        block.push_str("---");
+        block.push_str(self.newline);
+        Ok(block + &self.body)
+    }
+
+    fn emit(&self, key: &str, value: &Value) -> Result<String> {
+        let mut single = Map::new();
+        single.insert(key.to_string(), value.clone());
+
+        let yaml = psych_yaml::dump(&single)?;
+        let yaml = yaml.strip_prefix("---\n").unwrap_or(&yaml);
+        let yaml = yaml.strip_suffix("...\n").unwrap_or(yaml);
+        Ok(yaml.replace('\n', self.newline))
+    }
+
+    /// Looks up a dotted key path such as `author.name` or `tags.0`.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        let mut parts = key.split('.');
+        let mut value = self.data.get(parts.next()?)?;
+        for part in parts {
+            value = match value {
+                Value::Object(map) => map.get(part)?,
+                Value::Array(items) => items.get(part.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+
+    /// Sets a dotted key path, creating intermediate mappings as needed.
+    pub fn set(&mut self, key: &str, new_value: Value) -> Result<()> {
+        let parts: Vec<&str> = key.split('.').collect();
+        if parts.iter().any(|p| p.is_empty()) {
+            bail!("Invalid key: {:?}", key);
+        }
+
+        let (last, parents) = parts.split_last().unwrap();
+        let mut map = &mut self.data;
+        for part in parents {
+            let entry = map
+                .entry(part.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+            map = match entry {
+                Value::Object(child) => child,
+                _ => bail!("Cannot set {}: {} is not a mapping", key, part),
+            };
+        }
+        map.insert(last.to_string(), new_value);
+        Ok(())
+    }
+
+    /// Removes a dotted key path, returning the old value.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        let (parents, last) = match key.rsplit_once('.') {
+            Some((parents, last)) => (Some(parents), last),
+            None => (None, key),
+        };
+        let map = match parents {
+            Some(parents) => self.get_mut(parents)?.as_object_mut()?,
+            None => &mut self.data,
+        };
+        map.shift_remove(last)
+    }
+
+    fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        let mut parts = key.split('.');
+        let mut value = self.data.get_mut(parts.next()?)?;
+        for part in parts {
+            value = value.as_object_mut()?.get_mut(part)?;
+        }
+        Some(value)
+    }
+}
+
+/// Splits the block into per-key segments. A line at column 0 that isn't a
+/// comment or sequence item starts a new key. If the split doesn't line up
+/// with the parsed keys (flow collections spanning lines, say), there are no
+/// segments and every key is re-emitted.
+fn segments(yaml: &str, data: &Map<String, Value>) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = Vec::new();
+    for line in yaml.split_inclusive('\n') {
+        let starts_key = line
+            .chars()
+            .next()
+            .is_some_and(|c| !c.is_whitespace() && c != '#' && c != '-');
+        match segments.last_mut() {
+            Some(last) if !starts_key => last.text.push_str(line),
+            _ => segments.push(Segment {
+                key: starts_key.then(String::new),
+                text: line.to_string(),
+            }),
+        }
+    }
+
+    for segment in &mut segments {
+        if segment.key.is_none() {
+            continue;
+        }
+        match serde_yaml::from_str::<Map<String, Value>>(&segment.text) {
+            Ok(map) if map.len() == 1 => segment.key = map.keys().next().cloned(),
+            _ => return Vec::new(),
+        }
+    }
+
+    let keys: Vec<&String> = segments.iter().filter_map(|s| s.key.as_ref()).collect();
+    if keys.len() != data.len() || !keys.iter().all(|k| data.contains_key(*k)) {
+        return Vec::new();
+    }
+    segments
+}
+
+/// Parses a command-line value the way YAML would read it, so `true`,
+/// `3` and `[a, b]` become a boolean, number and list. Anything that isn't
+/// valid YAML stays a plain string.
+pub fn parse_value(raw: &str) -> Value {
+    if raw.trim().is_empty() {
+        return Value::String(raw.to_string());
+    }
+    serde_yaml::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const DOC: &str = "---\ntitle: Getting started\ntags:\n- intro\n- setup\nauthor:\n  name: Ada\n---\n# Heading\n\n---\nNot front matter.\n";
+
+    #[test]
+    fn test_parse_and_get() {
+        let doc = FrontMatter::parse(DOC).unwrap();
+
+        assert!(doc.present);
+        assert_eq!(doc.get("title"), Some(&json!("Getting started")));
+        assert_eq!(doc.get("tags.1"), Some(&json!("setup")));
+        assert_eq!(doc.get("author.name"), Some(&json!("Ada")));
+        assert_eq!(doc.get("author.email"), None);
+        assert_eq!(doc.body, "# Heading\n\n---\nNot front matter.\n");
+    }
+
+    #[test]
+    fn test_update_keeps_body() {
+        let mut doc = FrontMatter::parse(DOC).unwrap();
+        doc.set("draft", json!(true)).unwrap();
+        doc.set("author.name", json!("Grace")).unwrap();
+        assert_eq!(doc.remove("tags"), Some(json!(["intro", "setup"])));
+
+        assert_eq!(
+            doc.render().unwrap(),
+            "---\ntitle: Getting started\nauthor:\n  name: Grace\ndraft: true\n---\n# Heading\n\n---\nNot front matter.\n"
+        );
+        assert!(doc.set("title.sub", json!(1)).is_err());
+    }
+
+    #[test]
+    fn test_untouched_keys_keep_their_source() {
+        let source = "---\n# Site metadata\ntitle: \"Intro: start\"\ndate: 2024-01-05\ntags: [a, b]\n---\nBody\n";
+        let mut doc = FrontMatter::parse(source).unwrap();
+        assert_eq!(doc.render().unwrap(), source);
+
+        doc.set("tags", json!(["a", "b", "c"])).unwrap();
+        doc.set("layout", json!("guide")).unwrap();
+        assert_eq!(
+            doc.render().unwrap(),
+            "---\n# Site metadata\ntitle: \"Intro: start\"\ndate: 2024-01-05\ntags:\n- a\n- b\n- c\nlayout: guide\n---\nBody\n"
+        );
+    }
+
+    #[test]
+    fn test_without_front_matter() {
+        let mut doc = FrontMatter::parse("Just text\n").unwrap();
+        assert!(!doc.present);
+        assert!(doc.data.is_empty());
+        assert_eq!(doc.render().unwrap(), "---\n---\nJust text\n");
+
+        doc.set("title", json!("Added")).unwrap();
+        assert_eq!(doc.render().unwrap(), "---\ntitle: Added\n---\nJust text\n");
+    }
+
+    #[test]
+    fn test_crlf_and_errors() {
+        let doc = FrontMatter::parse("---\r\na: 1\r\n---\r\nbody\r\n").unwrap();
+        assert_eq!(doc.get("a"), Some(&json!(1)));
+        assert_eq!(doc.render().unwrap(), "---\r\na: 1\r\n---\r\nbody\r\n");
+
+        assert!(FrontMatter::parse("---\ntitle: x\nno end\n").is_err());
+        assert!(FrontMatter::parse("---\n- a list\n---\n").is_err());
+        assert!(FrontMatter::parse("---\n---\nempty\n")
+            .unwrap()
+            .data
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(parse_value("true"), json!(true));
+        assert_eq!(parse_value("42"), json!(42));
+        assert_eq!(parse_value("[a, b]"), json!(["a", "b"]));
+        assert_eq!(parse_value("hello world"), json!("hello world"));
+        assert_eq!(parse_value("a: b: c"), json!("a: b: c"));
+        assert_eq!(parse_value(""), json!(""));
+        assert_eq!(parse_value("null"), Value::Null);
+    }
+}
@@ -0,0 +1,161 @@
+use anyhow::{bail, Result};
+use regex::Regex;
+
+/// A shell-style glob compiled to a regex.
+///
+/// Supports `*` (within one path segment), `**` (across segments), `?`,
+/// `[abc]`/`[!abc]` classes and `{a,b}` alternation. Patterns without a `/`
+/// match against the file name only, the way `.gitignore` entries do.
+#[derive(Debug, Clone)]
+pub struct Glob {
+    pattern: String,
+    regex: Regex,
+    basename_only: bool,
+}
+
+#[allow(dead_code)]
+impl Glob {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let trimmed = pattern.trim_start_matches("./");
+        let basename_only = !trimmed.trim_end_matches('/').contains('/');
+        let anchored = trimmed.trim_start_matches('/').trim_end_matches('/');
+
+        let regex = Regex::new(&format!("^{}$", translate(anchored, pattern)?))?;
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            regex,
+            basename_only,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Matches a `/`-separated path relative to the search root.
+    pub fn is_match(&self, relative_path: &str) -> bool {
+        if self.basename_only {
+            let name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+            self.regex.is_match(name)
+        } else {
+            self.regex.is_match(relative_path)
+        }
+    }
+}
+
+/// Compiles a list of globs, e.g. from repeated `--include` flags.
+pub fn compile_all(patterns: &[String]) -> Result<Vec<Glob>> {
+    patterns.iter().map(|p| Glob::new(p)).collect()
+}
+
+pub fn any_match(globs: &[Glob], relative_path: &str) -> bool {
+    globs.iter().any(|glob| glob.is_match(relative_path))
+}
+
+fn translate(glob: &str, original: &str) -> Result<String> {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut out = String::new();
+    let mut braces = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                let at_start = i == 0 || chars[i - 1] == '/';
+                i += 2;
+                if at_start && chars.get(i) == Some(&'/') {
+                    // `**/` matches zero or more leading directories.
+                    out.push_str("(?:.*/)?");
+                    i += 1;
+                } else {
+                    out.push_str(".*");
+                }
+                continue;
+            }
+            '*' => out.push_str("[^/]*"),
+            '?' => out.push_str("[^/]"),
+            '[' => {
+                let close = chars[i + 1..]
+                    .iter()
+                    .skip(1)
+                    .position(|&c| c == ']')
+                    .map(|p| i + 2 + p);
+                let Some(close) = close else {
+                    bail!("Invalid glob {:?}: unclosed character class", original);
+                };
+                out.push('[');
+                let mut body = &chars[i + 1..close];
+                if let Some(('!' | '^', rest)) = body.split_first() {
+                    out.push('^');
+                    body = rest;
+                }
+                for &c in body {
+                    if matches!(c, '\\' | '[' | ']' | '^' | '&' | '~') {
+                        out.push('\\');
+                    }
+                    out.push(c);
+                }
+                out.push(']');
+                i = close;
+            }
+            '{' => {
+                braces += 1;
+                out.push_str("(?:");
+            }
+            '}' if braces > 0 => {
+                braces -= 1;
+                out.push(')');
+            }
+            ',' if braces > 0 => out.push('|'),
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                out.push_str(&regex::escape(&chars[i].to_string()));
+            }
+            c => out.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+
+    if braces > 0 {
+        bail!("Invalid glob {:?}: unclosed '{{'", original);
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, path: &str) -> bool {
+        Glob::new(pattern).unwrap().is_match(path)
+    }
+
+    #[test]
+    fn test_basename_patterns() {
+        assert!(matches("*.rs", "main.rs"));
+        assert!(matches("*.rs", "src/utils/glob.rs"));
+        assert!(!matches("*.rs", "src/utils/glob.rb"));
+        assert!(matches("file?.txt", "file1.txt"));
+        assert!(matches("*.{json,yml}", "a/b.yml"));
+        assert!(matches("[!a]*", "bravo"));
+        assert!(!matches("[!a]*", "alpha"));
+    }
+
+    #[test]
+    fn test_path_patterns() {
+        assert!(matches("src/*.rs", "src/cli.rs"));
+        assert!(!matches("src/*.rs", "src/utils/glob.rs"));
+        assert!(matches("src/**/*.rs", "src/utils/glob.rs"));
+        assert!(matches("src/**/*.rs", "src/cli.rs"));
+        assert!(matches("**/fixtures/*", "spec/fixtures/a.json"));
+        assert!(matches("/target/", "target"));
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        assert!(Glob::new("[abc").is_err());
+        assert!(Glob::new("{a,b").is_err());
+    }
+}
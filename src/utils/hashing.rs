@@ -0,0 +1,264 @@
+use anyhow::{bail, Result};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use sha3::Sha3_256;
+use std::fmt;
+use std::io::Read;
+use std::str::FromStr;
+use xxhash_rust::xxh3::Xxh3;
+use xxhash_rust::xxh64::Xxh64;
+
+/// Read size used when hashing streams; memory use stays flat regardless of
+/// file size.
+pub const BUFFER_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    Sha3_256,
+    Blake3,
+    /// XXH64 with seed 0, as printed by `xxhsum`.
+    Xxh64,
+    /// XXH3 64-bit, as printed by `xxhsum -H3`.
+    Xxh3,
+    /// CRC-32 (IEEE), as used by zip and gzip.
+    Crc32,
+}
+
+impl HashAlgorithm {
+    pub const ALL: [HashAlgorithm; 9] = [
+        HashAlgorithm::Md5,
+        HashAlgorithm::Sha1,
+        HashAlgorithm::Sha256,
+        HashAlgorithm::Sha512,
+        HashAlgorithm::Sha3_256,
+        HashAlgorithm::Blake3,
+        HashAlgorithm::Xxh64,
+        HashAlgorithm::Xxh3,
+        HashAlgorithm::Crc32,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha512 => "sha512",
+            HashAlgorithm::Sha3_256 => "sha3-256",
+            HashAlgorithm::Blake3 => "blake3",
+            HashAlgorithm::Xxh64 => "xxh64",
+            HashAlgorithm::Xxh3 => "xxh3",
+            HashAlgorithm::Crc32 => "crc32",
+        }
+    }
+
+    /// Length of the digest in hex characters.
+    pub fn hex_len(self) -> usize {
+        match self {
+            HashAlgorithm::Md5 => 32,
+            HashAlgorithm::Sha1 => 40,
+            HashAlgorithm::Sha256 | HashAlgorithm::Sha3_256 | HashAlgorithm::Blake3 => 64,
+            HashAlgorithm::Sha512 => 128,
+            HashAlgorithm::Xxh64 | HashAlgorithm::Xxh3 => 16,
+            HashAlgorithm::Crc32 => 8,
+        }
+    }
+
+    /// Whether the digest resists deliberate collisions. Only these are
+    /// suitable for verifying downloads.
+    #[allow(dead_code)]
+    pub fn is_cryptographic(self) -> bool {
+        matches!(
+            self,
+            HashAlgorithm::Sha256
+                | HashAlgorithm::Sha512
+                | HashAlgorithm::Sha3_256
+                | HashAlgorithm::Blake3
+        )
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let normalized = s.to_ascii_lowercase().replace(['-', '_'], "");
+        let algorithm = match normalized.as_str() {
+            "md5" => HashAlgorithm::Md5,
+            "sha1" => HashAlgorithm::Sha1,
+            "sha256" => HashAlgorithm::Sha256,
+            "sha512" => HashAlgorithm::Sha512,
+            "sha3256" | "sha3" => HashAlgorithm::Sha3_256,
+            "blake3" | "b3" => HashAlgorithm::Blake3,
+            "xxh64" | "xxhash" | "xxhash64" => HashAlgorithm::Xxh64,
+            "xxh3" | "xxh364" => HashAlgorithm::Xxh3,
+            "crc32" => HashAlgorithm::Crc32,
+            _ => bail!(
+                "Unknown hash algorithm: {} (expected one of {})",
+                s,
+                HashAlgorithm::ALL.map(HashAlgorithm::name).join(", ")
+            ),
+        };
+        Ok(algorithm)
+    }
+}
+
+enum State {
+    Md5(md5::Context),
+    Sha1(Sha1),
+    Sha256(Sha256),
+    Sha512(Sha512),
+    Sha3_256(Sha3_256),
+    Blake3(Box<blake3::Hasher>),
+    Xxh64(Xxh64),
+    Xxh3(Box<Xxh3>),
+    Crc32(crc32fast::Hasher),
+}
+
+/// Incremental hasher for any `HashAlgorithm`.
+pub struct Hasher {
+    algorithm: HashAlgorithm,
+    state: State,
+}
+
+#[allow(dead_code)]
+impl Hasher {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        let state = match algorithm {
+            HashAlgorithm::Md5 => State::Md5(md5::Context::new()),
+            HashAlgorithm::Sha1 => State::Sha1(Sha1::new()),
+            HashAlgorithm::Sha256 => State::Sha256(Sha256::new()),
+            HashAlgorithm::Sha512 => State::Sha512(Sha512::new()),
+            HashAlgorithm::Sha3_256 => State::Sha3_256(Sha3_256::new()),
+            HashAlgorithm::Blake3 => State::Blake3(Box::new(blake3::Hasher::new())),
+            HashAlgorithm::Xxh64 => State::Xxh64(Xxh64::new(0)),
+            HashAlgorithm::Xxh3 => State::Xxh3(Box::new(Xxh3::new())),
+            HashAlgorithm::Crc32 => State::Crc32(crc32fast::Hasher::new()),
+        };
+        Self { algorithm, state }
+    }
+
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        match &mut self.state {
+            State::Md5(context) => context.consume(data),
+            State::Sha1(hasher) => hasher.update(data),
+            State::Sha256(hasher) => hasher.update(data),
+            State::Sha512(hasher) => hasher.update(data),
+            State::Sha3_256(hasher) => hasher.update(data),
+            State::Blake3(hasher) => {
+                hasher.update(data);
+            }
+            State::Xxh64(hasher) => hasher.update(data),
+            State::Xxh3(hasher) => hasher.update(data),
+            State::Crc32(hasher) => hasher.update(data),
+        }
+    }
+
+    /// Returns the digest as lowercase hex.
+    pub fn finalize(self) -> String {
+        match self.state {
+            State::Md5(context) => format!("{:x}", context.finalize()),
+            State::Sha1(hasher) => format!("{:x}", hasher.finalize()),
+            State::Sha256(hasher) => format!("{:x}", hasher.finalize()),
+            State::Sha512(hasher) => format!("{:x}", hasher.finalize()),
+            State::Sha3_256(hasher) => format!("{:x}", hasher.finalize()),
+            State::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+            State::Xxh64(hasher) => format!("{:016x}", hasher.digest()),
+            State::Xxh3(hasher) => format!("{:016x}", hasher.digest()),
+            State::Crc32(hasher) => format!("{:08x}", hasher.finalize()),
+        }
+    }
+}
+
+/// Hashes `reader` once, feeding every algorithm from the same buffer.
+/// Digests are returned in the order the algorithms were given.
+pub fn digest_reader<R: Read>(mut reader: R, algorithms: &[HashAlgorithm]) -> Result<Vec<String>> {
+    let mut hashers: Vec<Hasher> = algorithms.iter().map(|&a| Hasher::new(a)).collect();
+    let mut buffer = vec![0u8; BUFFER_SIZE];
+
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        for hasher in &mut hashers {
+            hasher.update(&buffer[..read]);
+        }
+    }
+
+    Ok(hashers.into_iter().map(Hasher::finalize).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_digests() {
+        let digests = digest_reader(&b"hello world"[..], &HashAlgorithm::ALL).unwrap();
+        let expected = [
+            "5eb63bbbe01eeed093cb22bb8f5acdc3",
+            "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed",
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9",
+            "309ecc489c12d6eb4cc40f50c902f2b4d0ed77ee511a7c7a9bcd3ca86d4cd86f989dd35bc5ff499670da34255b45b0cfd830e81f605dcf7dc5542e93ae9cd76f",
+            "644bcc7e564373040999aac89e7622f3ca71fba1d972fd94a31c3bfbf24e3938",
+            "d74981efa70a0c880b8d8c1985d075dbcbf679b99a5f9914e5aaf96b831a9e24",
+            "45ab6734b21e6968",
+            "d447b1ea40e6988b",
+            "0d4a1185",
+        ];
+        for ((algorithm, digest), expected) in HashAlgorithm::ALL.iter().zip(&digests).zip(expected)
+        {
+            assert_eq!(digest, expected, "{}", algorithm);
+            assert_eq!(digest.len(), algorithm.hex_len(), "{}", algorithm);
+        }
+    }
+
+    #[test]
+    fn test_streaming_matches_one_shot() {
+        let data: Vec<u8> = (0..BUFFER_SIZE * 3 + 17).map(|i| (i % 251) as u8).collect();
+        let streamed =
+            digest_reader(&data[..], &[HashAlgorithm::Sha256, HashAlgorithm::Crc32]).unwrap();
+
+        assert_eq!(streamed[0], format!("{:x}", Sha256::digest(&data)));
+        assert_eq!(streamed[1], format!("{:08x}", crc32fast::hash(&data)));
+    }
+
+    #[test]
+    fn test_parse_names() {
+        assert_eq!(
+            "SHA3-256".parse::<HashAlgorithm>().unwrap(),
+            HashAlgorithm::Sha3_256
+        );
+        assert_eq!(
+            "xxhash".parse::<HashAlgorithm>().unwrap(),
+            HashAlgorithm::Xxh64
+        );
+        assert_eq!(
+            "sha_256".parse::<HashAlgorithm>().unwrap(),
+            HashAlgorithm::Sha256
+        );
+        for algorithm in HashAlgorithm::ALL {
+            assert_eq!(
+                algorithm.name().parse::<HashAlgorithm>().unwrap(),
+                algorithm
+            );
+        }
+        assert!("rot13".parse::<HashAlgorithm>().is_err());
+    }
+}
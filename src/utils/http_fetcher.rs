@@ -0,0 +1,333 @@
+use super::file_handler::{FileError, FileHandler};
+use super::hashing::HashAlgorithm;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+pub const DEFAULT_MAX_SIZE: u64 = 100 * 1024 * 1024;
+
+/// Downloads `http://` and `https://` inputs into a local cache so commands
+/// can treat them like any other file. Cached documents are revalidated with
+/// `If-None-Match`/`If-Modified-Since` on every fetch.
+pub struct HttpFetcher {
+    cache_dir: PathBuf,
+    timeout: Duration,
+    max_size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    fetched_at: String,
+}
+
+#[allow(dead_code)]
+impl HttpFetcher {
+    pub fn new() -> Self {
+        let cache_dir = dirs::cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("basiccli")
+            .join("http");
+
+        Self::new_with_options(cache_dir, DEFAULT_TIMEOUT, DEFAULT_MAX_SIZE)
+    }
+
+    pub fn new_with_options(cache_dir: PathBuf, timeout: Duration, max_size: u64) -> Self {
+        Self {
+            cache_dir,
+            timeout,
+            max_size,
+        }
+    }
+
+    pub fn is_remote(input: &str) -> bool {
+        let lower = input.to_lowercase();
+        lower.starts_with("http://") || lower.starts_with("https://")
+    }
+
+    /// Fetches `url` and returns the path of the cached copy.
+    pub fn fetch(&self, url: &str) -> Result<PathBuf> {
+        let key = cache_key(url);
+        // Keep the remote file name so extension-based format detection
+        // still works on the cached copy.
+        let body_path = self
+            .cache_dir
+            .join(format!("{}-{}", key, remote_file_name(url)));
+        let meta_path = self.cache_dir.join(format!("{}.json", key));
+
+        let cached: Option<CacheEntry> = if body_path.exists() {
+            FileHandler::read_json(&meta_path).ok()
+        } else {
+            None
+        };
+
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(self.timeout)
+            .timeout(self.timeout)
+            .build();
+
+        let mut request = agent.get(url);
+        if let Some(entry) = &cached {
+            if let Some(etag) = &entry.etag {
+                request = request.set("If-None-Match", etag);
+            }
+            if let Some(last_modified) = &entry.last_modified {
+                request = request.set("If-Modified-Since", last_modified);
+            }
+        }
+
+        let response = match request.call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(code, response)) => {
+                bail!(FileError::ReadError(
+                    url.to_string(),
+                    format!("HTTP {} {}", code, response.status_text())
+                ))
+            }
+            Err(e) => bail!(FileError::ReadError(url.to_string(), e.to_string())),
+        };
+
+        if response.status() == 304 && cached.is_some() {
+            return Ok(body_path);
+        }
+
+        if let Some(length) = response
+            .header("Content-Length")
+            .and_then(|l| l.parse::<u64>().ok())
+        {
+            if length > self.max_size {
+                bail!(self.too_large(url));
+            }
+        }
+
+        let entry = CacheEntry {
+            url: url.to_string(),
+            etag: response.header("ETag").map(String::from),
+            last_modified: response.header("Last-Modified").map(String::from),
+            fetched_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        let mut body = Vec::new();
+        response
+            .into_reader()
+            .take(self.max_size + 1)
+            .read_to_end(&mut body)
+            .map_err(|e| FileError::ReadError(url.to_string(), e.to_string()))?;
+        if body.len() as u64 > self.max_size {
+            bail!(self.too_large(url));
+        }
+
+        fs::create_dir_all(&self.cache_dir)
+            .with_context(|| format!("Failed to create cache dir: {:?}", self.cache_dir))?;
+        // The body goes first: metadata pointing at a stale body would
+        // revalidate to 304 and keep serving it.
+        FileHandler::atomic_write_with(&body_path, |out| Ok(out.write_all(&body)?))?;
+        FileHandler::atomic_write_with(&meta_path, |out| {
+            Ok(serde_json::to_writer_pretty(out, &entry)?)
+        })?;
+
+        Ok(body_path)
+    }
+
+    /// Resolves a command input to a local path, downloading it first when
+    /// it is a URL.
+    pub fn resolve(&self, input: &str) -> Result<PathBuf> {
+        if Self::is_remote(input) {
+            self.fetch(input)
+        } else {
+            Ok(PathBuf::from(input))
+        }
+    }
+
+    pub fn verify_sha256<P: AsRef<Path>>(path: P, expected: &str) -> Result<()> {
+        let path = path.as_ref();
+        let actual = FileHandler::checksum(path, HashAlgorithm::Sha256)?;
+
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            bail!(FileError::ChecksumMismatch(
+                path.display().to_string(),
+                expected.trim().to_lowercase(),
+                actual
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn too_large(&self, url: &str) -> FileError {
+        FileError::ReadError(
+            url.to_string(),
+            format!("response exceeds {} bytes", self.max_size),
+        )
+    }
+}
+
+impl Default for HttpFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn cache_key(url: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(url.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn remote_file_name(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let path = path.split_once("://").map(|(_, rest)| rest).unwrap_or(path);
+    let name = path
+        .split_once('/')
+        .map(|(_, rest)| rest.rsplit('/').next().unwrap_or(""))
+        .unwrap_or("");
+
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if sanitized.trim_matches('.').is_empty() {
+        "index".to_string()
+    } else {
+        sanitized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+
+    /// Minimal stand-in HTTP server that answers every request with a fixed
+    /// body and ETag, replying 304 when the client sends a matching
+    /// `If-None-Match`. Returns the base URL and a log of request headers.
+    fn serve(body: &'static str, requests: usize) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let log_handle = Arc::clone(&log);
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut headers = String::new();
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                    headers.push_str(&line);
+                }
+
+                let response = if headers.contains("If-None-Match: \"v1\"") {
+                    "HTTP/1.1 304 Not Modified\r\nContent-Length: 0\r\n\r\n".to_string()
+                } else if headers.starts_with("GET /missing") {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_string()
+                } else {
+                    format!(
+                        "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                };
+                log_handle.lock().unwrap().push(headers);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (url, log)
+    }
+
+    fn fetcher(dir: &TempDir, max_size: u64) -> HttpFetcher {
+        HttpFetcher::new_with_options(dir.path().to_path_buf(), Duration::from_secs(5), max_size)
+    }
+
+    #[test]
+    fn test_is_remote() {
+        assert!(HttpFetcher::is_remote("https://example.com/a.json"));
+        assert!(HttpFetcher::is_remote("HTTP://example.com"));
+        assert!(!HttpFetcher::is_remote("data/a.json"));
+    }
+
+    #[test]
+    fn test_remote_file_name() {
+        assert_eq!(
+            remote_file_name("https://x.io/a/b/data.csv?v=1"),
+            "data.csv"
+        );
+        assert_eq!(remote_file_name("https://x.io/"), "index");
+        assert_eq!(remote_file_name("https://x.io"), "index");
+        assert_eq!(
+            remote_file_name("http://x.io/my%20file.json"),
+            "my_20file.json"
+        );
+    }
+
+    #[test]
+    fn test_fetch_and_revalidate() {
+        let (url, log) = serve(r#"{"ok": true}"#, 2);
+        let dir = TempDir::new().unwrap();
+        let fetcher = fetcher(&dir, DEFAULT_MAX_SIZE);
+
+        let path = fetcher.fetch(&format!("{}/data.json", url)).unwrap();
+        assert!(path.to_string_lossy().ends_with("-data.json"));
+        assert_eq!(FileHandler::read(&path).unwrap(), r#"{"ok": true}"#);
+
+        let again = fetcher.fetch(&format!("{}/data.json", url)).unwrap();
+        assert_eq!(path, again);
+        assert_eq!(FileHandler::read(&again).unwrap(), r#"{"ok": true}"#);
+
+        let log = log.lock().unwrap();
+        assert!(!log[0].contains("If-None-Match"));
+        assert!(log[1].contains("If-None-Match: \"v1\""));
+    }
+
+    #[test]
+    fn test_fetch_enforces_size_limit() {
+        let (url, _) = serve("0123456789", 1);
+        let dir = TempDir::new().unwrap();
+
+        assert!(fetcher(&dir, 5).fetch(&url).is_err());
+    }
+
+    #[test]
+    fn test_fetch_reports_http_errors() {
+        let (url, _) = serve("", 1);
+        let dir = TempDir::new().unwrap();
+
+        let err = fetcher(&dir, DEFAULT_MAX_SIZE)
+            .fetch(&format!("{}/missing", url))
+            .unwrap_err();
+        assert!(err.to_string().contains("404"));
+    }
+
+    #[test]
+    fn test_verify_sha256() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("a.txt");
+        FileHandler::write(&path, "Hello World").unwrap();
+
+        let digest = "a591a6d40bf420404a011733cfb7b190d62c65bf0bcda32b57b277d9ad9f146e";
+        assert!(HttpFetcher::verify_sha256(&path, digest).is_ok());
+        assert!(HttpFetcher::verify_sha256(&path, &digest.to_uppercase()).is_ok());
+        assert!(HttpFetcher::verify_sha256(&path, "deadbeef").is_err());
+    }
+}
@@ -0,0 +1,241 @@
+//! Inlines JSON Reference (`$ref`) pointers, for bundling OpenAPI and JSON
+//! Schema documents that are split across files.
+//!
+//! Supported forms are local pointers (`#/components/schemas/Pet`), relative
+//! files (`pet.json`) and both together (`common.yaml#/Error`). Relative
+//! paths resolve against the directory of the file containing the `$ref`.
+//! References with a URL scheme are left as they are.
+
+use super::file_handler::{FileError, FileHandler};
+use anyhow::{bail, Result};
+use percent_encoding::percent_decode_str;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A `$ref` target: a canonical file path plus a JSON Pointer into it.
+type Target = (PathBuf, String);
+
+pub struct RefResolver {
+    documents: HashMap<PathBuf, Value>,
+    resolved: HashMap<Target, Value>,
+    /// Targets being resolved, outermost first, with the `$ref` text that
+    /// led to each, for reporting cycles.
+    chain: Vec<(Target, String)>,
+    count: usize,
+}
+
+impl RefResolver {
+    pub fn new() -> Self {
+        Self {
+            documents: HashMap::new(),
+            resolved: HashMap::new(),
+            chain: Vec::new(),
+            count: 0,
+        }
+    }
+
+    /// Returns `value` (read from `path`) with every reference inlined.
+    pub fn resolve(&mut self, value: &Value, path: &Path) -> Result<Value> {
+        let path = canonical(path)?;
+        self.documents
+            .entry(path.clone())
+            .or_insert_with(|| value.clone());
+        self.resolve_node(value, &path)
+    }
+
+    /// Number of `$ref`s replaced so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    fn resolve_node(&mut self, value: &Value, path: &Path) -> Result<Value> {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(reference)) = map.get("$ref") {
+                    if !is_remote(reference) {
+                        let mut resolved = self.resolve_ref(reference, path)?;
+                        // Siblings of `$ref` (a `description`, say) are kept
+                        // on top of an object target; otherwise dropped.
+                        if let Value::Object(target) = &mut resolved {
+                            for (key, sibling) in map.iter().filter(|(k, _)| *k != "$ref") {
+                                target.insert(key.clone(), self.resolve_node(sibling, path)?);
+                            }
+                        }
+                        return Ok(resolved);
+                    }
+                }
+
+                let mut out = serde_json::Map::new();
+                for (key, child) in map {
+                    out.insert(key.clone(), self.resolve_node(child, path)?);
+                }
+                Ok(Value::Object(out))
+            }
+            Value::Array(items) => items
+                .iter()
+                .map(|item| self.resolve_node(item, path))
+                .collect::<Result<Vec<_>>>()
+                .map(Value::Array),
+            other => Ok(other.clone()),
+        }
+    }
+
+    fn resolve_ref(&mut self, reference: &str, from: &Path) -> Result<Value> {
+        let (file, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        let path = if file.is_empty() {
+            from.to_path_buf()
+        } else {
+            let relative = percent_decode_str(file).decode_utf8_lossy().into_owned();
+            let joined = from.parent().unwrap_or(Path::new("")).join(relative);
+            canonical(&joined).map_err(|_| {
+                FileError::NotFound(format!(
+                    "{} (referenced as {:?} from {})",
+                    joined.display(),
+                    reference,
+                    from.display()
+                ))
+            })?
+        };
+        let pointer = percent_decode_str(fragment)
+            .decode_utf8_lossy()
+            .into_owned();
+        let target = (path, pointer);
+
+        if let Some(start) = self.chain.iter().position(|(t, _)| *t == target) {
+            let mut steps: Vec<&str> = self.chain[start..]
+                .iter()
+                .map(|(_, r)| r.as_str())
+                .collect();
+            steps.push(reference);
+            bail!("Circular $ref: {}", steps.join(" -> "));
+        }
+        self.count += 1;
+        if let Some(value) = self.resolved.get(&target) {
+            return Ok(value.clone());
+        }
+
+        let document = self.load(&target.0)?;
+        let Some(raw) = document.pointer(&target.1).cloned() else {
+            bail!(
+                "Unresolved $ref {:?} in {}: no such pointer in {}",
+                reference,
+                from.display(),
+                target.0.display()
+            );
+        };
+
+        self.chain.push((target.clone(), reference.to_string()));
+        let result = self.resolve_node(&raw, &target.0);
+        self.chain.pop();
+
+        let value = result?;
+        self.resolved.insert(target, value.clone());
+        Ok(value)
+    }
+
+    fn load(&mut self, path: &Path) -> Result<&Value> {
+        if !self.documents.contains_key(path) {
+            let is_yaml = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| e.eq_ignore_ascii_case("yaml") || e.eq_ignore_ascii_case("yml"));
+            let value: Value = if is_yaml {
+                FileHandler::read_yaml(path)?
+            } else {
+                FileHandler::read_json(path)?
+            };
+            self.documents.insert(path.to_path_buf(), value);
+        }
+        Ok(&self.documents[path])
+    }
+}
+
+fn canonical(path: &Path) -> Result<PathBuf> {
+    fs::canonicalize(path).map_err(|_| FileError::NotFound(path.display().to_string()).into())
+}
+
+fn is_remote(reference: &str) -> bool {
+    reference.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.len() > 1
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn resolve(dir: &TempDir, name: &str) -> Result<Value> {
+        let path = dir.path().join(name);
+        let value: Value = FileHandler::read_json(&path)?;
+        RefResolver::new().resolve(&value, &path)
+    }
+
+    #[test]
+    fn test_local_and_file_refs() {
+        let dir = TempDir::new().unwrap();
+        FileHandler::write(
+            dir.path().join("api.json"),
+            r##"{
+              "paths": {"/pets": {"$ref": "paths/pets.json"}},
+              "components": {"schemas": {"Id": {"type": "integer"}, "a/b": {"const": 1}}},
+              "slashed": {"$ref": "#/components/schemas/a~1b"}
+            }"##,
+        )
+        .unwrap();
+        FileHandler::write(
+            dir.path().join("paths/pets.json"),
+            r##"{"get": {"schema": {"$ref": "../schemas/pet.yaml#/Pet", "description": "A pet"}},
+                 "link": {"$ref": "https://example.com/schema.json"}}"##,
+        )
+        .unwrap();
+        FileHandler::write(
+            dir.path().join("schemas/pet.yaml"),
+            "Pet:\n  type: object\n  properties:\n    id:\n      $ref: '#/Id'\nId:\n  type: string\n",
+        )
+        .unwrap();
+
+        let resolved = resolve(&dir, "api.json").unwrap();
+        assert_eq!(
+            resolved["paths"]["/pets"]["get"]["schema"],
+            json!({
+                "type": "object",
+                "properties": {"id": {"type": "string"}},
+                "description": "A pet"
+            })
+        );
+        assert_eq!(
+            resolved["paths"]["/pets"]["link"],
+            json!({"$ref": "https://example.com/schema.json"})
+        );
+        assert_eq!(resolved["slashed"], json!({"const": 1}));
+    }
+
+    #[test]
+    fn test_reports_cycles_with_chain() {
+        let dir = TempDir::new().unwrap();
+        FileHandler::write(dir.path().join("a.json"), r#"{"x": {"$ref": "b.json#/y"}}"#).unwrap();
+        FileHandler::write(dir.path().join("b.json"), r#"{"y": {"$ref": "a.json#/x"}}"#).unwrap();
+
+        let error = resolve(&dir, "a.json").unwrap_err().to_string();
+        assert_eq!(error, "Circular $ref: b.json#/y -> a.json#/x -> b.json#/y");
+    }
+
+    #[test]
+    fn test_missing_targets() {
+        let dir = TempDir::new().unwrap();
+        FileHandler::write(dir.path().join("a.json"), r##"{"x": {"$ref": "#/nope"}}"##).unwrap();
+        FileHandler::write(dir.path().join("b.json"), r#"{"x": {"$ref": "gone.json"}}"#).unwrap();
+
+        let error = resolve(&dir, "a.json").unwrap_err().to_string();
+        assert!(error.starts_with("Unresolved $ref \"#/nope\""), "{}", error);
+        let error = resolve(&dir, "b.json").unwrap_err().to_string();
+        assert!(error.contains("referenced as \"gone.json\""), "{}", error);
+    }
+}
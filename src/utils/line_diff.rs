@@ -0,0 +1,512 @@
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Myers,
+    Patience,
+}
+
+impl FromStr for Algorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "myers" => Ok(Algorithm::Myers),
+            "patience" => Ok(Algorithm::Patience),
+            other => bail!(
+                "Unknown diff algorithm: {} (expected myers or patience)",
+                other
+            ),
+        }
+    }
+}
+
+/// One step of an edit script. Indices are 0-based positions in the old and
+/// new sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// A group of edits with surrounding context, as shown in unified diffs.
+/// Starts are 1-based line numbers; for an empty range they point at the
+/// line before the change, as GNU diff does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+    pub edits: Vec<Edit>,
+}
+
+impl Hunk {
+    /// The `@@ -1,3 +1,4 @@` header line.
+    pub fn header(&self) -> String {
+        format!(
+            "@@ -{} +{} @@",
+            format_range(self.old_start, self.old_len),
+            format_range(self.new_start, self.new_len)
+        )
+    }
+}
+
+fn format_range(start: usize, len: usize) -> String {
+    if len == 1 {
+        start.to_string()
+    } else {
+        format!("{},{}", start, len)
+    }
+}
+
+/// Computes the edit script that turns `old` into `new`.
+pub fn diff<T: AsRef<str>>(old: &[T], new: &[T], algorithm: Algorithm) -> Vec<Edit> {
+    // Intern lines so the algorithms compare integers rather than strings.
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+    let mut a = Vec::with_capacity(old.len());
+    let mut b = Vec::with_capacity(new.len());
+    for (lines, out) in [(old, &mut a), (new, &mut b)] {
+        for line in lines {
+            let next = ids.len();
+            out.push(*ids.entry(line.as_ref()).or_insert(next));
+        }
+    }
+
+    let mut edits = Vec::with_capacity(a.len().max(b.len()));
+    let differ = Differ {
+        a: &a,
+        b: &b,
+        algorithm,
+    };
+    differ.diff(0, a.len(), 0, b.len(), &mut edits);
+    edits
+}
+
+/// Groups an edit script into hunks with `context` unchanged lines around
+/// each change. Changes closer than `2 * context` lines share a hunk.
+pub fn hunks(edits: &[Edit], context: usize) -> Vec<Hunk> {
+    let changes: Vec<usize> = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| !matches!(e, Edit::Equal(..)))
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &i in &changes {
+        let start = i.saturating_sub(context);
+        let end = (i + context + 1).min(edits.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    // Line positions before each edit, to number hunks that start with an
+    // insertion or deletion.
+    let mut positions = Vec::with_capacity(edits.len());
+    let (mut old_pos, mut new_pos) = (0, 0);
+    for edit in edits {
+        positions.push((old_pos, new_pos));
+        match edit {
+            Edit::Equal(..) => {
+                old_pos += 1;
+                new_pos += 1;
+            }
+            Edit::Delete(_) => old_pos += 1,
+            Edit::Insert(_) => new_pos += 1,
+        }
+    }
+
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            let slice = &edits[start..end];
+            let old_len = slice
+                .iter()
+                .filter(|e| !matches!(e, Edit::Insert(_)))
+                .count();
+            let new_len = slice
+                .iter()
+                .filter(|e| !matches!(e, Edit::Delete(_)))
+                .count();
+            let (old_pos, new_pos) = positions[start];
+
+            Hunk {
+                old_start: if old_len > 0 { old_pos + 1 } else { old_pos },
+                old_len,
+                new_start: if new_len > 0 { new_pos + 1 } else { new_pos },
+                new_len,
+                edits: slice.to_vec(),
+            }
+        })
+        .collect()
+}
+
+struct Differ<'a> {
+    a: &'a [usize],
+    b: &'a [usize],
+    algorithm: Algorithm,
+}
+
+impl Differ<'_> {
+    fn diff(&self, a_lo: usize, a_hi: usize, b_lo: usize, b_hi: usize, out: &mut Vec<Edit>) {
+        let (a, b) = (self.a, self.b);
+
+        let mut prefix = 0;
+        while a_lo + prefix < a_hi && b_lo + prefix < b_hi && a[a_lo + prefix] == b[b_lo + prefix] {
+            prefix += 1;
+        }
+        let mut suffix = 0;
+        while a_hi - suffix > a_lo + prefix
+            && b_hi - suffix > b_lo + prefix
+            && a[a_hi - suffix - 1] == b[b_hi - suffix - 1]
+        {
+            suffix += 1;
+        }
+
+        for i in 0..prefix {
+            out.push(Edit::Equal(a_lo + i, b_lo + i));
+        }
+
+        let (x_lo, x_hi) = (a_lo + prefix, a_hi - suffix);
+        let (y_lo, y_hi) = (b_lo + prefix, b_hi - suffix);
+
+        if x_lo == x_hi {
+            out.extend((y_lo..y_hi).map(Edit::Insert));
+        } else if y_lo == y_hi {
+            out.extend((x_lo..x_hi).map(Edit::Delete));
+        } else {
+            match self.algorithm {
+                Algorithm::Myers => self.myers(x_lo, x_hi, y_lo, y_hi, out),
+                Algorithm::Patience => self.patience(x_lo, x_hi, y_lo, y_hi, out),
+            }
+        }
+
+        for i in 0..suffix {
+            out.push(Edit::Equal(a_hi - suffix + i, b_hi - suffix + i));
+        }
+    }
+
+    /// Myers' O(ND) algorithm in linear space: find the middle snake, then
+    /// recurse on both halves.
+    fn myers(&self, a_lo: usize, a_hi: usize, b_lo: usize, b_hi: usize, out: &mut Vec<Edit>) {
+        match self.middle_snake(a_lo, a_hi, b_lo, b_hi) {
+            Some((x, y)) => {
+                self.diff(a_lo, x, b_lo, y, out);
+                self.diff(x, a_hi, y, b_hi, out);
+            }
+            None => {
+                out.extend((a_lo..a_hi).map(Edit::Delete));
+                out.extend((b_lo..b_hi).map(Edit::Insert));
+            }
+        }
+    }
+
+    /// Returns a point on the optimal path that splits the problem in two.
+    fn middle_snake(
+        &self,
+        a_lo: usize,
+        a_hi: usize,
+        b_lo: usize,
+        b_hi: usize,
+    ) -> Option<(usize, usize)> {
+        let a = &self.a[a_lo..a_hi];
+        let b = &self.b[b_lo..b_hi];
+        let (n, m) = (a.len() as isize, b.len() as isize);
+        let max_d = (n + m + 1) / 2;
+        let offset = max_d;
+        let size = (2 * max_d + 2) as usize;
+        let mut forward = vec![-1isize; size];
+        let mut backward = vec![-1isize; size];
+        forward[(offset + 1) as usize] = 0;
+        backward[(offset + 1) as usize] = 0;
+
+        let delta = n - m;
+        let odd = delta % 2 != 0;
+        let (mut k1_start, mut k1_end, mut k2_start, mut k2_end) = (0, 0, 0, 0);
+
+        for d in 0..max_d {
+            let mut k1 = -d + k1_start;
+            while k1 <= d - k1_end {
+                let i = (offset + k1) as usize;
+                let mut x1 = if k1 == -d || (k1 != d && forward[i - 1] < forward[i + 1]) {
+                    forward[i + 1]
+                } else {
+                    forward[i - 1] + 1
+                };
+                let mut y1 = x1 - k1;
+                while x1 < n && y1 < m && a[x1 as usize] == b[y1 as usize] {
+                    x1 += 1;
+                    y1 += 1;
+                }
+                forward[i] = x1;
+
+                if x1 > n {
+                    k1_end += 2;
+                } else if y1 > m {
+                    k1_start += 2;
+                } else if odd {
+                    let j = offset + delta - k1;
+                    if j >= 0 && (j as usize) < size && backward[j as usize] != -1 {
+                        let x2 = n - backward[j as usize];
+                        if x1 >= x2 {
+                            return Some((a_lo + x1 as usize, b_lo + y1 as usize));
+                        }
+                    }
+                }
+                k1 += 2;
+            }
+
+            let mut k2 = -d + k2_start;
+            while k2 <= d - k2_end {
+                let i = (offset + k2) as usize;
+                let mut x2 = if k2 == -d || (k2 != d && backward[i - 1] < backward[i + 1]) {
+                    backward[i + 1]
+                } else {
+                    backward[i - 1] + 1
+                };
+                let mut y2 = x2 - k2;
+                while x2 < n && y2 < m && a[(n - x2 - 1) as usize] == b[(m - y2 - 1) as usize] {
+                    x2 += 1;
+                    y2 += 1;
+                }
+                backward[i] = x2;
+
+                if x2 > n {
+                    k2_end += 2;
+                } else if y2 > m {
+                    k2_start += 2;
+                } else if !odd {
+                    let j = offset + delta - k2;
+                    if j >= 0 && (j as usize) < size && forward[j as usize] != -1 {
+                        let x1 = forward[j as usize];
+                        let y1 = offset + x1 - j;
+                        if x1 >= n - x2 {
+                            return Some((a_lo + x1 as usize, b_lo + y1 as usize));
+                        }
+                    }
+                }
+                k2 += 2;
+            }
+        }
+
+        None
+    }
+
+    /// Patience diff: anchor on lines that occur exactly once on both sides,
+    /// keep the longest run of anchors in order, and diff between them.
+    /// Falls back to Myers when there are no unique common lines.
+    fn patience(&self, a_lo: usize, a_hi: usize, b_lo: usize, b_hi: usize, out: &mut Vec<Edit>) {
+        let anchors = self.unique_anchors(a_lo, a_hi, b_lo, b_hi);
+        if anchors.is_empty() {
+            return self.myers(a_lo, a_hi, b_lo, b_hi, out);
+        }
+
+        let (mut x, mut y) = (a_lo, b_lo);
+        for (ax, by) in anchors {
+            self.diff(x, ax, y, by, out);
+            out.push(Edit::Equal(ax, by));
+            x = ax + 1;
+            y = by + 1;
+        }
+        self.diff(x, a_hi, y, b_hi, out);
+    }
+
+    fn unique_anchors(
+        &self,
+        a_lo: usize,
+        a_hi: usize,
+        b_lo: usize,
+        b_hi: usize,
+    ) -> Vec<(usize, usize)> {
+        // line id -> (count in a, index in a, count in b, index in b)
+        let mut seen: HashMap<usize, (usize, usize, usize, usize)> = HashMap::new();
+        for i in a_lo..a_hi {
+            let entry = seen.entry(self.a[i]).or_insert((0, i, 0, 0));
+            entry.0 += 1;
+        }
+        for j in b_lo..b_hi {
+            if let Some(entry) = seen.get_mut(&self.b[j]) {
+                entry.2 += 1;
+                entry.3 = j;
+            }
+        }
+
+        let mut pairs: Vec<(usize, usize)> = seen
+            .into_values()
+            .filter(|&(a_count, _, b_count, _)| a_count == 1 && b_count == 1)
+            .map(|(_, i, _, j)| (i, j))
+            .collect();
+        pairs.sort_unstable();
+
+        longest_increasing(&pairs)
+    }
+}
+
+/// Longest subsequence of `pairs` (sorted by old index) whose new indices
+/// increase, via patience sorting.
+fn longest_increasing(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut piles: Vec<usize> = Vec::new();
+    let mut previous: Vec<Option<usize>> = vec![None; pairs.len()];
+
+    for (i, &(_, j)) in pairs.iter().enumerate() {
+        let pile = piles.partition_point(|&top| pairs[top].1 < j);
+        if pile > 0 {
+            previous[i] = Some(piles[pile - 1]);
+        }
+        if pile == piles.len() {
+            piles.push(i);
+        } else {
+            piles[pile] = i;
+        }
+    }
+
+    let mut result = Vec::with_capacity(piles.len());
+    let mut current = piles.last().copied();
+    while let Some(i) = current {
+        result.push(pairs[i]);
+        current = previous[i];
+    }
+    result.reverse();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Applies an edit script to check it reproduces `new` from `old`.
+    fn apply<'a>(old: &[&'a str], new: &[&'a str], edits: &[Edit]) -> Vec<&'a str> {
+        let mut result = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        for edit in edits {
+            match *edit {
+                Edit::Equal(x, y) => {
+                    assert_eq!((x, y), (i, j));
+                    assert_eq!(old[x], new[y]);
+                    result.push(old[x]);
+                    i += 1;
+                    j += 1;
+                }
+                Edit::Delete(x) => {
+                    assert_eq!(x, i);
+                    i += 1;
+                }
+                Edit::Insert(y) => {
+                    assert_eq!(y, j);
+                    result.push(new[y]);
+                    j += 1;
+                }
+            }
+        }
+        assert_eq!((i, j), (old.len(), new.len()));
+        result
+    }
+
+    fn changes(edits: &[Edit]) -> usize {
+        edits
+            .iter()
+            .filter(|e| !matches!(e, Edit::Equal(..)))
+            .count()
+    }
+
+    #[test]
+    fn test_identical_and_empty() {
+        let a = ["x", "y"];
+        assert_eq!(
+            diff(&a, &a, Algorithm::Myers),
+            vec![Edit::Equal(0, 0), Edit::Equal(1, 1)]
+        );
+        assert_eq!(
+            diff(&[] as &[&str], &["x"], Algorithm::Myers),
+            vec![Edit::Insert(0)]
+        );
+        assert_eq!(
+            diff(&["x"], &[] as &[&str], Algorithm::Patience),
+            vec![Edit::Delete(0)]
+        );
+    }
+
+    #[test]
+    fn test_myers_is_minimal() {
+        // The classic example from Myers' paper: D = 5.
+        let a: Vec<&str> = "ABCABBA".split("").filter(|s| !s.is_empty()).collect();
+        let b: Vec<&str> = "CBABAC".split("").filter(|s| !s.is_empty()).collect();
+        let edits = diff(&a, &b, Algorithm::Myers);
+
+        assert_eq!(apply(&a, &b, &edits), b);
+        assert_eq!(changes(&edits), 5);
+    }
+
+    #[test]
+    fn test_algorithms_produce_valid_scripts() {
+        let cases: [(&str, &str); 4] = [
+            ("a b c d e f", "a x c d y f"),
+            ("a b c", "d e f"),
+            ("x a b x c x", "a x b c x x"),
+            ("fn a } fn b } fn c }", "fn a } fn c } fn b }"),
+        ];
+
+        for (old, new) in cases {
+            let a: Vec<&str> = old.split(' ').collect();
+            let b: Vec<&str> = new.split(' ').collect();
+            for algorithm in [Algorithm::Myers, Algorithm::Patience] {
+                let edits = diff(&a, &b, algorithm);
+                assert_eq!(apply(&a, &b, &edits), b, "{:?} {:?}", algorithm, old);
+            }
+        }
+    }
+
+    #[test]
+    fn test_patience_anchors_on_unique_lines() {
+        let a = ["{", "one", "}", "{", "two", "}"];
+        let b = ["{", "two", "}", "{", "one", "}"];
+        let edits = diff(&a, &b, Algorithm::Patience);
+
+        assert_eq!(apply(&a, &b, &edits), b);
+        // Once the shared outer braces are stripped, the inner "}" "{" pair
+        // is unique on both sides and becomes the anchor.
+        assert!(edits.contains(&Edit::Equal(2, 2)));
+        assert!(edits.contains(&Edit::Equal(3, 3)));
+    }
+
+    #[test]
+    fn test_hunks() {
+        let a: Vec<String> = (1..=20).map(|i| i.to_string()).collect();
+        let mut b = a.clone();
+        b[1] = "two".to_string();
+        b.remove(17);
+        let edits = diff(&a, &b, Algorithm::Myers);
+
+        let hunks = hunks(&edits, 3);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].header(), "@@ -1,5 +1,5 @@");
+        assert_eq!(hunks[1].header(), "@@ -15,6 +15,5 @@");
+
+        let merged = super::hunks(&edits, 8);
+        assert_eq!(merged.len(), 1);
+    }
+
+    #[test]
+    fn test_hunk_ranges_for_pure_insertions() {
+        let edits = diff(&["a"], &["a", "b"], Algorithm::Myers);
+        assert_eq!(hunks(&edits, 0)[0].header(), "@@ -1,0 +2 @@");
+
+        let edits = diff(&[] as &[&str], &["a"], Algorithm::Myers);
+        assert_eq!(hunks(&edits, 3)[0].header(), "@@ -0,0 +1 @@");
+    }
+
+    #[test]
+    fn test_algorithm_from_str() {
+        assert_eq!(
+            "Patience".parse::<Algorithm>().unwrap(),
+            Algorithm::Patience
+        );
+        assert!("histogram".parse::<Algorithm>().is_err());
+    }
+}
@@ -0,0 +1,695 @@
+//! Decoder for Ruby's `Marshal` format (version 4.8).
+//!
+//! Values are mapped onto a `serde_json::Value` tree so they can flow through
+//! the same code paths as JSON and YAML input:
+//!
+//! * `nil`, `true`, `false`, fixnums and floats map to their JSON equivalents;
+//!   bignums outside the `i64`/`u64` range become decimal strings.
+//! * Strings are decoded using their encoding ivar; symbols become strings.
+//! * Hash keys that are not strings or symbols use their JSON text.
+//! * Structs and plain objects become objects keyed by member / ivar name
+//!   (without the leading `@`). `Time` values become RFC 3339 strings.
+//! * Subclass, extension and `marshal_load` wrappers are unwrapped.
+
+use super::ruby_json::format_float;
+use anyhow::{bail, Result};
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use serde_json::{Map, Number, Value};
+
+const MAJOR_VERSION: u8 = 4;
+const MINOR_VERSION: u8 = 8;
+/// Nested arrays, hashes and objects allowed before the input is rejected,
+/// so a hostile file can't overflow the stack.
+const MAX_DEPTH: usize = 512;
+
+/// Decodes a complete `Marshal.dump` payload.
+pub fn load(bytes: &[u8]) -> Result<Value> {
+    if bytes.len() < 2 {
+        bail!("data too short");
+    }
+    if bytes[0] != MAJOR_VERSION || bytes[1] > MINOR_VERSION {
+        bail!(
+            "unsupported format version {}.{} (expected {}.{})",
+            bytes[0],
+            bytes[1],
+            MAJOR_VERSION,
+            MINOR_VERSION
+        );
+    }
+
+    let mut reader = Reader {
+        bytes,
+        pos: 2,
+        symbols: Vec::new(),
+        objects: Vec::new(),
+        depth: 0,
+    };
+    let value = reader.read_value()?;
+    Ok(value.into_json())
+}
+
+/// Decoded node before strings are resolved against their encoding.
+#[derive(Debug, Clone)]
+enum Node {
+    Nil,
+    Bool(bool),
+    Int(i64),
+    Big(String),
+    Float(f64),
+    Str(RubyString),
+    Symbol(String),
+    Array(Vec<Node>),
+    Hash(Vec<(Node, Node)>),
+    Object(Vec<(String, Node)>),
+    Time(String),
+}
+
+#[derive(Debug, Clone)]
+struct RubyString {
+    bytes: Vec<u8>,
+    encoding: Option<String>,
+}
+
+impl RubyString {
+    fn decode(&self) -> String {
+        let encoding = self
+            .encoding
+            .as_deref()
+            .map(str::to_ascii_uppercase)
+            .unwrap_or_else(|| "ASCII-8BIT".to_string());
+
+        match encoding.as_str() {
+            "ISO-8859-1" => self.bytes.iter().map(|&b| b as char).collect(),
+            "UTF-16LE" | "UTF-16BE" => {
+                let units: Vec<u16> = self
+                    .bytes
+                    .chunks_exact(2)
+                    .map(|pair| {
+                        let pair = [pair[0], pair[1]];
+                        if encoding == "UTF-16LE" {
+                            u16::from_le_bytes(pair)
+                        } else {
+                            u16::from_be_bytes(pair)
+                        }
+                    })
+                    .collect();
+                String::from_utf16_lossy(&units)
+            }
+            _ => String::from_utf8_lossy(&self.bytes).into_owned(),
+        }
+    }
+}
+
+impl Node {
+    fn into_json(self) -> Value {
+        match self {
+            Node::Nil => Value::Null,
+            Node::Bool(b) => Value::Bool(b),
+            Node::Int(i) => Value::Number(i.into()),
+            Node::Big(digits) => digits
+                .parse::<i64>()
+                .map(Value::from)
+                .or_else(|_| digits.parse::<u64>().map(Value::from))
+                .unwrap_or(Value::String(digits)),
+            Node::Float(f) => Number::from_f64(f)
+                .map(Value::Number)
+                .unwrap_or_else(|| Value::String(format_float(f))),
+            Node::Str(s) => Value::String(s.decode()),
+            Node::Symbol(s) | Node::Time(s) => Value::String(s),
+            Node::Array(items) => Value::Array(items.into_iter().map(Node::into_json).collect()),
+            Node::Hash(pairs) => Value::Object(
+                pairs
+                    .into_iter()
+                    .map(|(k, v)| (k.into_key(), v.into_json()))
+                    .collect(),
+            ),
+            Node::Object(fields) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(k, v)| (k, v.into_json()))
+                    .collect::<Map<_, _>>(),
+            ),
+        }
+    }
+
+    /// Ruby's `JSON.generate` calls `to_s` on non-string keys.
+    fn into_key(self) -> String {
+        match self.into_json() {
+            Value::Null => String::new(),
+            Value::String(s) => s,
+            other => other.to_string(),
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    symbols: Vec<String>,
+    /// Every non-immediate value, in the order Ruby registers them, so `@`
+    /// back-references can be resolved.
+    objects: Vec<Node>,
+    depth: usize,
+}
+
+impl Reader<'_> {
+    fn read_byte(&mut self) -> Result<u8> {
+        match self.bytes.get(self.pos) {
+            Some(&b) => {
+                self.pos += 1;
+                Ok(b)
+            }
+            None => bail!("unexpected end of data"),
+        }
+    }
+
+    fn read_exact(&mut self, len: usize) -> Result<&[u8]> {
+        if self.bytes.len() - self.pos < len {
+            bail!("unexpected end of data");
+        }
+        let slice = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    /// Marshal's variable-length integer (`r_long`).
+    fn read_int(&mut self) -> Result<i64> {
+        let c = self.read_byte()? as i8;
+        Ok(match c {
+            0 => 0,
+            1..=4 => {
+                let mut x: i64 = 0;
+                for i in 0..c {
+                    x |= (self.read_byte()? as i64) << (8 * i);
+                }
+                x
+            }
+            -4..=-1 => {
+                let mut x: i64 = -1;
+                for i in 0..-c {
+                    x &= !(0xff << (8 * i));
+                    x |= (self.read_byte()? as i64) << (8 * i);
+                }
+                x
+            }
+            c if c > 0 => c as i64 - 5,
+            c => c as i64 + 5,
+        })
+    }
+
+    fn read_len(&mut self) -> Result<usize> {
+        let len = self.read_int()?;
+        if len < 0 {
+            bail!("negative length at offset {}", self.pos);
+        }
+        Ok(len as usize)
+    }
+
+    fn read_bytes(&mut self) -> Result<Vec<u8>> {
+        let len = self.read_len()?;
+        Ok(self.read_exact(len)?.to_vec())
+    }
+
+    fn read_symbol(&mut self) -> Result<String> {
+        match self.read_byte()? {
+            b':' => self.read_symbol_body(),
+            b';' => self.read_symlink(),
+            // Symbols with a non-ASCII encoding are wrapped in an ivar list.
+            b'I' => {
+                let name = match self.read_byte()? {
+                    b':' => self.read_symbol_body()?,
+                    other => bail!("expected symbol, found type byte {:#04x}", other),
+                };
+                self.read_ivars()?;
+                Ok(name)
+            }
+            other => bail!("expected symbol, found type byte {:#04x}", other),
+        }
+    }
+
+    fn read_symbol_body(&mut self) -> Result<String> {
+        let name = String::from_utf8_lossy(&self.read_bytes()?).into_owned();
+        self.symbols.push(name.clone());
+        Ok(name)
+    }
+
+    fn read_symlink(&mut self) -> Result<String> {
+        let index = self.read_len()?;
+        match self.symbols.get(index) {
+            Some(name) => Ok(name.clone()),
+            None => bail!("invalid symbol link {}", index),
+        }
+    }
+
+    fn read_ivars(&mut self) -> Result<Vec<(String, Node)>> {
+        let count = self.read_len()?;
+        let mut ivars = Vec::with_capacity(count.min(1024));
+        for _ in 0..count {
+            let name = self.read_symbol()?;
+            let value = self.read_value()?;
+            ivars.push((name, value));
+        }
+        Ok(ivars)
+    }
+
+    /// Reserves a slot in the object table before the value's children are
+    /// read, matching the order Ruby uses when dumping.
+    fn reserve(&mut self) -> usize {
+        self.objects.push(Node::Nil);
+        self.objects.len() - 1
+    }
+
+    fn register(&mut self, node: Node) -> Node {
+        self.objects.push(node.clone());
+        node
+    }
+
+    fn store(&mut self, slot: usize, node: Node) -> Node {
+        self.objects[slot] = node.clone();
+        node
+    }
+
+    fn read_value(&mut self) -> Result<Node> {
+        if self.depth >= MAX_DEPTH {
+            bail!(
+                "values nested more than {} levels deep at offset {}",
+                MAX_DEPTH,
+                self.pos
+            );
+        }
+        self.depth += 1;
+        let node = self.read_node();
+        self.depth -= 1;
+        node
+    }
+
+    fn read_node(&mut self) -> Result<Node> {
+        let offset = self.pos;
+        let node = match self.read_byte()? {
+            b'0' => Node::Nil,
+            b'T' => Node::Bool(true),
+            b'F' => Node::Bool(false),
+            b'i' => Node::Int(self.read_int()?),
+            b':' => Node::Symbol(self.read_symbol_body()?),
+            b';' => Node::Symbol(self.read_symlink()?),
+            b'@' => {
+                let index = self.read_len()?;
+                match self.objects.get(index) {
+                    Some(node) => node.clone(),
+                    None => bail!("invalid object link {}", index),
+                }
+            }
+            b'I' => {
+                let slot = self.objects.len();
+                let inner = self.read_value()?;
+                let ivars = self.read_ivars()?;
+                let node = apply_ivars(inner, ivars);
+                if slot < self.objects.len() {
+                    self.store(slot, node)
+                } else {
+                    node
+                }
+            }
+            b'"' => {
+                let bytes = self.read_bytes()?;
+                self.register(Node::Str(RubyString {
+                    bytes,
+                    encoding: None,
+                }))
+            }
+            b'f' => {
+                let bytes = self.read_bytes()?;
+                let float = parse_float(&bytes)?;
+                self.register(Node::Float(float))
+            }
+            b'l' => {
+                let sign = self.read_byte()?;
+                let len = self.read_len()? * 2;
+                let digits = bignum_to_decimal(self.read_exact(len)?);
+                let digits = if sign == b'-' && digits != "0" {
+                    format!("-{}", digits)
+                } else {
+                    digits
+                };
+                self.register(Node::Big(digits))
+            }
+            b'[' => {
+                let slot = self.reserve();
+                let count = self.read_len()?;
+                let mut items = Vec::with_capacity(count.min(1024));
+                for _ in 0..count {
+                    items.push(self.read_value()?);
+                }
+                self.store(slot, Node::Array(items))
+            }
+            tag @ (b'{' | b'}') => {
+                let slot = self.reserve();
+                let count = self.read_len()?;
+                let mut pairs = Vec::with_capacity(count.min(1024));
+                for _ in 0..count {
+                    let key = self.read_value()?;
+                    let value = self.read_value()?;
+                    pairs.push((key, value));
+                }
+                if tag == b'}' {
+                    // The hash's default value is not part of the data.
+                    self.read_value()?;
+                }
+                self.store(slot, Node::Hash(pairs))
+            }
+            b'S' => {
+                let slot = self.reserve();
+                self.read_symbol()?;
+                let fields = self.read_ivars()?;
+                self.store(slot, Node::Object(fields))
+            }
+            b'o' => {
+                let slot = self.reserve();
+                self.read_symbol()?;
+                let fields = self
+                    .read_ivars()?
+                    .into_iter()
+                    .map(|(name, value)| (strip_ivar_prefix(name), value))
+                    .collect();
+                self.store(slot, Node::Object(fields))
+            }
+            b'u' => {
+                let class = self.read_symbol()?;
+                let data = self.read_bytes()?;
+                let node = if class == "Time" {
+                    Node::Time(decode_time(&data)?)
+                } else {
+                    Node::Str(RubyString {
+                        bytes: data,
+                        encoding: None,
+                    })
+                };
+                self.register(node)
+            }
+            b'U' => {
+                let slot = self.reserve();
+                self.read_symbol()?;
+                let inner = self.read_value()?;
+                self.store(slot, inner)
+            }
+            b'e' => {
+                self.read_symbol()?;
+                self.read_value()?
+            }
+            b'C' => {
+                self.read_symbol()?;
+                self.read_value()?
+            }
+            b'/' => {
+                let source = self.read_bytes()?;
+                self.read_byte()?;
+                self.register(Node::Str(RubyString {
+                    bytes: source,
+                    encoding: None,
+                }))
+            }
+            b'c' | b'm' | b'M' => {
+                let name = self.read_bytes()?;
+                self.register(Node::Symbol(String::from_utf8_lossy(&name).into_owned()))
+            }
+            other => bail!("unsupported type byte {:#04x} at offset {}", other, offset),
+        };
+        Ok(node)
+    }
+}
+
+/// Applies the ivars that follow an `I` wrapper: string encodings and the
+/// UTC offset of `Time` values.
+fn apply_ivars(inner: Node, ivars: Vec<(String, Node)>) -> Node {
+    match inner {
+        Node::Str(mut s) => {
+            for (name, value) in ivars {
+                match (name.as_str(), value) {
+                    ("E", Node::Bool(true)) => s.encoding = Some("UTF-8".to_string()),
+                    ("E", Node::Bool(false)) => s.encoding = Some("US-ASCII".to_string()),
+                    ("encoding", Node::Str(name)) => {
+                        s.encoding = Some(String::from_utf8_lossy(&name.bytes).into_owned())
+                    }
+                    _ => {}
+                }
+            }
+            Node::Str(s)
+        }
+        Node::Time(time) => {
+            let offset = ivars.iter().find_map(|(name, value)| match value {
+                Node::Int(offset) if name == "offset" => Some(*offset),
+                _ => None,
+            });
+            match (offset, DateTime::parse_from_rfc3339(&time)) {
+                (Some(offset), Ok(utc)) => FixedOffset::east_opt(offset as i32)
+                    .map(|tz| Node::Time(utc.with_timezone(&tz).to_rfc3339()))
+                    .unwrap_or(Node::Time(time)),
+                _ => Node::Time(time),
+            }
+        }
+        other => other,
+    }
+}
+
+fn strip_ivar_prefix(name: String) -> String {
+    match name.strip_prefix('@') {
+        Some(stripped) => stripped.to_string(),
+        None => name,
+    }
+}
+
+fn parse_float(bytes: &[u8]) -> Result<f64> {
+    // Very old dumps append mantissa bytes after a NUL terminator.
+    let text = bytes.split(|&b| b == 0).next().unwrap_or_default();
+    let text = String::from_utf8_lossy(text);
+    match text.as_ref() {
+        "nan" => Ok(f64::NAN),
+        "inf" => Ok(f64::INFINITY),
+        "-inf" => Ok(f64::NEG_INFINITY),
+        other => other
+            .parse()
+            .map_err(|_| anyhow::anyhow!("invalid float {:?}", other)),
+    }
+}
+
+/// Converts a little-endian magnitude into decimal digits.
+fn bignum_to_decimal(bytes: &[u8]) -> String {
+    let mut magnitude: Vec<u8> = bytes.iter().rev().copied().collect();
+    let mut digits = Vec::new();
+
+    while magnitude.iter().any(|&b| b != 0) {
+        let mut remainder: u32 = 0;
+        for byte in magnitude.iter_mut() {
+            let value = (remainder << 8) | *byte as u32;
+            *byte = (value / 10) as u8;
+            remainder = value % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+    }
+
+    if digits.is_empty() {
+        return "0".to_string();
+    }
+    digits.reverse();
+    String::from_utf8(digits).unwrap_or_default()
+}
+
+/// Decodes the 8-byte payload of `Time#_dump` into an RFC 3339 string in UTC.
+fn decode_time(data: &[u8]) -> Result<String> {
+    if data.len() < 8 {
+        bail!("invalid Time payload");
+    }
+    let p = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+    let s = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+
+    let year = 1900 + ((p >> 14) & 0xffff) as i32;
+    let month = ((p >> 10) & 0xf) + 1;
+    let day = (p >> 5) & 0x1f;
+    let hour = p & 0x1f;
+    let minute = (s >> 26) & 0x3f;
+    let second = (s >> 20) & 0x3f;
+    let usec = s & 0xfffff;
+
+    let time = NaiveDate::from_ymd_opt(year, month, day)
+        .and_then(|d| d.and_hms_micro_opt(hour, minute, second, usec))
+        .ok_or_else(|| anyhow::anyhow!("invalid Time payload"))?;
+    Ok(time.and_utc().to_rfc3339())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn dump(body: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![4, 8];
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    #[test]
+    fn test_immediates() {
+        assert_eq!(load(&dump(b"0")).unwrap(), json!(null));
+        assert_eq!(load(&dump(b"T")).unwrap(), json!(true));
+        assert_eq!(load(&dump(b"F")).unwrap(), json!(false));
+    }
+
+    #[test]
+    fn test_fixnums() {
+        assert_eq!(load(&dump(b"i\x00")).unwrap(), json!(0));
+        assert_eq!(load(&dump(b"i\x06")).unwrap(), json!(1));
+        assert_eq!(load(&dump(b"i\xfa")).unwrap(), json!(-1));
+        assert_eq!(load(&dump(b"i\x02,\x01")).unwrap(), json!(300));
+        assert_eq!(load(&dump(b"i\xfe\xd4\xfe")).unwrap(), json!(-300));
+        assert_eq!(
+            load(&dump(b"i\x04\xff\xff\xff\x3f")).unwrap(),
+            json!(1073741823)
+        );
+    }
+
+    #[test]
+    fn test_bignums() {
+        // 2**31 and -(2**64)
+        assert_eq!(
+            load(&dump(b"l+\x07\x00\x00\x00\x80")).unwrap(),
+            json!(2147483648u64)
+        );
+        assert_eq!(
+            load(&dump(b"l-\x0a\x00\x00\x00\x00\x00\x00\x00\x00\x01\x00")).unwrap(),
+            json!("-18446744073709551616")
+        );
+    }
+
+    #[test]
+    fn test_floats() {
+        assert_eq!(load(&dump(b"f\x081.5")).unwrap(), json!(1.5));
+        assert_eq!(load(&dump(b"f\x09-inf")).unwrap(), json!("-Infinity"));
+        assert_eq!(load(&dump(b"f\x0a1e-05")).unwrap(), json!(0.00001));
+    }
+
+    #[test]
+    fn test_strings_with_encodings() {
+        assert_eq!(
+            load(&dump(b"I\"\x0ahello\x06:\x06ET")).unwrap(),
+            json!("hello")
+        );
+        assert_eq!(load(&dump(b"\"\x07\xc3\xa9")).unwrap(), json!("é"));
+        assert_eq!(
+            load(&dump(b"I\"\x06\xe9\x06:\x0dencoding\"\x0fISO-8859-1")).unwrap(),
+            json!("é")
+        );
+        assert_eq!(
+            load(&dump(b"I\"\x09h\x00i\x00\x06:\x0dencoding\"\x0dUTF-16LE")).unwrap(),
+            json!("hi")
+        );
+    }
+
+    #[test]
+    fn test_symbols_and_links() {
+        assert_eq!(load(&dump(b":\x08sym")).unwrap(), json!("sym"));
+        assert_eq!(load(&dump(b"[\x07:\x06a;\x00")).unwrap(), json!(["a", "a"]));
+        // s = "x"; [s, s]
+        assert_eq!(
+            load(&dump(b"[\x07I\"\x06x\x06:\x06ET@\x06")).unwrap(),
+            json!(["x", "x"])
+        );
+    }
+
+    #[test]
+    fn test_arrays_and_hashes() {
+        // [1, "a", nil]
+        assert_eq!(
+            load(&dump(b"[\x08i\x06I\"\x06a\x06:\x06ET0")).unwrap(),
+            json!([1, "a", null])
+        );
+        // { "a" => 1, :b => [true], 2 => nil }
+        assert_eq!(
+            load(&dump(b"{\x08I\"\x06a\x06:\x06ETi\x06:\x06b[\x06Ti\x070")).unwrap(),
+            json!({"a": 1, "b": [true], "2": null})
+        );
+        // Hash.new(0).merge("k" => 1)
+        assert_eq!(
+            load(&dump(b"}\x06I\"\x06k\x06:\x06ETi\x06i\x00")).unwrap(),
+            json!({"k": 1})
+        );
+    }
+
+    #[test]
+    fn test_structs_and_objects() {
+        // Point = Struct.new(:x, :y); Point.new(1, 2)
+        assert_eq!(
+            load(&dump(b"S:\x0aPoint\x07:\x06xi\x06:\x06yi\x07")).unwrap(),
+            json!({"x": 1, "y": 2})
+        );
+        // Foo.new with @a = 1, @b = "z"
+        assert_eq!(
+            load(&dump(
+                b"o:\x08Foo\x07:\x07@ai\x06:\x07@bI\"\x06z\x06:\x06ET"
+            ))
+            .unwrap(),
+            json!({"a": 1, "b": "z"})
+        );
+    }
+
+    #[test]
+    fn test_wrappers() {
+        // class MyString < String; MyString.new("s")
+        assert_eq!(
+            load(&dump(b"IC:\x0dMyString\"\x06s\x06:\x06ET")).unwrap(),
+            json!("s")
+        );
+        // Rational(1, 2)
+        assert_eq!(
+            load(&dump(b"U:\x0dRational[\x07i\x06i\x07")).unwrap(),
+            json!([1, 2])
+        );
+    }
+
+    #[test]
+    fn test_time() {
+        // Time.new(2024, 1, 15, 12, 30, 45, "+02:00"), stored as UTC plus offset
+        let p: u32 = (1 << 31) | (124 << 14) | (15 << 5) | 10;
+        let s: u32 = (30 << 26) | (45 << 20);
+        let mut body = b"Iu:\x09Time\x0d".to_vec();
+        body.extend_from_slice(&p.to_le_bytes());
+        body.extend_from_slice(&s.to_le_bytes());
+        body.extend_from_slice(b"\x06:\x0boffseti\x02\x20\x1c");
+
+        assert_eq!(
+            load(&dump(&body)).unwrap(),
+            json!("2024-01-15T12:30:45+02:00")
+        );
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        assert!(load(b"\x04").is_err());
+        assert!(load(b"\x03\x00i\x06").is_err());
+        assert!(load(&dump(b"[\x07i\x06")).is_err());
+        assert!(load(&dump(b"d:\x08Foo0")).is_err());
+    }
+
+    #[test]
+    fn test_rejects_deep_nesting() {
+        // Debug builds need more stack per level than a test thread's 2 MiB,
+        // so run with the 8 MiB a main thread usually gets.
+        std::thread::Builder::new()
+            .stack_size(8 * 1024 * 1024)
+            .spawn(|| {
+                // [[[...nil...]]] one level past the limit.
+                let mut body = b"[\x06".repeat(MAX_DEPTH);
+                body.push(b'0');
+                let error = load(&dump(&body)).unwrap_err().to_string();
+                assert!(error.contains("nested more than 512"), "{}", error);
+
+                let mut body = b"[\x06".repeat(MAX_DEPTH - 1);
+                body.push(b'0');
+                assert!(load(&dump(&body)).is_ok());
+
+                assert!(load(&dump(&b"{\x06".repeat(100_000))).is_err());
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+}
@@ -0,0 +1,851 @@
+//! YAML emitter that reproduces the output of Ruby's `Object#to_yaml`.
+//!
+//! Psych decides a requested scalar style in Ruby (`Psych::Visitors::YAMLTree`)
+//! and libyaml's emitter then downgrades it when the scalar cannot be written
+//! that way. Both halves are mirrored here so the bytes match.
+
+use super::ruby_json::format_float;
+use anyhow::Result;
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::sync::OnceLock;
+
+const BEST_INDENT: i32 = 2;
+const BEST_WIDTH: usize = 80;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PsychOptions {
+    /// Emit mapping keys as Ruby symbols (`:name: value`), matching data
+    /// that was loaded with `symbolize_names: true`.
+    pub symbolize_keys: bool,
+}
+
+/// Serializes `data` exactly like Ruby's `data.to_yaml`.
+pub fn dump<T: Serialize + ?Sized>(data: &T) -> Result<String> {
+    dump_with(data, PsychOptions::default())
+}
+
+pub fn dump_with<T: Serialize + ?Sized>(data: &T, options: PsychOptions) -> Result<String> {
+    let value = serde_json::to_value(data)?;
+    let mut emitter = Emitter::new(options);
+
+    emitter.write_indicator("---", true, false, false);
+    emitter.emit_node(&value, -1, false);
+    emitter.write_indent(-1);
+    // A trailing `|+` block leaves the document open-ended, so libyaml
+    // closes the stream with an explicit document end marker.
+    if emitter.open_ended {
+        emitter.write_indicator("...", true, false, false);
+        emitter.write_indent(-1);
+    }
+
+    Ok(emitter.out)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    Plain,
+    SingleQuoted,
+    DoubleQuoted,
+    Literal,
+}
+
+/// A scalar event as Psych hands it to libyaml.
+struct Scalar {
+    value: String,
+    style: Style,
+    tag: Option<&'static str>,
+    quoted_implicit: bool,
+}
+
+impl Scalar {
+    /// Numbers, booleans, nil and symbols: plain-implicit only.
+    fn plain(value: impl Into<String>) -> Self {
+        Self {
+            value: value.into(),
+            style: Style::Plain,
+            tag: None,
+            quoted_implicit: false,
+        }
+    }
+
+    /// Psych's `visit_String`: picks the style Ruby asks libyaml for.
+    fn string(s: &str) -> Self {
+        let (style, tag) = if has_inner_line_break(s) {
+            (Style::Literal, None)
+        } else if s == "<<" {
+            (Style::SingleQuoted, Some("!!str"))
+        } else if matches!(s, "y" | "Y" | "n" | "N") || starts_with_non_word_without_quote(s) {
+            (Style::DoubleQuoted, None)
+        } else if !tokenizes_as_string(s) || octal_like_with_invalid_digit(s) {
+            (Style::SingleQuoted, None)
+        } else {
+            (Style::Plain, None)
+        };
+
+        Self {
+            value: s.to_string(),
+            style,
+            tag,
+            quoted_implicit: tag.is_none(),
+        }
+    }
+
+    /// libyaml's `yaml_emitter_check_simple_key`.
+    fn is_simple_key(&self) -> bool {
+        let length = self.tag.map_or(0, str::len) + self.value.len();
+        !analyze_scalar(&self.value).multiline && length <= 128
+    }
+}
+
+/// What libyaml's `yaml_emitter_analyze_scalar` learns about a scalar.
+struct Analysis {
+    multiline: bool,
+    block_plain_allowed: bool,
+    single_quoted_allowed: bool,
+    block_allowed: bool,
+}
+
+struct Emitter {
+    out: String,
+    column: usize,
+    whitespace: bool,
+    indention: bool,
+    open_ended: bool,
+    options: PsychOptions,
+}
+
+impl Emitter {
+    fn new(options: PsychOptions) -> Self {
+        Self {
+            out: String::new(),
+            column: 0,
+            whitespace: true,
+            indention: true,
+            open_ended: false,
+            options,
+        }
+    }
+
+    fn emit_node(&mut self, value: &Value, parent_indent: i32, mapping_context: bool) {
+        match value {
+            Value::Null => self.emit_scalar(&Scalar::plain(""), parent_indent, false),
+            Value::Bool(b) => self.emit_scalar(&Scalar::plain(b.to_string()), parent_indent, false),
+            Value::Number(n) => {
+                let text = match n.as_f64() {
+                    Some(f) if n.is_f64() => format_float(f),
+                    _ => n.to_string(),
+                };
+                self.emit_scalar(&Scalar::plain(text), parent_indent, false)
+            }
+            Value::String(s) => self.emit_scalar(&Scalar::string(s), parent_indent, false),
+            Value::Array(items) if items.is_empty() => self.emit_empty("[", "]"),
+            Value::Object(map) if map.is_empty() => self.emit_empty("{", "}"),
+            Value::Array(items) => self.emit_sequence(items, parent_indent, mapping_context),
+            Value::Object(map) => self.emit_mapping(map, parent_indent),
+        }
+    }
+
+    fn emit_empty(&mut self, open: &str, close: &str) {
+        self.write_indicator(open, true, true, false);
+        self.write_indicator(close, false, false, false);
+    }
+
+    fn emit_sequence(&mut self, items: &[Value], parent_indent: i32, mapping_context: bool) {
+        // Sequences that are simple mapping values are written "indentless".
+        let indentless = mapping_context && !self.indention;
+        let indent = increase_indent(parent_indent, false, indentless);
+
+        for item in items {
+            self.write_indent(indent);
+            self.write_indicator("-", true, false, true);
+            self.emit_node(item, indent, false);
+        }
+    }
+
+    fn emit_mapping(&mut self, map: &Map<String, Value>, parent_indent: i32) {
+        let indent = increase_indent(parent_indent, false, false);
+
+        for (key, value) in map {
+            let key = if self.options.symbolize_keys {
+                Scalar::plain(format!(":{}", key))
+            } else {
+                Scalar::string(key)
+            };
+
+            self.write_indent(indent);
+            if key.is_simple_key() {
+                self.emit_scalar(&key, indent, true);
+                self.write_indicator(":", false, false, false);
+            } else {
+                self.write_indicator("?", true, false, true);
+                self.emit_scalar(&key, indent, false);
+                self.write_indent(indent);
+                self.write_indicator(":", true, false, true);
+            }
+            self.emit_node(value, indent, true);
+        }
+    }
+
+    /// libyaml's `yaml_emitter_emit_scalar`.
+    fn emit_scalar(&mut self, scalar: &Scalar, parent_indent: i32, simple_key: bool) {
+        let value = scalar.value.as_str();
+        let analysis = analyze_scalar(value);
+        let style = select_style(scalar.style, &analysis, value.is_empty(), simple_key);
+        let indent = increase_indent(parent_indent, true, false);
+
+        let tag = match scalar.tag {
+            None if !scalar.quoted_implicit && style != Style::Plain => Some("!"),
+            tag => tag,
+        };
+        if let Some(tag) = tag {
+            self.write_indicator(tag, true, false, false);
+        }
+
+        match style {
+            Style::Plain => self.write_plain(value, indent, !simple_key),
+            Style::SingleQuoted => self.write_single_quoted(value, indent, !simple_key),
+            Style::DoubleQuoted => self.write_double_quoted(value, indent, !simple_key),
+            Style::Literal => self.write_literal(value, indent),
+        }
+    }
+
+    fn put(&mut self, c: char) {
+        self.out.push(c);
+        self.column += 1;
+    }
+
+    fn put_break(&mut self) {
+        self.out.push('\n');
+        self.column = 0;
+    }
+
+    fn write_break(&mut self, c: char) {
+        if c == '\n' {
+            self.put_break();
+        } else {
+            self.out.push(c);
+            self.column = 0;
+        }
+    }
+
+    fn write_indent(&mut self, indent: i32) {
+        let indent = indent.max(0) as usize;
+
+        if !self.indention || self.column > indent || (self.column == indent && !self.whitespace) {
+            self.put_break();
+        }
+        while self.column < indent {
+            self.put(' ');
+        }
+
+        self.whitespace = true;
+        self.indention = true;
+    }
+
+    fn write_indicator(
+        &mut self,
+        indicator: &str,
+        need_whitespace: bool,
+        is_whitespace: bool,
+        is_indention: bool,
+    ) {
+        if need_whitespace && !self.whitespace {
+            self.put(' ');
+        }
+        for c in indicator.chars() {
+            self.put(c);
+        }
+
+        self.whitespace = is_whitespace;
+        self.indention = self.indention && is_indention;
+    }
+
+    fn write_plain(&mut self, value: &str, indent: i32, allow_breaks: bool) {
+        if !self.whitespace && !value.is_empty() {
+            self.put(' ');
+        }
+
+        let chars: Vec<char> = value.chars().collect();
+        let mut spaces = false;
+        for (i, &c) in chars.iter().enumerate() {
+            if c == ' ' {
+                let next_is_space = chars.get(i + 1) == Some(&' ');
+                if allow_breaks && !spaces && self.column > BEST_WIDTH && !next_is_space {
+                    self.write_indent(indent);
+                } else {
+                    self.put(c);
+                }
+                spaces = true;
+            } else {
+                self.put(c);
+                self.indention = false;
+                spaces = false;
+            }
+        }
+
+        self.whitespace = false;
+        self.indention = false;
+    }
+
+    fn write_single_quoted(&mut self, value: &str, indent: i32, allow_breaks: bool) {
+        self.write_indicator("'", true, false, false);
+
+        let chars: Vec<char> = value.chars().collect();
+        let mut spaces = false;
+        let mut breaks = false;
+        for (i, &c) in chars.iter().enumerate() {
+            if c == ' ' {
+                let next_is_space = chars.get(i + 1) == Some(&' ');
+                if allow_breaks
+                    && !spaces
+                    && self.column > BEST_WIDTH
+                    && i != 0
+                    && i != chars.len() - 1
+                    && !next_is_space
+                {
+                    self.write_indent(indent);
+                } else {
+                    self.put(c);
+                }
+                spaces = true;
+            } else if is_break(c) {
+                if !breaks && c == '\n' {
+                    self.put_break();
+                }
+                self.write_break(c);
+                self.indention = true;
+                breaks = true;
+            } else {
+                if breaks {
+                    self.write_indent(indent);
+                }
+                if c == '\'' {
+                    self.put('\'');
+                }
+                self.put(c);
+                self.indention = false;
+                spaces = false;
+                breaks = false;
+            }
+        }
+
+        if breaks {
+            self.write_indent(indent);
+        }
+
+        self.write_indicator("'", false, false, false);
+        self.whitespace = false;
+        self.indention = false;
+    }
+
+    fn write_double_quoted(&mut self, value: &str, indent: i32, allow_breaks: bool) {
+        self.write_indicator("\"", true, false, false);
+
+        let chars: Vec<char> = value.chars().collect();
+        let mut spaces = false;
+        for (i, &c) in chars.iter().enumerate() {
+            if !is_printable(c) || c == '\u{feff}' || is_break(c) || c == '"' || c == '\\' {
+                self.put('\\');
+                let escape = match c {
+                    '\0' => "0".to_string(),
+                    '\u{7}' => "a".to_string(),
+                    '\u{8}' => "b".to_string(),
+                    '\t' => "t".to_string(),
+                    '\n' => "n".to_string(),
+                    '\u{b}' => "v".to_string(),
+                    '\u{c}' => "f".to_string(),
+                    '\r' => "r".to_string(),
+                    '\u{1b}' => "e".to_string(),
+                    '"' => "\"".to_string(),
+                    '\\' => "\\".to_string(),
+                    '\u{85}' => "N".to_string(),
+                    '\u{a0}' => "_".to_string(),
+                    '\u{2028}' => "L".to_string(),
+                    '\u{2029}' => "P".to_string(),
+                    c if (c as u32) <= 0xFF => format!("x{:02X}", c as u32),
+                    c if (c as u32) <= 0xFFFF => format!("u{:04X}", c as u32),
+                    c => format!("U{:08X}", c as u32),
+                };
+                for e in escape.chars() {
+                    self.put(e);
+                }
+                spaces = false;
+            } else if c == ' ' {
+                if allow_breaks
+                    && !spaces
+                    && self.column > BEST_WIDTH
+                    && i != 0
+                    && i != chars.len() - 1
+                {
+                    self.write_indent(indent);
+                    if chars.get(i + 1) == Some(&' ') {
+                        self.put('\\');
+                    }
+                } else {
+                    self.put(c);
+                }
+                spaces = true;
+            } else {
+                self.put(c);
+                spaces = false;
+            }
+        }
+
+        self.write_indicator("\"", false, false, false);
+        self.whitespace = false;
+        self.indention = false;
+    }
+
+    fn write_literal(&mut self, value: &str, indent: i32) {
+        self.write_indicator("|", true, false, false);
+
+        let chars: Vec<char> = value.chars().collect();
+        if chars.first().is_some_and(|&c| c == ' ' || is_break(c)) {
+            self.write_indicator(&BEST_INDENT.to_string(), false, false, false);
+        }
+        let chomp = match chars.as_slice() {
+            [] => Some("-"),
+            [.., last] if !is_break(*last) => Some("-"),
+            [_] => Some("+"),
+            [.., before, _] if is_break(*before) => Some("+"),
+            _ => None,
+        };
+        self.open_ended = chomp == Some("+");
+        if let Some(chomp) = chomp {
+            self.write_indicator(chomp, false, false, false);
+        }
+
+        self.put_break();
+        self.indention = true;
+        self.whitespace = true;
+
+        let mut breaks = true;
+        for c in chars {
+            if is_break(c) {
+                self.write_break(c);
+                self.indention = true;
+                breaks = true;
+            } else {
+                if breaks {
+                    self.write_indent(indent);
+                }
+                self.put(c);
+                self.indention = false;
+                breaks = false;
+            }
+        }
+    }
+}
+
+/// libyaml's `yaml_emitter_increase_indent`.
+fn increase_indent(indent: i32, flow: bool, indentless: bool) -> i32 {
+    if indent < 0 {
+        if flow {
+            BEST_INDENT
+        } else {
+            0
+        }
+    } else if !indentless {
+        indent + BEST_INDENT
+    } else {
+        indent
+    }
+}
+
+/// libyaml's `yaml_emitter_select_scalar_style`, for block context.
+fn select_style(requested: Style, analysis: &Analysis, empty: bool, simple_key: bool) -> Style {
+    let mut style = requested;
+
+    if simple_key && analysis.multiline {
+        style = Style::DoubleQuoted;
+    }
+
+    if style == Style::Plain && (!analysis.block_plain_allowed || (empty && simple_key)) {
+        style = Style::SingleQuoted;
+    }
+
+    if style == Style::SingleQuoted && !analysis.single_quoted_allowed {
+        style = Style::DoubleQuoted;
+    }
+
+    if style == Style::Literal && (!analysis.block_allowed || simple_key) {
+        style = Style::DoubleQuoted;
+    }
+
+    style
+}
+
+/// libyaml's `yaml_emitter_analyze_scalar`, for block context.
+fn analyze_scalar(value: &str) -> Analysis {
+    if value.is_empty() {
+        return Analysis {
+            multiline: false,
+            block_plain_allowed: true,
+            single_quoted_allowed: true,
+            block_allowed: false,
+        };
+    }
+
+    let chars: Vec<char> = value.chars().collect();
+    let blankz = |i: usize| {
+        chars
+            .get(i)
+            .is_none_or(|&c| c == ' ' || c == '\t' || is_break(c))
+    };
+
+    let mut block_indicators = value.starts_with("---") || value.starts_with("...");
+    let mut line_breaks = false;
+    let mut special_characters = false;
+    let mut leading_space = false;
+    let mut leading_break = false;
+    let mut trailing_space = false;
+    let mut trailing_break = false;
+    let mut break_space = false;
+    let mut space_break = false;
+    let mut preceded_by_whitespace = true;
+    let mut previous_space = false;
+    let mut previous_break = false;
+
+    for (i, &c) in chars.iter().enumerate() {
+        let followed_by_whitespace = blankz(i + 1);
+        let first = i == 0;
+        let last = i == chars.len() - 1;
+
+        if first {
+            if matches!(
+                c,
+                '#' | ','
+                    | '['
+                    | ']'
+                    | '{'
+                    | '}'
+                    | '&'
+                    | '*'
+                    | '!'
+                    | '|'
+                    | '>'
+                    | '\''
+                    | '"'
+                    | '%'
+                    | '@'
+                    | '`'
+            ) {
+                block_indicators = true;
+            }
+            if matches!(c, '?' | ':') && followed_by_whitespace {
+                block_indicators = true;
+            }
+            if c == '-' && followed_by_whitespace {
+                block_indicators = true;
+            }
+        } else {
+            if c == ':' && followed_by_whitespace {
+                block_indicators = true;
+            }
+            if c == '#' && preceded_by_whitespace {
+                block_indicators = true;
+            }
+        }
+
+        if !is_printable(c) {
+            special_characters = true;
+        }
+        if is_break(c) {
+            line_breaks = true;
+        }
+
+        if c == ' ' {
+            leading_space |= first;
+            trailing_space |= last;
+            break_space |= previous_break;
+            previous_space = true;
+            previous_break = false;
+        } else if is_break(c) {
+            leading_break |= first;
+            trailing_break |= last;
+            space_break |= previous_space;
+            previous_space = false;
+            previous_break = true;
+        } else {
+            previous_space = false;
+            previous_break = false;
+        }
+
+        preceded_by_whitespace = c == ' ' || c == '\t' || is_break(c);
+    }
+
+    let mut analysis = Analysis {
+        multiline: line_breaks,
+        block_plain_allowed: true,
+        single_quoted_allowed: true,
+        block_allowed: true,
+    };
+
+    if leading_space || leading_break || trailing_space || trailing_break {
+        analysis.block_plain_allowed = false;
+    }
+    if trailing_space {
+        analysis.block_allowed = false;
+    }
+    if break_space {
+        analysis.block_plain_allowed = false;
+        analysis.single_quoted_allowed = false;
+    }
+    if space_break || special_characters {
+        analysis.block_plain_allowed = false;
+        analysis.single_quoted_allowed = false;
+        analysis.block_allowed = false;
+    }
+    if line_breaks || block_indicators {
+        analysis.block_plain_allowed = false;
+    }
+
+    analysis
+}
+
+/// libyaml's `IS_PRINTABLE` (with unicode output enabled, as Psych does).
+fn is_printable(c: char) -> bool {
+    matches!(c as u32,
+        0x0A | 0x20..=0x7E | 0xA0..=0xD7FF | 0xE000..=0xFEFE | 0xFF00..=0xFFFD)
+}
+
+fn is_break(c: char) -> bool {
+    matches!(c, '\r' | '\n' | '\u{85}' | '\u{2028}' | '\u{2029}')
+}
+
+/// Psych's `o =~ /\n(?!\Z)/`: a newline that is not part of a single
+/// trailing line break.
+fn has_inner_line_break(s: &str) -> bool {
+    s.match_indices('\n').any(|(i, _)| {
+        let rest = &s[i + 1..];
+        !rest.is_empty() && rest != "\n"
+    })
+}
+
+/// Psych's `o =~ /^[^[:word:]][^"]*$/` for single-line strings.
+fn starts_with_non_word_without_quote(s: &str) -> bool {
+    let mut chars = s.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    if first.is_alphanumeric() || first == '_' {
+        return false;
+    }
+
+    if first == '\n' {
+        return true;
+    }
+    for c in chars {
+        match c {
+            '\n' => return true,
+            '"' => return false,
+            _ => {}
+        }
+    }
+    true
+}
+
+fn octal_like_with_invalid_digit(s: &str) -> bool {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN
+        .get_or_init(|| Regex::new(r"\A0[0-7]*[89]").unwrap())
+        .is_match(s)
+}
+
+/// Whether `Psych::ScalarScanner#tokenize` would hand the string back as a
+/// String (rather than nil, a boolean, a number, a date or a symbol).
+fn tokenizes_as_string(s: &str) -> bool {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        [
+            // 0: looks like a word
+            r"(?m)^[^0-9.:\-]?[\p{Alphabetic}_ \t\r\n\f\v!@#$%\^&*(){}<>|/\\~;=]+",
+            // 1: time
+            r"(?m)^-?[0-9]{4}-[0-9]{1,2}-[0-9]{1,2}(?:[Tt]|[ \t\r\n\f\v]+)[0-9]{1,2}:[0-9][0-9]:[0-9][0-9](?:\.[0-9]*)?(?:[ \t\r\n\f\v]*(?:Z|[-+][0-9]{1,2}:?(?:[0-9][0-9])?))?$",
+            // 2: date
+            r"(?m)^[0-9]{4}-(?:1[012]|0[0-9]|[0-9])-(?:[12][0-9]|3[01]|0[0-9]|[0-9])$",
+            // 3: special floats
+            r"(?mi)^(?:\+?\.inf|-\.inf|\.nan)$",
+            // 4: symbol
+            r"(?m)^:.",
+            // 5: sexagesimal
+            r"(?m)^[-+]?[0-9][0-9_]*(?::[0-5]?[0-9]){1,2}(?:\.[0-9_]*)?$",
+            // 6: float
+            r"(?m)^[-+]?(?:[0-9][0-9_,]*)?\.[0-9]*(?:[eE][-+][0-9]+)?$",
+            // 7: integer (legacy, allows commas)
+            r"(?m)^(?:[-+]?0b[0-1_,]+|[-+]?0[0-7_,]+|[-+]?(?:0|[1-9](?:[0-9]|,[0-9]|_[0-9])*)|[-+]?0x[0-9a-fA-F_,]+)$",
+        ]
+        .iter()
+        .map(|p| Regex::new(p).unwrap())
+        .collect()
+    });
+
+    if s.is_empty() {
+        return false;
+    }
+
+    if patterns[0].is_match(s) || s.contains('\n') {
+        if s.chars().count() > 5 {
+            return true;
+        }
+        // `$` also matches before a trailing newline in Ruby.
+        let lower = s.to_lowercase();
+        if !lower.starts_with(['y', 't', 'o', 'n', 'f', '~']) {
+            return true;
+        }
+        return s != "~"
+            && !matches!(
+                lower.trim_end_matches('\n'),
+                "null" | "yes" | "true" | "on" | "no" | "false" | "off"
+            );
+    }
+
+    if patterns[1].is_match(s) {
+        return false;
+    }
+    if patterns[2].is_match(s) {
+        return NaiveDate::parse_from_str(s, "%Y-%m-%d").is_err();
+    }
+    if patterns[3].is_match(s) || patterns[4].is_match(s) || patterns[5].is_match(s) {
+        return false;
+    }
+    if patterns[6].is_match(s) {
+        let body = s.trim_start_matches(['-', '+']);
+        return body == ".";
+    }
+
+    !patterns[7].is_match(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn golden(json: &str, yaml: &str) {
+        let data: Value = serde_json::from_str(json).unwrap();
+        assert_eq!(dump(&data).unwrap(), yaml);
+    }
+
+    #[test]
+    fn test_golden_basic() {
+        golden(
+            include_str!("../../spec/fixtures/yaml/basic.json"),
+            include_str!("../../spec/fixtures/yaml/basic.yml"),
+        );
+    }
+
+    #[test]
+    fn test_golden_nested() {
+        golden(
+            include_str!("../../spec/fixtures/yaml/nested.json"),
+            include_str!("../../spec/fixtures/yaml/nested.yml"),
+        );
+    }
+
+    #[test]
+    fn test_golden_quoting() {
+        golden(
+            include_str!("../../spec/fixtures/yaml/quoting.json"),
+            include_str!("../../spec/fixtures/yaml/quoting.yml"),
+        );
+    }
+
+    #[test]
+    fn test_golden_multiline() {
+        golden(
+            include_str!("../../spec/fixtures/yaml/multiline.json"),
+            include_str!("../../spec/fixtures/yaml/multiline.yml"),
+        );
+    }
+
+    #[test]
+    fn test_golden_sequences() {
+        golden(
+            include_str!("../../spec/fixtures/yaml/sequences.json"),
+            include_str!("../../spec/fixtures/yaml/sequences.yml"),
+        );
+    }
+
+    #[test]
+    fn test_golden_long_lines() {
+        golden(
+            include_str!("../../spec/fixtures/yaml/long_lines.json"),
+            include_str!("../../spec/fixtures/yaml/long_lines.yml"),
+        );
+    }
+
+    #[test]
+    fn test_root_scalars_and_empty_collections() {
+        assert_eq!(dump(&json!("foo")).unwrap(), "--- foo\n");
+        assert_eq!(dump(&json!(1)).unwrap(), "--- 1\n");
+        assert_eq!(dump(&json!({})).unwrap(), "--- {}\n");
+        assert_eq!(dump(&json!([])).unwrap(), "--- []\n");
+        assert_eq!(dump(&json!("a\nb")).unwrap(), "--- |-\n  a\n  b\n");
+        assert_eq!(
+            dump(&json!("a\nb\n\n")).unwrap(),
+            "--- |+\n  a\n  b\n\n...\n"
+        );
+    }
+
+    #[test]
+    fn test_symbolize_keys() {
+        let options = PsychOptions {
+            symbolize_keys: true,
+        };
+        let data = json!({"config": {"host": "localhost", "port": 3000}});
+        assert_eq!(
+            dump_with(&data, options).unwrap(),
+            "---\n:config:\n  :host: localhost\n  :port: 3000\n"
+        );
+        assert_eq!(
+            dump_with(&json!({"a: b": 1}), options).unwrap(),
+            "---\n! ':a: b': 1\n"
+        );
+    }
+
+    #[test]
+    fn test_complex_keys() {
+        assert_eq!(
+            dump(&json!({"a\nb": [1]})).unwrap(),
+            "---\n? |-\n  a\n  b\n: - 1\n"
+        );
+        let long_key = "k".repeat(129);
+        assert_eq!(
+            dump(&json!({ long_key.clone(): 1 })).unwrap(),
+            format!("---\n? {}\n: 1\n", long_key)
+        );
+    }
+
+    #[test]
+    fn test_tokenizes_as_string() {
+        for s in ["hello", "1.0.0", "None", "nope", "yesterday", "-", "a: b"] {
+            assert!(tokenizes_as_string(s), "{s:?} should stay a string");
+        }
+        for s in [
+            "",
+            "true",
+            "No",
+            "~",
+            "null",
+            "42",
+            "-1_000",
+            "1,000",
+            "0x1F",
+            "1.5",
+            ".inf",
+            "2025-01-15",
+            "12:30",
+            ":sym",
+        ] {
+            assert!(!tokenizes_as_string(s), "{s:?} should not stay a string");
+        }
+        assert!(tokenizes_as_string("2025-02-30"));
+        assert!(tokenizes_as_string("1e3"));
+        assert!(!tokenizes_as_string("true\n"));
+    }
+}
@@ -0,0 +1,248 @@
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use std::fmt::Write;
+
+/// Mirrors the generator options of Ruby's `JSON.generate` that affect the
+/// emitted bytes. The defaults match Ruby's defaults.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RubyJsonOptions {
+    /// Escape every non-ASCII character as `\uXXXX` (`ascii_only: true`).
+    pub ascii_only: bool,
+    /// Escape `/`, U+2028 and U+2029 (`script_safe: true`).
+    pub script_safe: bool,
+}
+
+/// Serializes `data` exactly like Ruby's `JSON.generate(data)`.
+pub fn generate<T: Serialize + ?Sized>(data: &T) -> Result<String> {
+    generate_with(data, false, RubyJsonOptions::default())
+}
+
+/// Serializes `data` exactly like Ruby's `JSON.pretty_generate(data)`.
+pub fn pretty_generate<T: Serialize + ?Sized>(data: &T) -> Result<String> {
+    generate_with(data, true, RubyJsonOptions::default())
+}
+
+pub fn generate_with<T: Serialize + ?Sized>(
+    data: &T,
+    pretty: bool,
+    options: RubyJsonOptions,
+) -> Result<String> {
+    let value = serde_json::to_value(data)?;
+    let mut out = String::new();
+    Generator { pretty, options }.write_value(&mut out, &value, 0);
+    Ok(out)
+}
+
+struct Generator {
+    pretty: bool,
+    options: RubyJsonOptions,
+}
+
+impl Generator {
+    fn write_value(&self, out: &mut String, value: &Value, depth: usize) {
+        match value {
+            Value::Null => out.push_str("null"),
+            Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+            Value::Number(n) => out.push_str(&format_number(n)),
+            Value::String(s) => self.write_string(out, s),
+            Value::Array(items) => self.write_array(out, items, depth),
+            Value::Object(map) => self.write_object(out, map, depth),
+        }
+    }
+
+    fn write_array(&self, out: &mut String, items: &[Value], depth: usize) {
+        if items.is_empty() {
+            out.push_str("[]");
+            return;
+        }
+
+        out.push('[');
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            self.newline(out, depth + 1);
+            self.write_value(out, item, depth + 1);
+        }
+        self.newline(out, depth);
+        out.push(']');
+    }
+
+    fn write_object(&self, out: &mut String, map: &Map<String, Value>, depth: usize) {
+        if map.is_empty() {
+            out.push_str("{}");
+            return;
+        }
+
+        out.push('{');
+        for (i, (key, value)) in map.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            self.newline(out, depth + 1);
+            self.write_string(out, key);
+            out.push(':');
+            if self.pretty {
+                out.push(' ');
+            }
+            self.write_value(out, value, depth + 1);
+        }
+        self.newline(out, depth);
+        out.push('}');
+    }
+
+    fn newline(&self, out: &mut String, depth: usize) {
+        if self.pretty {
+            out.push('\n');
+            out.push_str(&"  ".repeat(depth));
+        }
+    }
+
+    fn write_string(&self, out: &mut String, s: &str) {
+        out.push('"');
+        for c in s.chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                '\u{8}' => out.push_str("\\b"),
+                '\u{c}' => out.push_str("\\f"),
+                '/' if self.options.script_safe => out.push_str("\\/"),
+                '\u{2028}' | '\u{2029}' if self.options.script_safe => {
+                    let _ = write!(out, "\\u{:04x}", c as u32);
+                }
+                c if (c as u32) < 0x20 => {
+                    let _ = write!(out, "\\u{:04x}", c as u32);
+                }
+                c if self.options.ascii_only && !c.is_ascii() => {
+                    let mut units = [0u16; 2];
+                    for unit in c.encode_utf16(&mut units) {
+                        let _ = write!(out, "\\u{:04x}", unit);
+                    }
+                }
+                c => out.push(c),
+            }
+        }
+        out.push('"');
+    }
+}
+
+fn format_number(n: &Number) -> String {
+    match n.as_f64() {
+        Some(f) if n.is_f64() => format_float(f),
+        _ => n.to_string(),
+    }
+}
+
+/// Formats a float the way Ruby's `Float#to_s` does: shortest round-trip
+/// digits, fixed notation for decimal exponents in `-4..16`, and
+/// `d.ddde+XX` scientific notation otherwise.
+pub fn format_float(f: f64) -> String {
+    if f.is_nan() {
+        return "NaN".to_string();
+    }
+    if f.is_infinite() {
+        return if f > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+    if f == 0.0 {
+        return if f.is_sign_negative() { "-0.0" } else { "0.0" }.to_string();
+    }
+
+    // `{:e}` yields the shortest round-trip digits, e.g. "-1.2345e-7".
+    let scientific = format!("{:e}", f);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", mantissa),
+    };
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let decpt = exponent + 1;
+
+    let body = if decpt > 0 && decpt <= 16 {
+        let decpt = decpt as usize;
+        if digits.len() <= decpt {
+            format!("{}{}.0", digits, "0".repeat(decpt - digits.len()))
+        } else {
+            format!("{}.{}", &digits[..decpt], &digits[decpt..])
+        }
+    } else if decpt <= 0 && decpt > -4 {
+        format!("0.{}{}", "0".repeat((-decpt) as usize), digits)
+    } else {
+        let fraction = if digits.len() > 1 { &digits[1..] } else { "0" };
+        format!("{}.{}e{:+03}", &digits[..1], fraction, exponent)
+    };
+
+    format!("{}{}", sign, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn test_generate_compact() {
+        let data = json!({"b": 1, "a": [1, 2.5, null, true], "c": {}});
+        assert_eq!(
+            generate(&data).unwrap(),
+            r#"{"b":1,"a":[1,2.5,null,true],"c":{}}"#
+        );
+    }
+
+    #[test]
+    fn test_pretty_generate() {
+        let data = json!({"name": "x", "list": [1, {"k": []}], "empty": {}});
+        let expected = "{\n  \"name\": \"x\",\n  \"list\": [\n    1,\n    {\n      \"k\": []\n    }\n  ],\n  \"empty\": {}\n}";
+        assert_eq!(pretty_generate(&data).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_preserves_key_order() {
+        let data: Value = serde_json::from_str(r#"{"z": 1, "a": 2, "m": 3}"#).unwrap();
+        assert_eq!(generate(&data).unwrap(), r#"{"z":1,"a":2,"m":3}"#);
+    }
+
+    #[test]
+    fn test_float_formatting() {
+        assert_eq!(format_float(1.0), "1.0");
+        assert_eq!(format_float(-2.5), "-2.5");
+        assert_eq!(format_float(100.0), "100.0");
+        assert_eq!(format_float(0.1), "0.1");
+        assert_eq!(format_float(0.0001), "0.0001");
+        assert_eq!(format_float(0.00001), "1.0e-05");
+        assert_eq!(format_float(1.5e-7), "1.5e-07");
+        assert_eq!(format_float(1e15), "1000000000000000.0");
+        assert_eq!(format_float(1e16), "1.0e+16");
+        assert_eq!(format_float(1.2345e20), "1.2345e+20");
+        assert_eq!(format_float(1e100), "1.0e+100");
+        assert_eq!(format_float(123456.789), "123456.789");
+        assert_eq!(format_float(0.0), "0.0");
+    }
+
+    #[test]
+    fn test_string_escaping_defaults() {
+        let data = json!("a/b \"q\" \\ é 日 \u{1f} \u{2028}\n");
+        assert_eq!(
+            generate(&data).unwrap(),
+            "\"a/b \\\"q\\\" \\\\ é 日 \\u001f \u{2028}\\n\""
+        );
+    }
+
+    #[test]
+    fn test_ascii_only_and_script_safe() {
+        let options = RubyJsonOptions {
+            ascii_only: true,
+            script_safe: true,
+        };
+        let data = json!("</script> é 😀 \u{2028}");
+        assert_eq!(
+            generate_with(&data, false, options).unwrap(),
+            r#""<\/script> \u00e9 \ud83d\ude00 \u2028""#
+        );
+    }
+}
@@ -0,0 +1,207 @@
+require 'cgi'
+
+module BasicCli
+  module Utils
+    # Renders rows as a width-aware terminal table, Markdown or HTML. Mirrors
+    # the Rust `utils::table` module so both binaries print the same layout.
+    class Table
+      DEFAULT_MAX_CELL_WIDTH = 40
+      MIN_COLUMN_WIDTH = 3
+
+      # Zero-width marks and East Asian wide characters, the common cases of
+      # Unicode display width.
+      ZERO_WIDTH = /[\p{Mn}\p{Me}\p{Cf}]/.freeze
+      WIDE = /[\u1100-\u115F\u2E80-\u303E\u3041-\u33FF\u3400-\u4DBF\u4E00-\u9FFF\uA000-\uA4CF\uAC00-\uD7A3\uF900-\uFAFF\uFE30-\uFE4F\uFF00-\uFF60\uFFE0-\uFFE6\u{1F300}-\u{1F64F}\u{1F900}-\u{1F9FF}\u{20000}-\u{3FFFD}]/.freeze
+
+      def initialize(headers, title: nil, alignments: {}, max_cell_width: DEFAULT_MAX_CELL_WIDTH,
+                     max_width: self.class.terminal_width)
+        @headers = headers.map(&:to_s)
+        @title = title
+        @alignments = alignments
+        @max_cell_width = [max_cell_width, MIN_COLUMN_WIDTH].max
+        @max_width = max_width
+        @rows = []
+      end
+
+      def add_row(row)
+        @rows << row.map(&:to_s)
+        self
+      end
+
+      def render(format)
+        case format.to_s
+        when 'console' then render_console
+        when 'markdown' then render_markdown
+        when 'html' then render_html
+        else raise ArgumentError, "Unsupported table format: #{format}"
+        end
+      end
+
+      def render_console
+        widths = column_widths
+        return '' if widths.empty?
+
+        inner_width = widths.sum + (3 * (widths.size - 1))
+        lines = [border('┌', '┬', '┐', widths, solid: !@title.nil?)]
+        if @title
+          lines << "│ #{self.class.pad(self.class.truncate(@title, inner_width), inner_width, :center)} │"
+          lines << border('├', '┬', '┤', widths)
+        end
+
+        if @headers.any? { |h| !h.empty? }
+          lines << console_row(@headers, widths, header: true)
+          lines << border('├', '┼', '┤', widths)
+        end
+
+        @rows.each { |row| lines << console_row(row, widths) }
+        lines << border('└', '┴', '┘', widths)
+        lines.join("\n")
+      end
+
+      def render_markdown
+        columns = column_count
+        lines = []
+        if @title
+          lines << "**#{escape_markdown(@title)}**"
+          lines << ''
+        end
+
+        lines << "| #{Array.new(columns) { |i| escape_markdown(@headers[i] || '') }.join(' | ')} |"
+        separators = Array.new(columns) do |i|
+          { left: '---', right: '---:', center: ':---:' }.fetch(alignment(i))
+        end
+        lines << "| #{separators.join(' | ')} |"
+        @rows.each do |row|
+          lines << "| #{Array.new(columns) { |i| escape_markdown(row[i] || '') }.join(' | ')} |"
+        end
+        lines.join("\n")
+      end
+
+      def render_html
+        columns = column_count
+        lines = ['<table>']
+        lines << "  <caption>#{CGI.escapeHTML(@title)}</caption>" if @title
+
+        if @headers.any? { |h| !h.empty? }
+          lines.push('  <thead>', '    <tr>')
+          columns.times { |i| lines << "      <th>#{CGI.escapeHTML(@headers[i] || '')}</th>" }
+          lines.push('    </tr>', '  </thead>')
+        end
+
+        lines << '  <tbody>'
+        @rows.each do |row|
+          lines << '    <tr>'
+          columns.times do |i|
+            style = { left: '', right: ' style="text-align: right"',
+                      center: ' style="text-align: center"' }.fetch(alignment(i))
+            lines << "      <td#{style}>#{CGI.escapeHTML(row[i] || '')}</td>"
+          end
+          lines << '    </tr>'
+        end
+        lines.push('  </tbody>', '</table>')
+        lines.join("\n")
+      end
+
+      def self.terminal_width
+        columns = ENV['COLUMNS'].to_i
+        columns.positive? ? columns : nil
+      end
+
+      def self.char_width(char)
+        return 0 if char.match?(ZERO_WIDTH)
+
+        char.match?(WIDE) ? 2 : 1
+      end
+
+      def self.display_width(text)
+        text.each_char.sum { |c| char_width(c) }
+      end
+
+      # Cuts `text` down to at most `width` display columns, marking the cut
+      # with an ellipsis.
+      def self.truncate(text, width)
+        text = text.tr("\n\r\t", '   ')
+        return text if display_width(text) <= width
+
+        result = +''
+        used = 0
+        text.each_char do |c|
+          w = char_width(c)
+          break if used + w + 1 > width
+
+          result << c
+          used += w
+        end
+        "#{result}…"
+      end
+
+      def self.pad(text, width, alignment)
+        fill = [width - display_width(text), 0].max
+        case alignment
+        when :right then (' ' * fill) + text
+        when :center
+          left = fill / 2
+          (' ' * left) + text + (' ' * (fill - left))
+        else text + (' ' * fill)
+        end
+      end
+
+      private
+
+      def console_row(row, widths, header: false)
+        cells = widths.each_with_index.map do |width, i|
+          text = self.class.truncate(row[i] || '', width)
+          self.class.pad(text, width, header ? :left : alignment(i))
+        end
+        "│ #{cells.join(' │ ')} │"
+      end
+
+      def column_count
+        ([@headers.size] + @rows.map(&:size)).max
+      end
+
+      def alignment(column)
+        @alignments.fetch(column, :left)
+      end
+
+      def column_widths
+        columns = column_count
+        widths = Array.new(columns) do |i|
+          ([@headers] + @rows).filter_map { |row| row[i] }
+                              .map { |cell| self.class.display_width(cell) }
+                              .max.to_i.clamp(1, @max_cell_width)
+        end
+
+        if @max_width
+          # Shrink the widest column one step at a time until the table fits.
+          overhead = 4 + (3 * [columns - 1, 0].max)
+          while widths.sum + overhead > @max_width
+            widest = widths.max
+            break if widest.nil? || widest <= MIN_COLUMN_WIDTH
+
+            widths[widths.rindex(widest)] -= 1
+          end
+        end
+
+        # A title wider than the columns widens the last column.
+        if @title && !widths.empty?
+          inner = widths.sum + (3 * (widths.size - 1))
+          wanted = self.class.display_width(@title)
+          wanted = [wanted, [@max_width - 4, 0].max].min if @max_width
+          widths[-1] += wanted - inner if wanted > inner
+        end
+
+        widths
+      end
+
+      def border(left, joint, right, widths, solid: false)
+        joint = '─' if solid
+        "#{left}#{widths.map { |w| '─' * (w + 2) }.join(joint)}#{right}"
+      end
+
+      def escape_markdown(text)
+        text.gsub('|', '\\|').gsub("\n", '<br>')
+      end
+    end
+  end
+end
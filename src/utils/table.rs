@@ -0,0 +1,434 @@
+use anyhow::{bail, Result};
+use std::io::IsTerminal;
+use std::str::FromStr;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+const DEFAULT_MAX_CELL_WIDTH: usize = 40;
+const MIN_COLUMN_WIDTH: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    Console,
+    Markdown,
+    Html,
+}
+
+impl FromStr for TableFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "console" | "table" => Ok(TableFormat::Console),
+            "markdown" | "md" => Ok(TableFormat::Markdown),
+            "html" => Ok(TableFormat::Html),
+            _ => bail!("Unsupported table format: {}", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    Right,
+    Center,
+}
+
+#[derive(Debug, Clone)]
+pub struct Table {
+    title: Option<String>,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    alignments: Vec<Alignment>,
+    max_cell_width: usize,
+    max_width: Option<usize>,
+}
+
+#[allow(dead_code)]
+impl Table {
+    pub fn new<S: Into<String>>(headers: Vec<S>) -> Self {
+        let headers: Vec<String> = headers.into_iter().map(Into::into).collect();
+        Self {
+            title: None,
+            alignments: vec![Alignment::Left; headers.len()],
+            headers,
+            rows: Vec::new(),
+            max_cell_width: DEFAULT_MAX_CELL_WIDTH,
+            max_width: terminal_width(),
+        }
+    }
+
+    pub fn with_title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_alignment(mut self, column: usize, alignment: Alignment) -> Self {
+        if column >= self.alignments.len() {
+            self.alignments.resize(column + 1, Alignment::Left);
+        }
+        self.alignments[column] = alignment;
+        self
+    }
+
+    pub fn with_max_cell_width(mut self, width: usize) -> Self {
+        self.max_cell_width = width.max(MIN_COLUMN_WIDTH);
+        self
+    }
+
+    pub fn with_max_width(mut self, width: Option<usize>) -> Self {
+        self.max_width = width;
+        self
+    }
+
+    pub fn add_row<S: Into<String>>(&mut self, row: Vec<S>) {
+        self.rows.push(row.into_iter().map(Into::into).collect());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn render(&self, format: TableFormat) -> String {
+        match format {
+            TableFormat::Console => self.render_console(),
+            TableFormat::Markdown => self.render_markdown(),
+            TableFormat::Html => self.render_html(),
+        }
+    }
+
+    pub fn render_console(&self) -> String {
+        let widths = self.column_widths();
+        if widths.is_empty() {
+            return String::new();
+        }
+
+        let inner_width: usize = widths.iter().sum::<usize>() + 3 * (widths.len() - 1);
+        let mut lines = Vec::new();
+
+        lines.push(border('┌', '┬', '┐', &widths, self.title.is_some()));
+        if let Some(title) = &self.title {
+            let title = truncate(title, inner_width);
+            lines.push(format!(
+                "│ {} │",
+                pad(&title, inner_width, Alignment::Center)
+            ));
+            lines.push(border('├', '┬', '┤', &widths, false));
+        }
+
+        if self.headers.iter().any(|h| !h.is_empty()) {
+            lines.push(self.console_row(&self.headers, &widths, true));
+            lines.push(border('├', '┼', '┤', &widths, false));
+        }
+
+        for row in &self.rows {
+            lines.push(self.console_row(row, &widths, false));
+        }
+
+        lines.push(border('└', '┴', '┘', &widths, false));
+        lines.join("\n")
+    }
+
+    pub fn render_markdown(&self) -> String {
+        let columns = self.column_count();
+        let mut lines = Vec::new();
+
+        if let Some(title) = &self.title {
+            lines.push(format!("**{}**", escape_markdown(title)));
+            lines.push(String::new());
+        }
+
+        let header: Vec<String> = (0..columns)
+            .map(|i| escape_markdown(self.headers.get(i).map(String::as_str).unwrap_or("")))
+            .collect();
+        lines.push(format!("| {} |", header.join(" | ")));
+
+        let separator: Vec<&str> = (0..columns)
+            .map(|i| match self.alignment(i) {
+                Alignment::Left => "---",
+                Alignment::Right => "---:",
+                Alignment::Center => ":---:",
+            })
+            .collect();
+        lines.push(format!("| {} |", separator.join(" | ")));
+
+        for row in &self.rows {
+            let cells: Vec<String> = (0..columns)
+                .map(|i| escape_markdown(row.get(i).map(String::as_str).unwrap_or("")))
+                .collect();
+            lines.push(format!("| {} |", cells.join(" | ")));
+        }
+
+        lines.join("\n")
+    }
+
+    pub fn render_html(&self) -> String {
+        let columns = self.column_count();
+        let mut lines = vec!["<table>".to_string()];
+
+        if let Some(title) = &self.title {
+            lines.push(format!("  <caption>{}</caption>", escape_html(title)));
+        }
+
+        if self.headers.iter().any(|h| !h.is_empty()) {
+            lines.push("  <thead>".to_string());
+            lines.push("    <tr>".to_string());
+            for i in 0..columns {
+                let header = self.headers.get(i).map(String::as_str).unwrap_or("");
+                lines.push(format!("      <th>{}</th>", escape_html(header)));
+            }
+            lines.push("    </tr>".to_string());
+            lines.push("  </thead>".to_string());
+        }
+
+        lines.push("  <tbody>".to_string());
+        for row in &self.rows {
+            lines.push("    <tr>".to_string());
+            for i in 0..columns {
+                let cell = row.get(i).map(String::as_str).unwrap_or("");
+                let style = match self.alignment(i) {
+                    Alignment::Left => "",
+                    Alignment::Right => " style=\"text-align: right\"",
+                    Alignment::Center => " style=\"text-align: center\"",
+                };
+                lines.push(format!("      <td{}>{}</td>", style, escape_html(cell)));
+            }
+            lines.push("    </tr>".to_string());
+        }
+        lines.push("  </tbody>".to_string());
+        lines.push("</table>".to_string());
+
+        lines.join("\n")
+    }
+
+    fn console_row(&self, row: &[String], widths: &[usize], header: bool) -> String {
+        let cells: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(i, &width)| {
+                let text = truncate(row.get(i).map(String::as_str).unwrap_or(""), width);
+                let alignment = if header {
+                    Alignment::Left
+                } else {
+                    self.alignment(i)
+                };
+                pad(&text, width, alignment)
+            })
+            .collect();
+
+        format!("│ {} │", cells.join(" │ "))
+    }
+
+    fn column_count(&self) -> usize {
+        self.rows
+            .iter()
+            .map(Vec::len)
+            .chain(std::iter::once(self.headers.len()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn alignment(&self, column: usize) -> Alignment {
+        self.alignments
+            .get(column)
+            .copied()
+            .unwrap_or(Alignment::Left)
+    }
+
+    fn column_widths(&self) -> Vec<usize> {
+        let columns = self.column_count();
+        let mut widths: Vec<usize> = (0..columns)
+            .map(|i| {
+                std::iter::once(&self.headers)
+                    .chain(self.rows.iter())
+                    .filter_map(|row| row.get(i))
+                    .map(|cell| display_width(cell))
+                    .max()
+                    .unwrap_or(0)
+                    .clamp(1, self.max_cell_width)
+            })
+            .collect();
+
+        if let Some(max_width) = self.max_width {
+            // Shrink the widest column one step at a time until the table fits.
+            let overhead = 4 + 3 * columns.saturating_sub(1);
+            while widths.iter().sum::<usize>() + overhead > max_width {
+                let Some((index, &widest)) = widths.iter().enumerate().max_by_key(|(_, w)| **w)
+                else {
+                    break;
+                };
+                if widest <= MIN_COLUMN_WIDTH {
+                    break;
+                }
+                widths[index] -= 1;
+            }
+        }
+
+        // A title wider than the columns widens the last column.
+        if let (Some(title), Some(last)) = (&self.title, widths.len().checked_sub(1)) {
+            let inner: usize = widths.iter().sum::<usize>() + 3 * last;
+            let wanted =
+                display_width(title).min(self.max_width.unwrap_or(usize::MAX).saturating_sub(4));
+            if wanted > inner {
+                widths[last] += wanted - inner;
+            }
+        }
+
+        widths
+    }
+}
+
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// Cuts `text` down to at most `width` display columns, marking the cut
+/// with an ellipsis.
+pub fn truncate(text: &str, width: usize) -> String {
+    let text = text.replace(['\n', '\r', '\t'], " ");
+    if display_width(&text) <= width {
+        return text;
+    }
+
+    let mut result = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w + 1 > width {
+            break;
+        }
+        result.push(c);
+        used += w;
+    }
+    result.push('…');
+    result
+}
+
+pub fn pad(text: &str, width: usize, alignment: Alignment) -> String {
+    let fill = width.saturating_sub(display_width(text));
+    match alignment {
+        Alignment::Left => format!("{}{}", text, " ".repeat(fill)),
+        Alignment::Right => format!("{}{}", " ".repeat(fill), text),
+        Alignment::Center => {
+            let left = fill / 2;
+            format!("{}{}{}", " ".repeat(left), text, " ".repeat(fill - left))
+        }
+    }
+}
+
+fn border(left: char, joint: char, right: char, widths: &[usize], solid: bool) -> String {
+    let joint = if solid { '─' } else { joint };
+    let segments: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+    format!("{}{}{}", left, segments.join(&joint.to_string()), right)
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+pub fn terminal_width() -> Option<usize> {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse().ok())
+        .filter(|&c: &usize| c > 0)
+}
+
+/// Resolves a `--color` value: `always`, `never`, or `auto` (color when
+/// stdout is a terminal and `NO_COLOR` is unset).
+pub fn color_enabled(mode: &str) -> bool {
+    match mode {
+        "always" => true,
+        "never" => false,
+        _ => std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_console_alignment() {
+        let mut table = Table::new(vec!["Name", "Count"])
+            .with_alignment(1, Alignment::Right)
+            .with_max_width(None);
+        table.add_row(vec!["alpha", "1"]);
+        table.add_row(vec!["beta", "200"]);
+
+        let output = table.render_console();
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines[0], "┌───────┬───────┐");
+        assert_eq!(lines[1], "│ Name  │ Count │");
+        assert_eq!(lines[3], "│ alpha │     1 │");
+        assert_eq!(lines[4], "│ beta  │   200 │");
+        assert!(lines
+            .iter()
+            .all(|l| display_width(l) == display_width(lines[0])));
+    }
+
+    #[test]
+    fn test_unicode_width() {
+        let mut table = Table::new(vec!["Word"]).with_max_width(None);
+        table.add_row(vec!["日本語"]);
+        table.add_row(vec!["abc"]);
+
+        let output = table.render_console();
+        let widths: Vec<usize> = output.lines().map(display_width).collect();
+        assert!(widths.iter().all(|w| *w == widths[0]));
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("hello", 10), "hello");
+        assert_eq!(truncate("hello world", 6), "hello…");
+        assert_eq!(display_width(&truncate("日本語日本語", 5)), 5);
+    }
+
+    #[test]
+    fn test_max_width_shrinks_widest_column() {
+        let mut table = Table::new(vec!["a", "b"]).with_max_width(Some(20));
+        table.add_row(vec!["x".repeat(30), "y".to_string()]);
+
+        let output = table.render_console();
+        assert!(output.lines().all(|l| display_width(l) <= 20));
+        assert!(output.contains('…'));
+    }
+
+    #[test]
+    fn test_tiny_terminal_with_title() {
+        let mut table = Table::new(vec!["a"])
+            .with_title("Title")
+            .with_max_width(Some(3));
+        table.add_row(vec!["value"]);
+        assert!(table.render_console().contains("va…"));
+    }
+
+    #[test]
+    fn test_html_escaping() {
+        let mut table = Table::new(vec!["<h>"]).with_title("T & T");
+        table.add_row(vec!["a\"b"]);
+
+        let html = table.render_html();
+        assert!(html.contains("<caption>T &amp; T</caption>"));
+        assert!(html.contains("<th>&lt;h&gt;</th>"));
+        assert!(html.contains("<td>a&quot;b</td>"));
+    }
+
+    #[test]
+    fn test_table_format_from_str() {
+        assert_eq!(
+            "markdown".parse::<TableFormat>().unwrap(),
+            TableFormat::Markdown
+        );
+        assert_eq!("HTML".parse::<TableFormat>().unwrap(), TableFormat::Html);
+        assert!("xml".parse::<TableFormat>().is_err());
+    }
+}
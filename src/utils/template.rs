@@ -0,0 +1,1111 @@
+use super::byte_size;
+use super::ruby_json;
+use super::table::{display_width, pad, truncate, Alignment};
+use anyhow::{anyhow, bail, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Utc};
+use serde_json::{Number, Value};
+use std::collections::HashMap;
+use std::fs;
+
+const FILTERS: &[&str] = &[
+    "upper",
+    "lower",
+    "capitalize",
+    "trim",
+    "length",
+    "default",
+    "join",
+    "first",
+    "last",
+    "reverse",
+    "sort",
+    "keys",
+    "values",
+    "replace",
+    "round",
+    "fixed",
+    "bytes",
+    "ljust",
+    "rjust",
+    "truncate",
+    "json",
+    "date",
+];
+
+/// A small, logic-light template language for formatting command output:
+///
+/// - `{{ path.to.value | filter | filter(arg) }}` prints a value
+/// - `{% if expr %}…{% elif expr %}…{% else %}…{% endif %}`
+/// - `{% for item in list %}…{% else %}…{% endfor %}`, with `loop.index`,
+///   `loop.index0`, `loop.first`, `loop.last` and `loop.length`; objects
+///   iterate as `{% for key, value in object %}`
+/// - `{# comment #}`
+///
+/// A newline directly after a `{% %}` tag or comment is dropped, and `{{-`,
+/// `-}}`, `{%-`, `-%}` trim whitespace on that side of a tag. Undefined
+/// values render as empty strings (use `default` to substitute one).
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Output(Expr),
+    If {
+        branches: Vec<(Expr, Vec<Node>)>,
+        otherwise: Vec<Node>,
+    },
+    For {
+        key: Option<String>,
+        value: String,
+        iterable: Expr,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Value),
+    Path(String, Vec<Expr>),
+    Filter(Box<Expr>, String, Vec<Expr>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, &'static str, Box<Expr>),
+}
+
+#[allow(dead_code)]
+impl Template {
+    pub fn parse(source: &str) -> Result<Self> {
+        let segments = split_segments(source)?;
+        let mut parser = BlockParser {
+            segments,
+            position: 0,
+        };
+        let (nodes, end) = parser.parse_until(&[])?;
+        if let Some((tag, line)) = end {
+            bail!(
+                "Template error at line {}: unexpected {{% {} %}}",
+                line,
+                tag
+            );
+        }
+        Ok(Self { nodes })
+    }
+
+    /// Parses a `--template` argument: either the template itself, or
+    /// `@path` to read it from a file.
+    pub fn load(argument: &str) -> Result<Self> {
+        match argument.strip_prefix('@') {
+            Some(path) => {
+                let source = fs::read_to_string(path)
+                    .map_err(|e| anyhow!("Failed to read template {}: {}", path, e))?;
+                Self::parse(&source)
+            }
+            None => Self::parse(argument),
+        }
+    }
+
+    pub fn render(&self, context: &Value) -> Result<String> {
+        let mut renderer = Renderer {
+            root: context,
+            scopes: Vec::new(),
+        };
+        let mut out = String::new();
+        renderer.render_nodes(&self.nodes, &mut out)?;
+        Ok(out)
+    }
+}
+
+enum Segment {
+    Text(String),
+    Output(String, usize),
+    Tag(String, usize),
+}
+
+/// Splits the source into text and tags, applying the whitespace rules.
+fn split_segments(source: &str) -> Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+    let mut rest = source;
+    let mut line = 1;
+    let mut trim_next = false;
+    let mut drop_newline = false;
+
+    loop {
+        let next = ["{{", "{%", "{#"]
+            .iter()
+            .filter_map(|open| rest.find(open))
+            .min();
+
+        let text_end = next.unwrap_or(rest.len());
+        let mut text = &rest[..text_end];
+        if trim_next {
+            text = text.trim_start();
+        } else if drop_newline {
+            text = text
+                .strip_prefix("\r\n")
+                .or_else(|| text.strip_prefix('\n'))
+                .unwrap_or(text);
+        }
+
+        let Some(start) = next else {
+            push_text(&mut segments, text);
+            break;
+        };
+
+        let opener = &rest[start..start + 2];
+        let closer = match opener {
+            "{{" => "}}",
+            "{%" => "%}",
+            _ => "#}",
+        };
+        let after_open = &rest[start + 2..];
+        let Some(close) = after_open.find(closer) else {
+            bail!("Template error at line {}: unclosed {}", line, opener);
+        };
+
+        let mut inner = &after_open[..close];
+        if let Some(stripped) = inner.strip_prefix('-') {
+            text = text.trim_end();
+            inner = stripped;
+        }
+        trim_next = false;
+        if let Some(stripped) = inner.strip_suffix('-') {
+            trim_next = true;
+            inner = stripped;
+        }
+        push_text(&mut segments, text);
+
+        line += rest[..start].matches('\n').count();
+        match opener {
+            "{{" => segments.push(Segment::Output(inner.trim().to_string(), line)),
+            "{%" => segments.push(Segment::Tag(inner.trim().to_string(), line)),
+            _ => {}
+        }
+        drop_newline = opener != "{{";
+        line += inner.matches('\n').count();
+
+        rest = &after_open[close + 2..];
+    }
+
+    Ok(segments)
+}
+
+fn push_text(segments: &mut Vec<Segment>, text: &str) {
+    if !text.is_empty() {
+        segments.push(Segment::Text(text.to_string()));
+    }
+}
+
+/// The tag that ended a block, with its line number.
+type EndTag = (String, usize);
+
+struct BlockParser {
+    segments: Vec<Segment>,
+    position: usize,
+}
+
+impl BlockParser {
+    /// Parses nodes until one of the `terminators` tags, returning that tag
+    /// (with its line) or `None` at the end of input.
+    fn parse_until(&mut self, terminators: &[&str]) -> Result<(Vec<Node>, Option<EndTag>)> {
+        let mut nodes = Vec::new();
+
+        while self.position < self.segments.len() {
+            let index = self.position;
+            self.position += 1;
+
+            match &self.segments[index] {
+                Segment::Text(text) => nodes.push(Node::Text(text.clone())),
+                Segment::Output(source, line) => {
+                    nodes.push(Node::Output(parse_expression(source, *line)?));
+                }
+                Segment::Tag(source, line) => {
+                    let (source, line) = (source.clone(), *line);
+                    let keyword = source.split_whitespace().next().unwrap_or("");
+                    if terminators.contains(&keyword) {
+                        return Ok((nodes, Some((source, line))));
+                    }
+                    nodes.push(match keyword {
+                        "if" => self.parse_if(&source, line)?,
+                        "for" => self.parse_for(&source, line)?,
+                        "" => bail!("Template error at line {}: empty tag", line),
+                        _ => bail!(
+                            "Template error at line {}: unexpected {{% {} %}}",
+                            line,
+                            source
+                        ),
+                    });
+                }
+            }
+        }
+
+        Ok((nodes, None))
+    }
+
+    fn parse_if(&mut self, source: &str, line: usize) -> Result<Node> {
+        let mut branches = Vec::new();
+        let mut condition = parse_expression(&source[2..], line)?;
+
+        loop {
+            let (body, end) = self.parse_until(&["elif", "else", "endif"])?;
+            let Some((tag, tag_line)) = end else {
+                bail!("Template error at line {}: missing {{% endif %}}", line);
+            };
+            branches.push((condition, body));
+
+            match tag.split_whitespace().next() {
+                Some("elif") => condition = parse_expression(&tag[4..], tag_line)?,
+                Some("else") => {
+                    let (otherwise, end) = self.parse_until(&["endif"])?;
+                    if end.is_none() {
+                        bail!("Template error at line {}: missing {{% endif %}}", line);
+                    }
+                    return Ok(Node::If {
+                        branches,
+                        otherwise,
+                    });
+                }
+                _ => {
+                    return Ok(Node::If {
+                        branches,
+                        otherwise: Vec::new(),
+                    })
+                }
+            }
+        }
+    }
+
+    fn parse_for(&mut self, source: &str, line: usize) -> Result<Node> {
+        let invalid = || {
+            anyhow!(
+                "Template error at line {}: expected {{% for x in list %}}",
+                line
+            )
+        };
+
+        let spec = source[3..].trim();
+        let (names, iterable) = spec.split_once(" in ").ok_or_else(invalid)?;
+        let names: Vec<&str> = names.split(',').map(str::trim).collect();
+        if !names.iter().all(|n| is_identifier(n)) {
+            return Err(invalid());
+        }
+        let (key, value) = match names.as_slice() {
+            [value] => (None, value.to_string()),
+            [key, value] => (Some(key.to_string()), value.to_string()),
+            _ => return Err(invalid()),
+        };
+        let iterable = parse_expression(iterable, line)?;
+
+        let (body, end) = self.parse_until(&["else", "endfor"])?;
+        let Some((tag, _)) = end else {
+            bail!("Template error at line {}: missing {{% endfor %}}", line);
+        };
+        let otherwise = if tag == "else" {
+            let (otherwise, end) = self.parse_until(&["endfor"])?;
+            if end.is_none() {
+                bail!("Template error at line {}: missing {{% endfor %}}", line);
+            }
+            otherwise
+        } else {
+            Vec::new()
+        };
+
+        Ok(Node::For {
+            key,
+            value,
+            iterable,
+            body,
+            otherwise,
+        })
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Literal(Value),
+    Symbol(&'static str),
+}
+
+fn tokenize(source: &str, line: usize) -> Result<Vec<Token>> {
+    const SYMBOLS: &[&str] = &[
+        "==", "!=", "<=", ">=", "<", ">", "|", ".", ",", "(", ")", "[", "]",
+    ];
+
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '"' || c == '\'' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => bail!("Template error at line {}: unterminated string", line),
+                    Some(&q) if q == c => break,
+                    Some('\\') => {
+                        i += 1;
+                        match chars.get(i) {
+                            Some('n') => text.push('\n'),
+                            Some('t') => text.push('\t'),
+                            Some(&other) => text.push(other),
+                            None => {}
+                        }
+                    }
+                    Some(&other) => text.push(other),
+                }
+                i += 1;
+            }
+            i += 1;
+            tokens.push(Token::Literal(Value::String(text)));
+        } else if c.is_ascii_digit()
+            || (c == '-' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit()))
+        {
+            let start = i;
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                // A dot followed by a non-digit is path access, e.g. `items.0.name`.
+                if chars[i] == '.' && !chars.get(i + 1).is_some_and(|d| d.is_ascii_digit()) {
+                    break;
+                }
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let number = match text.parse::<i64>() {
+                Ok(n) => Number::from(n),
+                Err(_) => text
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(Number::from_f64)
+                    .ok_or_else(|| {
+                        anyhow!("Template error at line {}: bad number {}", line, text)
+                    })?,
+            };
+            tokens.push(Token::Literal(Value::Number(number)));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            tokens.push(match word.as_str() {
+                "true" => Token::Literal(Value::Bool(true)),
+                "false" => Token::Literal(Value::Bool(false)),
+                "null" | "nil" | "none" => Token::Literal(Value::Null),
+                _ => Token::Ident(word),
+            });
+        } else {
+            let rest: String = chars[i..].iter().take(2).collect();
+            let Some(symbol) = SYMBOLS.iter().find(|s| rest.starts_with(**s)) else {
+                bail!("Template error at line {}: unexpected {:?}", line, c);
+            };
+            tokens.push(Token::Symbol(symbol));
+            i += symbol.len();
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_expression(source: &str, line: usize) -> Result<Expr> {
+    let tokens = tokenize(source, line)?;
+    if tokens.is_empty() {
+        bail!("Template error at line {}: missing expression", line);
+    }
+
+    let mut parser = ExprParser {
+        tokens,
+        position: 0,
+        line,
+    };
+    let expr = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+        bail!("Template error at line {}: unexpected {:?}", line, token);
+    }
+    Ok(expr)
+}
+
+struct ExprParser {
+    tokens: Vec<Token>,
+    position: usize,
+    line: usize,
+}
+
+impl ExprParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(word)) if word == keyword) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<()> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            bail!(
+                "Template error at line {}: expected '{}'",
+                self.line,
+                symbol
+            )
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut left = self.parse_and()?;
+        while self.eat_keyword("or") {
+            left = Expr::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut left = self.parse_not()?;
+        while self.eat_keyword("and") {
+            left = Expr::And(Box::new(left), Box::new(self.parse_not()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr> {
+        if self.eat_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr> {
+        let left = self.parse_filtered()?;
+
+        let operator = match self.peek() {
+            Some(Token::Symbol(s)) if ["==", "!=", "<", "<=", ">", ">="].contains(s) => *s,
+            Some(Token::Ident(word)) if word == "in" => "in",
+            _ => return Ok(left),
+        };
+        self.position += 1;
+
+        let right = self.parse_filtered()?;
+        Ok(Expr::Compare(Box::new(left), operator, Box::new(right)))
+    }
+
+    fn parse_filtered(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_primary()?;
+
+        while self.eat_symbol("|") {
+            let Some(Token::Ident(name)) = self.next() else {
+                bail!(
+                    "Template error at line {}: expected a filter name",
+                    self.line
+                );
+            };
+            if !FILTERS.contains(&name.as_str()) {
+                bail!(
+                    "Template error at line {}: unknown filter '{}'",
+                    self.line,
+                    name
+                );
+            }
+
+            let mut args = Vec::new();
+            if self.eat_symbol("(") && !self.eat_symbol(")") {
+                loop {
+                    args.push(self.parse_or()?);
+                    if self.eat_symbol(")") {
+                        break;
+                    }
+                    self.expect_symbol(",")?;
+                }
+            }
+            expr = Expr::Filter(Box::new(expr), name, args);
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some(Token::Literal(value)) => Ok(Expr::Literal(value)),
+            Some(Token::Symbol("(")) => {
+                let expr = self.parse_or()?;
+                self.expect_symbol(")")?;
+                Ok(expr)
+            }
+            Some(Token::Ident(name)) => {
+                let mut segments = Vec::new();
+                loop {
+                    if self.eat_symbol(".") {
+                        match self.next() {
+                            Some(Token::Ident(key)) => {
+                                segments.push(Expr::Literal(Value::String(key)))
+                            }
+                            Some(Token::Literal(Value::Number(n))) => {
+                                segments.push(Expr::Literal(Value::Number(n)))
+                            }
+                            _ => bail!(
+                                "Template error at line {}: expected a name after '.'",
+                                self.line
+                            ),
+                        }
+                    } else if self.eat_symbol("[") {
+                        segments.push(self.parse_or()?);
+                        self.expect_symbol("]")?;
+                    } else {
+                        break;
+                    }
+                }
+                Ok(Expr::Path(name, segments))
+            }
+            other => bail!(
+                "Template error at line {}: unexpected {:?}",
+                self.line,
+                other
+            ),
+        }
+    }
+}
+
+struct Renderer<'a> {
+    root: &'a Value,
+    scopes: Vec<HashMap<String, Value>>,
+}
+
+impl Renderer<'_> {
+    fn render_nodes(&mut self, nodes: &[Node], out: &mut String) -> Result<()> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => out.push_str(text),
+                Node::Output(expr) => out.push_str(&to_text(&self.eval(expr)?)?),
+                Node::If {
+                    branches,
+                    otherwise,
+                } => {
+                    let mut taken = None;
+                    for (condition, body) in branches {
+                        if truthy(&self.eval(condition)?) {
+                            taken = Some(body);
+                            break;
+                        }
+                    }
+                    self.render_nodes(taken.unwrap_or(otherwise), out)?;
+                }
+                Node::For {
+                    key,
+                    value,
+                    iterable,
+                    body,
+                    otherwise,
+                } => {
+                    let items: Vec<(Value, Value)> = match self.eval(iterable)? {
+                        Value::Array(items) => items
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, item)| (Value::from(i), item))
+                            .collect(),
+                        Value::Object(map) => match key {
+                            Some(_) => map
+                                .into_iter()
+                                .map(|(k, v)| (Value::String(k), v))
+                                .collect(),
+                            None => map
+                                .into_iter()
+                                .map(|(k, _)| (Value::Null, Value::String(k)))
+                                .collect(),
+                        },
+                        Value::Null => Vec::new(),
+                        other => vec![(Value::from(0), other)],
+                    };
+
+                    if items.is_empty() {
+                        self.render_nodes(otherwise, out)?;
+                        continue;
+                    }
+
+                    let length = items.len();
+                    for (index, (item_key, item)) in items.into_iter().enumerate() {
+                        let mut scope = HashMap::new();
+                        scope.insert(
+                            "loop".to_string(),
+                            serde_json::json!({
+                                "index": index + 1,
+                                "index0": index,
+                                "first": index == 0,
+                                "last": index + 1 == length,
+                                "length": length,
+                            }),
+                        );
+                        if let Some(key) = key {
+                            scope.insert(key.clone(), item_key);
+                        }
+                        scope.insert(value.clone(), item);
+
+                        self.scopes.push(scope);
+                        let result = self.render_nodes(body, out);
+                        self.scopes.pop();
+                        result?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn lookup(&self, name: &str) -> Value {
+        for scope in self.scopes.iter().rev() {
+            if let Some(value) = scope.get(name) {
+                return value.clone();
+            }
+        }
+        self.root.get(name).cloned().unwrap_or(Value::Null)
+    }
+
+    fn eval(&self, expr: &Expr) -> Result<Value> {
+        Ok(match expr {
+            Expr::Literal(value) => value.clone(),
+            Expr::Path(name, segments) => {
+                let mut value = self.lookup(name);
+                for segment in segments {
+                    value = match (value, self.eval(segment)?) {
+                        (Value::Object(map), Value::String(key)) => {
+                            map.get(&key).cloned().unwrap_or(Value::Null)
+                        }
+                        (Value::Array(items), Value::Number(n)) => n
+                            .as_i64()
+                            .and_then(|i| {
+                                let i = if i < 0 { items.len() as i64 + i } else { i };
+                                items.get(usize::try_from(i).ok()?).cloned()
+                            })
+                            .unwrap_or(Value::Null),
+                        _ => Value::Null,
+                    };
+                }
+                value
+            }
+            Expr::Filter(input, name, args) => {
+                let input = self.eval(input)?;
+                let args = args
+                    .iter()
+                    .map(|arg| self.eval(arg))
+                    .collect::<Result<Vec<_>>>()?;
+                apply_filter(name, input, &args)?
+            }
+            Expr::Not(inner) => Value::Bool(!truthy(&self.eval(inner)?)),
+            Expr::And(left, right) => {
+                let left = self.eval(left)?;
+                if truthy(&left) {
+                    self.eval(right)?
+                } else {
+                    left
+                }
+            }
+            Expr::Or(left, right) => {
+                let left = self.eval(left)?;
+                if truthy(&left) {
+                    left
+                } else {
+                    self.eval(right)?
+                }
+            }
+            Expr::Compare(left, operator, right) => {
+                let (left, right) = (self.eval(left)?, self.eval(right)?);
+                Value::Bool(compare(&left, operator, &right))
+            }
+        })
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
+
+fn compare(left: &Value, operator: &str, right: &Value) -> bool {
+    use std::cmp::Ordering;
+
+    if operator == "in" {
+        return match right {
+            Value::Array(items) => items.iter().any(|item| compare(left, "==", item)),
+            Value::Object(map) => left.as_str().is_some_and(|key| map.contains_key(key)),
+            Value::String(haystack) => left.as_str().is_some_and(|s| haystack.contains(s)),
+            _ => false,
+        };
+    }
+
+    let ordering = match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64().partial_cmp(&b.as_f64()),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => (left == right).then_some(Ordering::Equal),
+    };
+
+    match operator {
+        "==" => ordering == Some(Ordering::Equal),
+        "!=" => ordering != Some(Ordering::Equal),
+        "<" => ordering == Some(Ordering::Less),
+        "<=" => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        ">" => ordering == Some(Ordering::Greater),
+        _ => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+    }
+}
+
+/// Renders a value as template output: strings verbatim, null as nothing,
+/// arrays and objects as compact JSON.
+fn to_text(value: &Value) -> Result<String> {
+    Ok(match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Bool(_) | Value::Number(_) => value.to_string(),
+        _ => ruby_json::generate(value)?,
+    })
+}
+
+fn number_arg(args: &[Value], index: usize, default: i64) -> i64 {
+    args.get(index).and_then(Value::as_i64).unwrap_or(default)
+}
+
+fn apply_filter(name: &str, input: Value, args: &[Value]) -> Result<Value> {
+    let text = || to_text(&input);
+    let number = || {
+        input
+            .as_f64()
+            .or_else(|| input.as_str().and_then(|s| s.trim().parse().ok()))
+    };
+
+    Ok(match name {
+        "upper" => Value::String(text()?.to_uppercase()),
+        "lower" => Value::String(text()?.to_lowercase()),
+        "capitalize" => {
+            let text = text()?;
+            let mut chars = text.chars();
+            Value::String(match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            })
+        }
+        "trim" => Value::String(text()?.trim().to_string()),
+        "length" => Value::from(match &input {
+            Value::Array(items) => items.len(),
+            Value::Object(map) => map.len(),
+            Value::String(s) => s.chars().count(),
+            Value::Null => 0,
+            other => to_text(other)?.chars().count(),
+        }),
+        "default" => {
+            if truthy(&input) {
+                input
+            } else {
+                args.first().cloned().unwrap_or(Value::Null)
+            }
+        }
+        "join" => {
+            let separator = args.first().and_then(Value::as_str).unwrap_or(", ");
+            let items = match &input {
+                Value::Array(items) => items.iter().map(to_text).collect::<Result<Vec<_>>>()?,
+                other => vec![to_text(other)?],
+            };
+            Value::String(items.join(separator))
+        }
+        "first" | "last" => match input {
+            Value::Array(items) => {
+                let item = if name == "first" {
+                    items.first()
+                } else {
+                    items.last()
+                };
+                item.cloned().unwrap_or(Value::Null)
+            }
+            Value::String(s) => {
+                let c = if name == "first" {
+                    s.chars().next()
+                } else {
+                    s.chars().last()
+                };
+                c.map(|c| Value::String(c.to_string()))
+                    .unwrap_or(Value::Null)
+            }
+            other => other,
+        },
+        "reverse" => match input {
+            Value::Array(mut items) => {
+                items.reverse();
+                Value::Array(items)
+            }
+            other => Value::String(to_text(&other)?.chars().rev().collect()),
+        },
+        "sort" => match input {
+            Value::Array(mut items) => {
+                // Optional argument sorts objects by that attribute.
+                let attribute = args.first().and_then(Value::as_str);
+                let key = |v: &Value| match attribute {
+                    Some(attr) => v.get(attr).cloned().unwrap_or(Value::Null),
+                    None => v.clone(),
+                };
+                items.sort_by(|a, b| {
+                    let (a, b) = (key(a), key(b));
+                    if compare(&a, "<", &b) {
+                        std::cmp::Ordering::Less
+                    } else if compare(&a, ">", &b) {
+                        std::cmp::Ordering::Greater
+                    } else {
+                        std::cmp::Ordering::Equal
+                    }
+                });
+                Value::Array(items)
+            }
+            other => other,
+        },
+        "keys" | "values" => match input {
+            Value::Object(map) => Value::Array(if name == "keys" {
+                map.keys().cloned().map(Value::String).collect()
+            } else {
+                map.values().cloned().collect()
+            }),
+            _ => Value::Array(Vec::new()),
+        },
+        "replace" => {
+            let from = args.first().map(to_text).transpose()?.unwrap_or_default();
+            let to = args.get(1).map(to_text).transpose()?.unwrap_or_default();
+            Value::String(text()?.replace(&from, &to))
+        }
+        "round" => match number() {
+            Some(n) => {
+                let precision = number_arg(args, 0, 0).clamp(0, 15) as i32;
+                let factor = 10f64.powi(precision);
+                let rounded = (n * factor).round() / factor;
+                if precision == 0 {
+                    Value::from(rounded as i64)
+                } else {
+                    Number::from_f64(rounded)
+                        .map(Value::Number)
+                        .unwrap_or(Value::Null)
+                }
+            }
+            None => input,
+        },
+        "fixed" => match number() {
+            Some(n) => {
+                let precision = number_arg(args, 0, 2).clamp(0, 15) as usize;
+                Value::String(format!("{:.*}", precision, n))
+            }
+            None => input,
+        },
+        "bytes" => match number() {
+            Some(n) if n >= 0.0 => Value::String(byte_size::format(n as u64)),
+            _ => input,
+        },
+        "ljust" | "rjust" => {
+            let width = number_arg(args, 0, 0).max(0) as usize;
+            let alignment = if name == "ljust" {
+                Alignment::Left
+            } else {
+                Alignment::Right
+            };
+            let text = text()?;
+            Value::String(if display_width(&text) >= width {
+                text
+            } else {
+                pad(&text, width, alignment)
+            })
+        }
+        "truncate" => {
+            let width = number_arg(args, 0, 40).max(1) as usize;
+            Value::String(truncate(&text()?, width))
+        }
+        "json" => Value::String(if args.first().is_some_and(truthy) {
+            ruby_json::pretty_generate(&input)?
+        } else {
+            ruby_json::generate(&input)?
+        }),
+        "date" => {
+            let format = args
+                .first()
+                .and_then(Value::as_str)
+                .unwrap_or("%Y-%m-%d %H:%M:%S");
+            let time: Option<DateTime<Utc>> = match &input {
+                Value::String(s) => DateTime::parse_from_rfc3339(s)
+                    .ok()
+                    .map(|t| t.with_timezone(&Utc)),
+                Value::Number(n) => n
+                    .as_i64()
+                    .and_then(|secs| DateTime::from_timestamp(secs, 0)),
+                _ => None,
+            };
+            match time {
+                Some(time) => {
+                    // chrono panics while formatting an invalid specifier.
+                    let items: Vec<Item> = StrftimeItems::new(format).collect();
+                    if items.contains(&Item::Error) {
+                        bail!("Invalid date format {:?}", format);
+                    }
+                    Value::String(time.format_with_items(items.into_iter()).to_string())
+                }
+                None => input,
+            }
+        }
+        _ => bail!("Unknown filter '{}'", name),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(source: &str, context: Value) -> String {
+        Template::parse(source).unwrap().render(&context).unwrap()
+    }
+
+    #[test]
+    fn test_variables_and_filters() {
+        let context = json!({"name": "BasicCli", "info": {"size": 1536, "tags": ["a", "b"]}});
+
+        assert_eq!(
+            render(
+                "{{ name }} v{{ missing | default('1.0') }}",
+                context.clone()
+            ),
+            "BasicCli v1.0"
+        );
+        assert_eq!(render("{{ name | upper }}", context.clone()), "BASICCLI");
+        assert_eq!(
+            render("{{ info.size | bytes }}", context.clone()),
+            "1.5 KiB"
+        );
+        assert_eq!(
+            render("{{ info.tags | join('+') }}", context.clone()),
+            "a+b"
+        );
+        assert_eq!(
+            render("{{ info.tags.1 }}/{{ info['tags'][0] }}", context.clone()),
+            "b/a"
+        );
+        assert_eq!(
+            render("[{{ name | ljust(10) }}]", context.clone()),
+            "[BasicCli  ]"
+        );
+        assert_eq!(
+            render(
+                "{{ 3.14159 | round(2) }} {{ 2 | fixed(3) }}",
+                context.clone()
+            ),
+            "3.14 2.000"
+        );
+        assert_eq!(render("{{ info.tags }}", context), "[\"a\",\"b\"]");
+    }
+
+    #[test]
+    fn test_conditionals() {
+        let template = "{% if count > 10 %}many{% elif count == 0 %}none{% else %}some{% endif %}";
+
+        assert_eq!(render(template, json!({"count": 50})), "many");
+        assert_eq!(render(template, json!({"count": 0})), "none");
+        assert_eq!(render(template, json!({"count": 3})), "some");
+        assert_eq!(
+            render(
+                "{% if not a and (b or 'x' in c) %}yes{% endif %}",
+                json!({"a": false, "c": ["x"]})
+            ),
+            "yes"
+        );
+    }
+
+    #[test]
+    fn test_loops() {
+        let context =
+            json!({"items": [{"n": "a"}, {"n": "b"}, {"n": "c"}], "env": {"A": 1, "B": 2}});
+
+        assert_eq!(
+            render("{% for i in items %}{{ loop.index }}={{ i.n }}{% if not loop.last %}, {% endif %}{% endfor %}", context.clone()),
+            "1=a, 2=b, 3=c"
+        );
+        assert_eq!(
+            render(
+                "{% for k, v in env %}{{ k }}:{{ v }} {% endfor %}",
+                context.clone()
+            ),
+            "A:1 B:2 "
+        );
+        assert_eq!(
+            render(
+                "{% for x in nothing %}x{% else %}empty{% endfor %}",
+                context
+            ),
+            "empty"
+        );
+    }
+
+    #[test]
+    fn test_whitespace_control() {
+        let template = "{% for i in items %}\n- {{ i }}\n{% endfor %}\n{#- comment -#}\ndone";
+        assert_eq!(render(template, json!({"items": [1, 2]})), "- 1\n- 2\ndone");
+    }
+
+    #[test]
+    fn test_invalid_date_format() {
+        let template = Template::parse("{{ t | date(\"%Q\") }}").unwrap();
+        let error = template
+            .render(&json!({"t": "2024-01-15T10:00:00Z"}))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Invalid date format"), "{}", error);
+        assert_eq!(
+            render("{{ t | date(\"%d.%m.%Y\") }}", json!({"t": 0})),
+            "01.01.1970"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (source, message) in [
+            ("{% if x %}open", "missing {% endif %}"),
+            ("{{ x | nope }}", "unknown filter 'nope'"),
+            ("{% endfor %}", "unexpected {% endfor %}"),
+            ("{{ x ", "unclosed {{"),
+            ("line\n{% bogus %}", "line 2"),
+        ] {
+            let error = Template::parse(source).unwrap_err().to_string();
+            assert!(error.contains(message), "{:?} -> {}", source, error);
+        }
+    }
+}
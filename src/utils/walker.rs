@@ -0,0 +1,651 @@
+//! Recursive directory traversal shared by `find`, `search`, `checksum` and
+//! the other commands that take directories.
+//!
+//! Entries come out depth-first in name order. Exclude globs and ignore
+//! files prune whole subtrees; the remaining filters (include globs, type,
+//! size, modification time, minimum depth) only decide what is reported.
+
+use super::file_handler::FileError;
+use super::glob::{self, Glob};
+use anyhow::{bail, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use std::fmt;
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryType {
+    File,
+    Dir,
+    Symlink,
+    /// Sockets, FIFOs and device nodes.
+    Other,
+}
+
+impl EntryType {
+    pub fn name(self) -> &'static str {
+        match self {
+            EntryType::File => "file",
+            EntryType::Dir => "directory",
+            EntryType::Symlink => "symlink",
+            EntryType::Other => "other",
+        }
+    }
+
+    fn of(metadata: &Metadata) -> Self {
+        let file_type = metadata.file_type();
+        if file_type.is_symlink() {
+            EntryType::Symlink
+        } else if file_type.is_dir() {
+            EntryType::Dir
+        } else if file_type.is_file() {
+            EntryType::File
+        } else {
+            EntryType::Other
+        }
+    }
+}
+
+impl fmt::Display for EntryType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for EntryType {
+    type Err = anyhow::Error;
+
+    /// Accepts `find -type` letters as well as full names.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "f" | "file" => Ok(EntryType::File),
+            "d" | "dir" | "directory" => Ok(EntryType::Dir),
+            "l" | "link" | "symlink" => Ok(EntryType::Symlink),
+            "o" | "other" => Ok(EntryType::Other),
+            _ => bail!("Unknown entry type: {} (expected f, d, l or o)", s),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct WalkOptions {
+    /// Only report entries matching one of these; directories are still
+    /// descended into.
+    pub include: Vec<Glob>,
+    /// Skip matching entries, and everything beneath matching directories.
+    pub exclude: Vec<Glob>,
+    /// File names such as `.gitignore` to read rules from in every
+    /// directory.
+    pub ignore_files: Vec<String>,
+    /// Entry types to report; empty reports all of them.
+    pub types: Vec<EntryType>,
+    /// Size bounds (inclusive). Directories never match a size filter.
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub newer_than: Option<SystemTime>,
+    pub older_than: Option<SystemTime>,
+    /// Depth 1 is the root's children. Deeper directories are not read.
+    pub min_depth: Option<usize>,
+    pub max_depth: Option<usize>,
+    /// Follow symlinks to directories (loops are detected and skipped).
+    pub follow_links: bool,
+}
+
+#[derive(Debug)]
+pub struct WalkEntry {
+    pub path: PathBuf,
+    /// `/`-separated path below the root it was found under.
+    pub relative: String,
+    /// 0 for roots named explicitly, 1 for their children, and so on.
+    pub depth: usize,
+    pub file_type: EntryType,
+    /// The link's own metadata unless links are followed.
+    pub metadata: Metadata,
+}
+
+/// Everything a walk reported, plus directories that couldn't be read.
+#[derive(Debug, Default)]
+pub struct Walk {
+    pub entries: Vec<WalkEntry>,
+    pub errors: Vec<String>,
+}
+
+pub struct Walker {
+    options: WalkOptions,
+}
+
+impl Walker {
+    pub fn new(options: WalkOptions) -> Self {
+        Self { options }
+    }
+
+    /// Collects every reported entry under `roots`.
+    pub fn walk<P: AsRef<Path>>(&self, roots: &[P]) -> Result<Walk> {
+        let mut entries = Vec::new();
+        let errors = self.visit(roots, |entry| {
+            entries.push(entry);
+            Ok(())
+        })?;
+        Ok(Walk { entries, errors })
+    }
+
+    /// Calls `f` for each reported entry as it is found. Roots named as
+    /// files are reported themselves (glob and ignore rules don't apply to
+    /// them); directory roots are not. Unreadable roots are an error, while
+    /// unreadable subdirectories are skipped and returned as messages.
+    pub fn visit<P, F>(&self, roots: &[P], mut f: F) -> Result<Vec<String>>
+    where
+        P: AsRef<Path>,
+        F: FnMut(WalkEntry) -> Result<()>,
+    {
+        let mut errors = Vec::new();
+        for root in roots {
+            let root = root.as_ref();
+            let metadata =
+                fs::metadata(root).map_err(|_| FileError::NotFound(root.display().to_string()))?;
+
+            if metadata.is_dir() {
+                let mut state = State {
+                    ignores: Vec::new(),
+                    ancestors: Vec::new(),
+                    errors: &mut errors,
+                };
+                if self.options.follow_links {
+                    state.ancestors.push(fs::canonicalize(root)?);
+                }
+                let entries = read_dir(root)?;
+                self.walk_dir(&mut state, root, "", entries, 1, &mut f)?;
+            } else {
+                let entry = WalkEntry {
+                    path: root.to_path_buf(),
+                    relative: root.to_string_lossy().replace('\\', "/"),
+                    depth: 0,
+                    file_type: EntryType::of(&metadata),
+                    metadata,
+                };
+                if self.matches_metadata(&entry) {
+                    f(entry)?;
+                }
+            }
+        }
+        Ok(errors)
+    }
+
+    fn walk_dir<F>(
+        &self,
+        state: &mut State,
+        dir: &Path,
+        relative: &str,
+        entries: Vec<fs::DirEntry>,
+        depth: usize,
+        f: &mut F,
+    ) -> Result<()>
+    where
+        F: FnMut(WalkEntry) -> Result<()>,
+    {
+        let pushed = self.load_ignores(state, dir, relative)?;
+
+        for dir_entry in entries {
+            let path = dir_entry.path();
+            let name = dir_entry.file_name().to_string_lossy().into_owned();
+            let child_relative = if relative.is_empty() {
+                name
+            } else {
+                format!("{}/{}", relative, name)
+            };
+
+            let mut metadata = match fs::symlink_metadata(&path) {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    state.errors.push(format!("{}: {}", path.display(), e));
+                    continue;
+                }
+            };
+            if self.options.follow_links && metadata.file_type().is_symlink() {
+                // Dangling links are reported as links.
+                if let Ok(target) = fs::metadata(&path) {
+                    metadata = target;
+                }
+            }
+            let file_type = EntryType::of(&metadata);
+            let is_dir = file_type == EntryType::Dir;
+
+            if glob::any_match(&self.options.exclude, &child_relative)
+                || is_ignored(&state.ignores, &child_relative, is_dir)
+            {
+                continue;
+            }
+
+            let entry = WalkEntry {
+                path: path.clone(),
+                relative: child_relative.clone(),
+                depth,
+                file_type,
+                metadata,
+            };
+            if self.matches(&entry) {
+                f(entry)?;
+            }
+
+            if !is_dir || self.options.max_depth.is_some_and(|max| depth >= max) {
+                continue;
+            }
+            let canonical = if self.options.follow_links {
+                match fs::canonicalize(&path) {
+                    Ok(canonical) if state.ancestors.contains(&canonical) => {
+                        state
+                            .errors
+                            .push(format!("{}: file system loop detected", path.display()));
+                        continue;
+                    }
+                    Ok(canonical) => Some(canonical),
+                    Err(_) => None,
+                }
+            } else {
+                None
+            };
+            let children = match read_dir(&path) {
+                Ok(children) => children,
+                Err(e) => {
+                    state.errors.push(e.to_string());
+                    continue;
+                }
+            };
+
+            if let Some(canonical) = &canonical {
+                state.ancestors.push(canonical.clone());
+            }
+            self.walk_dir(state, &path, &child_relative, children, depth + 1, f)?;
+            if canonical.is_some() {
+                state.ancestors.pop();
+            }
+        }
+
+        state.ignores.truncate(state.ignores.len() - pushed);
+        Ok(())
+    }
+
+    /// Reads this directory's ignore files onto the stack; returns how many
+    /// were pushed.
+    fn load_ignores(&self, state: &mut State, dir: &Path, relative: &str) -> Result<usize> {
+        let mut pushed = 0;
+        for name in &self.options.ignore_files {
+            let path = dir.join(name);
+            if !path.is_file() {
+                continue;
+            }
+            let content = fs::read_to_string(&path)
+                .map_err(|e| FileError::ReadError(path.display().to_string(), e.to_string()))?;
+            let rules = parse_ignore_rules(&content)
+                .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+            state.ignores.push(IgnoreFile {
+                base: relative.to_string(),
+                rules,
+            });
+            pushed += 1;
+        }
+        Ok(pushed)
+    }
+
+    fn matches(&self, entry: &WalkEntry) -> bool {
+        let options = &self.options;
+        if options.min_depth.is_some_and(|min| entry.depth < min) {
+            return false;
+        }
+        if !options.include.is_empty() && !glob::any_match(&options.include, &entry.relative) {
+            return false;
+        }
+        self.matches_metadata(entry)
+    }
+
+    fn matches_metadata(&self, entry: &WalkEntry) -> bool {
+        let options = &self.options;
+        if !options.types.is_empty() && !options.types.contains(&entry.file_type) {
+            return false;
+        }
+
+        if options.min_size.is_some() || options.max_size.is_some() {
+            let size = entry.metadata.len();
+            if entry.file_type == EntryType::Dir
+                || options.min_size.is_some_and(|min| size < min)
+                || options.max_size.is_some_and(|max| size > max)
+            {
+                return false;
+            }
+        }
+
+        if options.newer_than.is_some() || options.older_than.is_some() {
+            let Ok(modified) = entry.metadata.modified() else {
+                return false;
+            };
+            if options.newer_than.is_some_and(|t| modified <= t)
+                || options.older_than.is_some_and(|t| modified >= t)
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Per-root traversal state.
+struct State<'a> {
+    /// Ignore files in effect, outermost first.
+    ignores: Vec<IgnoreFile>,
+    /// Canonical paths of the directories being walked, when following
+    /// links.
+    ancestors: Vec<PathBuf>,
+    errors: &'a mut Vec<String>,
+}
+
+struct IgnoreFile {
+    /// Directory the file was found in, relative to the root.
+    base: String,
+    rules: Vec<IgnoreRule>,
+}
+
+struct IgnoreRule {
+    glob: Glob,
+    negated: bool,
+    dir_only: bool,
+}
+
+/// Parses `.gitignore` syntax: `#` comments, `!` negation, a trailing `/`
+/// for directories only, and a `/` elsewhere anchoring the pattern to the
+/// ignore file's directory.
+fn parse_ignore_rules(content: &str) -> Result<Vec<IgnoreRule>> {
+    let mut rules = Vec::new();
+    for line in content.lines() {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        rules.push(IgnoreRule {
+            glob: Glob::new(pattern)?,
+            negated,
+            dir_only: pattern.ends_with('/'),
+        });
+    }
+    Ok(rules)
+}
+
+/// The last matching rule decides, with deeper ignore files read after
+/// (and so overriding) shallower ones.
+fn is_ignored(ignores: &[IgnoreFile], relative: &str, is_dir: bool) -> bool {
+    let mut ignored = false;
+    for file in ignores {
+        let path = if file.base.is_empty() {
+            relative
+        } else {
+            match relative
+                .strip_prefix(file.base.as_str())
+                .and_then(|rest| rest.strip_prefix('/'))
+            {
+                Some(rest) => rest,
+                None => continue,
+            }
+        };
+        for rule in &file.rules {
+            if (!rule.dir_only || is_dir) && rule.glob.is_match(path) {
+                ignored = !rule.negated;
+            }
+        }
+    }
+    ignored
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<fs::DirEntry>> {
+    let read_error =
+        |e: std::io::Error| FileError::ReadError(dir.display().to_string(), e.to_string());
+    let mut entries: Vec<fs::DirEntry> = fs::read_dir(dir)
+        .map_err(read_error)?
+        .collect::<std::io::Result<_>>()
+        .map_err(read_error)?;
+    entries.sort_by_key(|entry| entry.file_name());
+    Ok(entries)
+}
+
+/// Parses a `--newer`/`--older` bound: a duration back from now (`90s`,
+/// `15m`, `2h`, `7d`, `2w`), a date (`2024-01-31`, local midnight), a local
+/// date and time, or an RFC 3339 timestamp.
+pub fn parse_time(input: &str) -> Result<SystemTime> {
+    let trimmed = input.trim();
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    if let Ok(count) = number.parse::<u64>() {
+        let seconds = match unit.trim() {
+            "s" => Some(1),
+            "m" => Some(60),
+            "h" => Some(3600),
+            "d" => Some(86_400),
+            "w" => Some(604_800),
+            _ => None,
+        };
+        // Durations reaching before what the clock can represent are invalid.
+        let ago = seconds
+            .and_then(|seconds| count.checked_mul(seconds))
+            .and_then(|seconds| SystemTime::now().checked_sub(Duration::from_secs(seconds)));
+        if let Some(time) = ago {
+            return Ok(time);
+        }
+    }
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(trimmed) {
+        return Ok(time.into());
+    }
+    let local = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(trimmed, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(trimmed, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        });
+    if let Some(time) = local.and_then(|t| Local.from_local_datetime(&t).earliest()) {
+        return Ok(time.into());
+    }
+
+    bail!(
+        "Invalid time: {:?} (expected a duration such as 2h or 7d, a date, or an RFC 3339 timestamp)",
+        input
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::file_handler::FileHandler;
+    use tempfile::TempDir;
+
+    fn tree() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for (path, content) in [
+            ("README.md", "readme"),
+            ("src/main.rs", "fn main() {}"),
+            ("src/lib.rs", ""),
+            ("src/gen/out.rs", "generated"),
+            ("target/debug/app", "binary"),
+            ("logs/a.log", "aaaa"),
+            ("logs/keep.log", "k"),
+        ] {
+            FileHandler::write(dir.path().join(path), content).unwrap();
+        }
+        dir
+    }
+
+    fn relative(options: WalkOptions, dir: &TempDir) -> Vec<String> {
+        let walk = Walker::new(options).walk(&[dir.path()]).unwrap();
+        assert!(walk.errors.is_empty(), "{:?}", walk.errors);
+        walk.entries.into_iter().map(|e| e.relative).collect()
+    }
+
+    #[test]
+    fn test_sorted_depth_first() {
+        let dir = tree();
+        let options = WalkOptions {
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            relative(options, &dir),
+            [
+                "README.md",
+                "logs",
+                "logs/a.log",
+                "logs/keep.log",
+                "src",
+                "src/gen",
+                "src/lib.rs",
+                "src/main.rs",
+                "target",
+                "target/debug"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_globs_and_types() {
+        let dir = tree();
+        let options = WalkOptions {
+            include: glob::compile_all(&["*.rs".into()]).unwrap(),
+            exclude: glob::compile_all(&["gen".into()]).unwrap(),
+            types: vec![EntryType::File],
+            ..Default::default()
+        };
+        assert_eq!(relative(options, &dir), ["src/lib.rs", "src/main.rs"]);
+
+        let options = WalkOptions {
+            types: vec![EntryType::Dir],
+            min_depth: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(relative(options, &dir), ["src/gen", "target/debug"]);
+    }
+
+    #[test]
+    fn test_ignore_files() {
+        let dir = tree();
+        FileHandler::write(
+            dir.path().join(".gitignore"),
+            "# build output\n/target/\n*.log\n",
+        )
+        .unwrap();
+        FileHandler::write(dir.path().join("logs/.gitignore"), "!keep.log\n").unwrap();
+        FileHandler::write(dir.path().join("src/.gitignore"), "gen/\nlib.rs\n").unwrap();
+
+        let options = WalkOptions {
+            ignore_files: vec![".gitignore".into()],
+            types: vec![EntryType::File],
+            ..Default::default()
+        };
+        assert_eq!(
+            relative(options, &dir),
+            [
+                ".gitignore",
+                "README.md",
+                "logs/.gitignore",
+                "logs/keep.log",
+                "src/.gitignore",
+                "src/main.rs"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_size_and_time_filters() {
+        let dir = tree();
+        let options = WalkOptions {
+            min_size: Some(4),
+            max_size: Some(6),
+            ..Default::default()
+        };
+        assert_eq!(
+            relative(options, &dir),
+            ["README.md", "logs/a.log", "target/debug/app"]
+        );
+
+        let options = WalkOptions {
+            newer_than: Some(parse_time("1h").unwrap()),
+            types: vec![EntryType::File],
+            ..Default::default()
+        };
+        assert_eq!(relative(options, &dir).len(), 7);
+        let options = WalkOptions {
+            older_than: Some(parse_time("1h").unwrap()),
+            ..Default::default()
+        };
+        assert!(relative(options, &dir).is_empty());
+    }
+
+    #[test]
+    fn test_file_roots_and_missing_roots() {
+        let dir = tree();
+        let file = dir.path().join("src/main.rs");
+        let walk = Walker::new(WalkOptions::default()).walk(&[&file]).unwrap();
+        assert_eq!(walk.entries.len(), 1);
+        assert_eq!(walk.entries[0].depth, 0);
+        assert_eq!(walk.entries[0].path, file);
+
+        assert!(Walker::new(WalkOptions::default())
+            .walk(&[dir.path().join("missing")])
+            .is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_loops() {
+        let dir = tree();
+        std::os::unix::fs::symlink(dir.path().join("src"), dir.path().join("src/gen/up")).unwrap();
+
+        let walk = Walker::new(WalkOptions::default())
+            .walk(&[dir.path()])
+            .unwrap();
+        let link = walk
+            .entries
+            .iter()
+            .find(|e| e.relative == "src/gen/up")
+            .unwrap();
+        assert_eq!(link.file_type, EntryType::Symlink);
+
+        let options = WalkOptions {
+            follow_links: true,
+            ..Default::default()
+        };
+        let walk = Walker::new(options).walk(&[dir.path()]).unwrap();
+        assert_eq!(walk.errors.len(), 1, "{:?}", walk.errors);
+        assert!(walk.errors[0].contains("loop"));
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let now = SystemTime::now();
+        let two_hours = parse_time("2h").unwrap();
+        let elapsed = now.duration_since(two_hours).unwrap().as_secs();
+        assert!((7199..=7201).contains(&elapsed));
+
+        let utc = parse_time("2024-01-31T12:00:00Z").unwrap();
+        assert_eq!(
+            utc.duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            1_706_702_400
+        );
+        assert!(parse_time("2024-01-31").is_ok());
+        assert!(parse_time("yesterday").is_err());
+        for huge in ["9999999999999999999d", "18446744073709551615s"] {
+            let error = parse_time(huge).unwrap_err().to_string();
+            assert!(error.starts_with("Invalid time"), "{}", error);
+        }
+        assert!("x".parse::<EntryType>().is_err());
+        assert_eq!("d".parse::<EntryType>().unwrap(), EntryType::Dir);
+    }
+}
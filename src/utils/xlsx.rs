@@ -0,0 +1,1015 @@
+use super::zip_archive::{ZipReader, ZipWriter};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{Duration, NaiveDate};
+use std::collections::HashMap;
+
+const MAIN_NAMESPACE: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+const RELATIONSHIP_NAMESPACE: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const PACKAGE_RELATIONSHIPS: &str = "http://schemas.openxmlformats.org/package/2006/relationships";
+
+/// Excel's own sheet limits (row 1048576, column XFD). Cell references past
+/// them only come from corrupt or hostile files.
+const MAX_ROWS: usize = 1_048_576;
+const MAX_COLUMNS: usize = 16_384;
+
+/// Which sheet to read and where its header row is.
+#[derive(Debug, Clone)]
+pub struct SheetOptions {
+    /// Sheet name, or 1-based position; the first sheet when `None`.
+    pub sheet: Option<String>,
+    /// 1-based row holding the column names; rows above it are skipped.
+    pub header_row: usize,
+}
+
+impl Default for SheetOptions {
+    fn default() -> Self {
+        Self {
+            sheet: None,
+            header_row: 1,
+        }
+    }
+}
+
+/// A worksheet as text, the same way a CSV export would render it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sheet {
+    pub name: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+struct SheetRef {
+    name: String,
+    path: String,
+}
+
+/// Lists the sheet names of a workbook in order.
+#[allow(dead_code)]
+pub fn sheet_names(bytes: &[u8]) -> Result<Vec<String>> {
+    let zip = ZipReader::new(bytes)?;
+    Ok(workbook_sheets(&zip)?
+        .0
+        .into_iter()
+        .map(|s| s.name)
+        .collect())
+}
+
+pub fn read_sheet(bytes: &[u8], options: &SheetOptions) -> Result<Sheet> {
+    if options.header_row == 0 {
+        bail!("Header row numbers start at 1");
+    }
+
+    let zip = ZipReader::new(bytes)?;
+    let (sheets, date1904) = workbook_sheets(&zip)?;
+    let sheet = select_sheet(sheets, options.sheet.as_deref())?;
+
+    let shared_strings = match zip.read_string("xl/sharedStrings.xml")? {
+        Some(xml) => parse_shared_strings(&xml)?,
+        None => Vec::new(),
+    };
+    let date_styles = match zip.read_string("xl/styles.xml")? {
+        Some(xml) => parse_date_styles(&xml)?,
+        None => Vec::new(),
+    };
+    let xml = zip
+        .read_string(&sheet.path)?
+        .with_context(|| format!("workbook is missing {}", sheet.path))?;
+
+    let cells = CellContext {
+        shared_strings: &shared_strings,
+        date_styles: &date_styles,
+        date1904,
+    };
+    let grid = parse_cells(&xml, &cells)?;
+
+    let mut rows = grid.into_iter().skip(options.header_row - 1);
+    let header_cells = rows.next().unwrap_or_default();
+    let mut data: Vec<Vec<String>> = rows
+        .filter(|row| row.iter().any(|cell| !cell.is_empty()))
+        .collect();
+
+    let width = data
+        .iter()
+        .chain(std::iter::once(&header_cells))
+        .map(|row| {
+            row.iter()
+                .rposition(|cell| !cell.is_empty())
+                .map_or(0, |i| i + 1)
+        })
+        .max()
+        .unwrap_or(0);
+
+    let headers = (0..width)
+        .map(|i| match header_cells.get(i).map(|h| h.trim()) {
+            Some(header) if !header.is_empty() => header.to_string(),
+            _ => format!("column_{}", i + 1),
+        })
+        .collect();
+    for row in &mut data {
+        row.resize(width, String::new());
+    }
+
+    Ok(Sheet {
+        name: sheet.name,
+        headers,
+        rows: data,
+    })
+}
+
+/// Builds a single-sheet workbook. Values that look like plain numbers are
+/// stored as numbers; everything else as text. The header row is bold.
+pub fn write_workbook(
+    sheet_name: &str,
+    headers: &[String],
+    rows: &[Vec<String>],
+) -> Result<Vec<u8>> {
+    if sheet_name.is_empty()
+        || sheet_name.chars().count() > 31
+        || sheet_name.contains(['[', ']', ':', '*', '?', '/', '\\'])
+    {
+        bail!("Invalid sheet name: {:?}", sheet_name);
+    }
+
+    let mut sheet = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="{}"><sheetData>"#,
+        MAIN_NAMESPACE
+    );
+    for (r, row) in std::iter::once(headers)
+        .chain(rows.iter().map(Vec::as_slice))
+        .enumerate()
+    {
+        let style = if r == 0 { r#" s="1""# } else { "" };
+        sheet.push_str(&format!(r#"<row r="{}">"#, r + 1));
+        for (c, value) in row.iter().enumerate() {
+            if value.is_empty() {
+                continue;
+            }
+            let reference = format!("{}{}", column_name(c), r + 1);
+            if r > 0 && looks_numeric(value) {
+                sheet.push_str(&format!(r#"<c r="{}"><v>{}</v></c>"#, reference, value));
+            } else {
+                sheet.push_str(&format!(
+                    r#"<c r="{}" t="inlineStr"{}><is><t xml:space="preserve">{}</t></is></c>"#,
+                    reference,
+                    style,
+                    escape(value)
+                ));
+            }
+        }
+        sheet.push_str("</row>");
+    }
+    sheet.push_str("</sheetData></worksheet>");
+
+    let content_types = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/><Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/></Types>"#;
+    let root_rels = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="{}"><Relationship Id="rId1" Type="{}/officeDocument" Target="xl/workbook.xml"/></Relationships>"#,
+        PACKAGE_RELATIONSHIPS, RELATIONSHIP_NAMESPACE
+    );
+    let workbook = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="{}" xmlns:r="{}"><sheets><sheet name="{}" sheetId="1" r:id="rId1"/></sheets></workbook>"#,
+        MAIN_NAMESPACE,
+        RELATIONSHIP_NAMESPACE,
+        escape(sheet_name)
+    );
+    let workbook_rels = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="{p}"><Relationship Id="rId1" Type="{r}/worksheet" Target="worksheets/sheet1.xml"/><Relationship Id="rId2" Type="{r}/styles" Target="styles.xml"/></Relationships>"#,
+        p = PACKAGE_RELATIONSHIPS,
+        r = RELATIONSHIP_NAMESPACE
+    );
+    let styles = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<styleSheet xmlns="{}"><fonts count="2"><font><sz val="11"/><name val="Calibri"/></font><font><b/><sz val="11"/><name val="Calibri"/></font></fonts><fills count="2"><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill></fills><borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders><cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs><cellXfs count="2"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/><xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/></cellXfs><cellStyles count="1"><cellStyle name="Normal" xfId="0" builtinId="0"/></cellStyles></styleSheet>"#,
+        MAIN_NAMESPACE
+    );
+
+    let mut zip = ZipWriter::new();
+    zip.add("[Content_Types].xml", content_types.as_bytes())?;
+    zip.add("_rels/.rels", root_rels.as_bytes())?;
+    zip.add("xl/workbook.xml", workbook.as_bytes())?;
+    zip.add("xl/_rels/workbook.xml.rels", workbook_rels.as_bytes())?;
+    zip.add("xl/styles.xml", styles.as_bytes())?;
+    zip.add("xl/worksheets/sheet1.xml", sheet.as_bytes())?;
+    Ok(zip.finish())
+}
+
+/// Reads the sheet list (resolving each sheet's part through the workbook
+/// relationships) and whether the workbook uses the 1904 date system.
+fn workbook_sheets(zip: &ZipReader) -> Result<(Vec<SheetRef>, bool)> {
+    let workbook = zip
+        .read_string("xl/workbook.xml")?
+        .context("not an Excel workbook (missing xl/workbook.xml)")?;
+
+    let mut targets = HashMap::new();
+    if let Some(rels) = zip.read_string("xl/_rels/workbook.xml.rels")? {
+        let mut scanner = XmlScanner::new(&rels);
+        while let Some(event) = scanner.next_event()? {
+            if let XmlEvent::Start {
+                name: "Relationship",
+                attrs,
+                ..
+            } = event
+            {
+                if let (Some(id), Some(target)) = (attr(&attrs, "Id"), attr(&attrs, "Target")) {
+                    let path = match target.strip_prefix('/') {
+                        Some(absolute) => absolute.to_string(),
+                        None => format!("xl/{}", target),
+                    };
+                    targets.insert(id.to_string(), path);
+                }
+            }
+        }
+    }
+
+    let mut sheets = Vec::new();
+    let mut date1904 = false;
+    let mut scanner = XmlScanner::new(&workbook);
+    while let Some(event) = scanner.next_event()? {
+        match event {
+            XmlEvent::Start {
+                name: "sheet",
+                attrs,
+                ..
+            } => {
+                let name = attr(&attrs, "name").unwrap_or_default().to_string();
+                // `r:id` has the local name `id`, distinct from `sheetId`.
+                let path = attr(&attrs, "id")
+                    .and_then(|id| targets.get(id).cloned())
+                    .unwrap_or_else(|| format!("xl/worksheets/sheet{}.xml", sheets.len() + 1));
+                sheets.push(SheetRef { name, path });
+            }
+            XmlEvent::Start {
+                name: "workbookPr",
+                attrs,
+                ..
+            } => {
+                date1904 = matches!(attr(&attrs, "date1904"), Some("1" | "true"));
+            }
+            _ => {}
+        }
+    }
+
+    Ok((sheets, date1904))
+}
+
+fn select_sheet(sheets: Vec<SheetRef>, selector: Option<&str>) -> Result<SheetRef> {
+    let names = || {
+        sheets
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let index = match selector {
+        None => Some(0),
+        Some(selector) => sheets
+            .iter()
+            .position(|s| s.name == selector)
+            .or_else(|| {
+                sheets
+                    .iter()
+                    .position(|s| s.name.eq_ignore_ascii_case(selector))
+            })
+            .or_else(|| {
+                selector
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|n| n.checked_sub(1))
+            }),
+    };
+
+    match index {
+        Some(i) if i < sheets.len() => Ok(sheets.into_iter().nth(i).unwrap()),
+        _ if sheets.is_empty() => bail!("Workbook has no sheets"),
+        _ => bail!(
+            "Sheet {:?} not found (available: {})",
+            selector.unwrap_or_default(),
+            names()
+        ),
+    }
+}
+
+fn parse_shared_strings(xml: &str) -> Result<Vec<String>> {
+    let mut strings = Vec::new();
+    let mut current = String::new();
+    let mut in_text = false;
+    let mut phonetic_depth = 0;
+    let mut scanner = XmlScanner::new(xml);
+
+    while let Some(event) = scanner.next_event()? {
+        match event {
+            XmlEvent::Start { name: "si", .. } => current.clear(),
+            XmlEvent::End { name: "si" } => strings.push(std::mem::take(&mut current)),
+            // Phonetic guides (furigana) are not part of the cell text.
+            XmlEvent::Start {
+                name: "rPh",
+                empty: false,
+                ..
+            } => phonetic_depth += 1,
+            XmlEvent::End { name: "rPh" } => phonetic_depth -= 1,
+            XmlEvent::Start {
+                name: "t", empty, ..
+            } => in_text = !empty,
+            XmlEvent::End { name: "t" } => in_text = false,
+            XmlEvent::Text(text) if in_text && phonetic_depth == 0 => current.push_str(&text),
+            _ => {}
+        }
+    }
+
+    Ok(strings)
+}
+
+/// Returns, per cell style index, whether numbers in that style are dates.
+fn parse_date_styles(xml: &str) -> Result<Vec<bool>> {
+    let mut custom_formats = HashMap::new();
+    let mut styles = Vec::new();
+    let mut in_cell_xfs = false;
+    let mut scanner = XmlScanner::new(xml);
+
+    while let Some(event) = scanner.next_event()? {
+        match event {
+            XmlEvent::Start {
+                name: "numFmt",
+                attrs,
+                ..
+            } => {
+                if let (Some(id), Some(code)) =
+                    (attr(&attrs, "numFmtId"), attr(&attrs, "formatCode"))
+                {
+                    custom_formats.insert(id.to_string(), is_date_format(code));
+                }
+            }
+            XmlEvent::Start {
+                name: "cellXfs",
+                empty,
+                ..
+            } => in_cell_xfs = !empty,
+            XmlEvent::End { name: "cellXfs" } => in_cell_xfs = false,
+            XmlEvent::Start {
+                name: "xf", attrs, ..
+            } if in_cell_xfs => {
+                let id = attr(&attrs, "numFmtId").unwrap_or("0");
+                let builtin_date = id
+                    .parse::<u32>()
+                    .is_ok_and(|id| matches!(id, 14..=22 | 27..=36 | 45..=47 | 50..=58));
+                styles.push(builtin_date || custom_formats.get(id).copied().unwrap_or(false));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(styles)
+}
+
+/// A number format is a date/time format if, outside quoted text, escapes
+/// and `[...]` sections, it uses any of the d/m/y/h/s placeholders.
+fn is_date_format(code: &str) -> bool {
+    let mut chars = code.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                for c in chars.by_ref() {
+                    if c == '"' {
+                        break;
+                    }
+                }
+            }
+            '\\' | '_' | '*' => {
+                chars.next();
+            }
+            '[' => {
+                let section: String = chars.by_ref().take_while(|&c| c != ']').collect();
+                // Elapsed time such as [h]:mm is still a time format.
+                if matches!(
+                    section.to_ascii_lowercase().as_str(),
+                    "h" | "hh" | "m" | "mm" | "s" | "ss"
+                ) {
+                    return true;
+                }
+            }
+            'd' | 'D' | 'm' | 'M' | 'y' | 'Y' | 'h' | 'H' | 's' | 'S' => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+struct CellContext<'a> {
+    shared_strings: &'a [String],
+    date_styles: &'a [bool],
+    date1904: bool,
+}
+
+/// Reads `<sheetData>` into a dense grid of display strings.
+fn parse_cells(xml: &str, context: &CellContext) -> Result<Vec<Vec<String>>> {
+    let mut grid: Vec<Vec<String>> = Vec::new();
+    let mut row_index = 0;
+    let mut column = 0;
+    let mut cell: Option<(String, Option<usize>)> = None;
+    let mut value = String::new();
+    let mut collecting = false;
+    let mut scanner = XmlScanner::new(xml);
+
+    while let Some(event) = scanner.next_event()? {
+        match event {
+            XmlEvent::Start {
+                name: "row", attrs, ..
+            } => {
+                row_index = match attr(&attrs, "r").and_then(|r| r.parse::<usize>().ok()) {
+                    Some(r) if r > 0 => r - 1,
+                    _ => grid.len(),
+                };
+                if row_index >= MAX_ROWS {
+                    bail!(
+                        "row {} is beyond the sheet limit of {} rows",
+                        row_index + 1,
+                        MAX_ROWS
+                    );
+                }
+                column = 0;
+                if grid.len() <= row_index {
+                    grid.resize(row_index + 1, Vec::new());
+                }
+            }
+            XmlEvent::Start {
+                name: "c",
+                attrs,
+                empty,
+            } => {
+                if let Some(reference) = attr(&attrs, "r") {
+                    column = column_index(reference)?.unwrap_or(column);
+                }
+                if column >= MAX_COLUMNS {
+                    bail!(
+                        "column {} is beyond the sheet limit of {} columns",
+                        column + 1,
+                        MAX_COLUMNS
+                    );
+                }
+                let cell_type = attr(&attrs, "t").unwrap_or("n").to_string();
+                let style = attr(&attrs, "s").and_then(|s| s.parse().ok());
+                value.clear();
+                if empty {
+                    column += 1;
+                } else {
+                    cell = Some((cell_type, style));
+                }
+            }
+            XmlEvent::Start {
+                name: "v" | "t",
+                empty: false,
+                ..
+            } if cell.is_some() => {
+                collecting = true;
+            }
+            XmlEvent::End { name: "v" | "t" } => collecting = false,
+            XmlEvent::Text(text) if collecting => value.push_str(&text),
+            XmlEvent::End { name: "c" } => {
+                if let Some((cell_type, style)) = cell.take() {
+                    let text = cell_text(&cell_type, style, &value, context)?;
+                    if grid.len() <= row_index {
+                        grid.resize(row_index + 1, Vec::new());
+                    }
+                    let row = &mut grid[row_index];
+                    if row.len() <= column {
+                        row.resize(column + 1, String::new());
+                    }
+                    row[column] = text;
+                }
+                column += 1;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(grid)
+}
+
+fn cell_text(
+    cell_type: &str,
+    style: Option<usize>,
+    raw: &str,
+    context: &CellContext,
+) -> Result<String> {
+    Ok(match cell_type {
+        "s" => {
+            let index: usize = raw
+                .trim()
+                .parse()
+                .map_err(|_| anyhow!("invalid shared string index {:?}", raw))?;
+            context
+                .shared_strings
+                .get(index)
+                .cloned()
+                .with_context(|| format!("shared string {} out of range", index))?
+        }
+        "b" => if raw.trim() == "1" { "TRUE" } else { "FALSE" }.to_string(),
+        "n" => {
+            let is_date = style
+                .and_then(|s| context.date_styles.get(s))
+                .copied()
+                .unwrap_or(false);
+            match raw.trim().parse::<f64>() {
+                Ok(serial) if is_date => {
+                    format_serial_date(serial, context.date1904).unwrap_or_else(|| raw.to_string())
+                }
+                _ => raw.trim().to_string(),
+            }
+        }
+        // `str` (formula result), `inlineStr`, `e` (error such as #N/A).
+        _ => raw.to_string(),
+    })
+}
+
+/// Converts an Excel serial date to ISO 8601 text: a date, a time of day
+/// (serials below 1), or both.
+fn format_serial_date(serial: f64, date1904: bool) -> Option<String> {
+    if !serial.is_finite() || serial < 0.0 {
+        return None;
+    }
+
+    // 1899-12-30 absorbs Lotus 1-2-3's phantom 1900-02-29 for real dates.
+    let epoch = if date1904 {
+        NaiveDate::from_ymd_opt(1904, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(1899, 12, 30)?
+    };
+    let total_seconds = (serial * 86_400.0).round() as i64;
+    let (days, seconds) = (
+        total_seconds.div_euclid(86_400),
+        total_seconds.rem_euclid(86_400),
+    );
+    let time = format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+
+    if days == 0 && seconds > 0 && !date1904 {
+        return Some(time);
+    }
+    let date = epoch.checked_add_signed(Duration::days(days))?;
+    Some(if seconds == 0 {
+        date.format("%Y-%m-%d").to_string()
+    } else {
+        format!("{} {}", date.format("%Y-%m-%d"), time)
+    })
+}
+
+/// `A` → 0, `Z` → 25, `AA` → 26; the row digits are ignored. `None` when
+/// the reference has no column letters.
+fn column_index(reference: &str) -> Result<Option<usize>> {
+    let letters: String = reference
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect();
+    if letters.is_empty() {
+        return Ok(None);
+    }
+    let number = letters.chars().try_fold(0usize, |acc, c| {
+        acc.checked_mul(26)?
+            .checked_add(c.to_ascii_uppercase() as usize - 'A' as usize + 1)
+            .filter(|&n| n <= MAX_COLUMNS)
+    });
+    match number {
+        Some(number) => Ok(Some(number - 1)),
+        None => bail!(
+            "cell {} is beyond the sheet limit of {} columns",
+            reference,
+            MAX_COLUMNS
+        ),
+    }
+}
+
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// Plain decimal numbers that survive a round trip through an Excel number
+/// cell (no leading zeros, at most 15 significant digits).
+fn looks_numeric(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    let (integer, fraction) = match digits.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (digits, None),
+    };
+
+    let integer_ok = !integer.is_empty()
+        && integer.bytes().all(|b| b.is_ascii_digit())
+        && (integer == "0" || !integer.starts_with('0'));
+    let fraction_ok =
+        fraction.is_none_or(|f| !f.is_empty() && f.bytes().all(|b| b.is_ascii_digit()));
+    let significant = integer.trim_start_matches('0').len() + fraction.map_or(0, str::len);
+
+    integer_ok && fraction_ok && significant <= 15
+}
+
+/// Escapes text for XML, encoding characters XML 1.0 can't carry (and
+/// literal `_xHHHH_` sequences) the way Excel does.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (i, c) in text.char_indices() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '_' if is_ooxml_escape(&text[i..]) => out.push_str("_x005F_"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if (c as u32) < 0x20 => out.push_str(&format!("_x{:04X}_", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn is_ooxml_escape(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    bytes.len() >= 7
+        && bytes[1] == b'x'
+        && bytes[2..6].iter().all(u8::is_ascii_hexdigit)
+        && bytes[6] == b'_'
+}
+
+fn unescape(text: &str) -> String {
+    if !text.contains(['&', '_']) {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find(['&', '_']) {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        if rest.starts_with('_') {
+            if is_ooxml_escape(rest) {
+                let code = u32::from_str_radix(&rest[2..6], 16).unwrap_or(0xFFFD);
+                out.push(char::from_u32(code).unwrap_or('\u{FFFD}'));
+                rest = &rest[7..];
+            } else {
+                out.push('_');
+                rest = &rest[1..];
+            }
+            continue;
+        }
+
+        let Some(end) = rest.find(';') else {
+            out.push_str(rest);
+            return out;
+        };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn attr<'a>(attrs: &'a [(&str, String)], name: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+#[derive(Debug)]
+enum XmlEvent<'a> {
+    /// Element and attribute names have their namespace prefix removed.
+    Start {
+        name: &'a str,
+        attrs: Vec<(&'a str, String)>,
+        empty: bool,
+    },
+    End {
+        name: &'a str,
+    },
+    Text(String),
+}
+
+/// A minimal pull parser, enough for the well-formed XML inside Office
+/// documents. DTDs and processing instructions are skipped.
+struct XmlScanner<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> XmlScanner<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            position: 0,
+        }
+    }
+
+    fn next_event(&mut self) -> Result<Option<XmlEvent<'a>>> {
+        loop {
+            let rest = &self.source[self.position..];
+            if rest.is_empty() {
+                return Ok(None);
+            }
+
+            if !rest.starts_with('<') {
+                let end = rest.find('<').unwrap_or(rest.len());
+                self.position += end;
+                return Ok(Some(XmlEvent::Text(unescape(&rest[..end]))));
+            }
+
+            if let Some(body) = rest.strip_prefix("<![CDATA[") {
+                let end = body.find("]]>").context("unterminated CDATA section")?;
+                self.position += 9 + end + 3;
+                return Ok(Some(XmlEvent::Text(body[..end].to_string())));
+            }
+            if rest.starts_with("<!--") {
+                let end = rest.find("-->").context("unterminated XML comment")?;
+                self.position += end + 3;
+                continue;
+            }
+            if rest.starts_with("<?") || rest.starts_with("<!") {
+                let end = rest.find('>').context("unterminated XML declaration")?;
+                self.position += end + 1;
+                continue;
+            }
+
+            let end = tag_end(rest).context("unterminated XML tag")?;
+            let tag = &rest[1..end];
+            self.position += end + 1;
+
+            if let Some(name) = tag.strip_prefix('/') {
+                return Ok(Some(XmlEvent::End {
+                    name: local_name(name.trim()),
+                }));
+            }
+
+            let (tag, empty) = match tag.strip_suffix('/') {
+                Some(tag) => (tag, true),
+                None => (tag, false),
+            };
+            let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+            let name = local_name(&tag[..name_end]);
+            let attrs = parse_attributes(&tag[name_end..])?;
+
+            return Ok(Some(XmlEvent::Start { name, attrs, empty }));
+        }
+    }
+}
+
+/// Finds the `>` closing a tag, ignoring any inside quoted attribute values.
+fn tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, b) in tag.bytes().enumerate() {
+        match (quote, b) {
+            (None, b'"' | b'\'') => quote = Some(b),
+            (Some(q), _) if q == b => quote = None,
+            (None, b'>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn parse_attributes(mut rest: &str) -> Result<Vec<(&str, String)>> {
+    let mut attrs = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return Ok(attrs);
+        }
+
+        let eq = rest.find('=').context("malformed XML attribute")?;
+        let name = local_name(rest[..eq].trim());
+        rest = rest[eq + 1..].trim_start();
+
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'');
+        let quote = quote.context("unquoted XML attribute value")?;
+        let close = rest[1..]
+            .find(quote)
+            .context("unterminated XML attribute value")?;
+        attrs.push((name, unescape(&rest[1..1 + close])));
+        rest = &rest[close + 2..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    /// A workbook shaped like Excel's own output: shared strings, styles
+    /// with a date format, a title row above the header and a gap column.
+    fn excel_workbook() -> Vec<u8> {
+        let mut zip = ZipWriter::new();
+        zip.add(
+            "xl/workbook.xml",
+            br#"<?xml version="1.0"?><workbook xmlns:r="x"><workbookPr/><sheets><sheet name="Summary" sheetId="1" r:id="rId2"/><sheet name="Data" sheetId="2" r:id="rId1"/></sheets></workbook>"#,
+        )
+        .unwrap();
+        zip.add(
+            "xl/_rels/workbook.xml.rels",
+            br#"<Relationships><Relationship Id="rId1" Target="worksheets/sheet1.xml"/><Relationship Id="rId2" Target="/xl/worksheets/sheet2.xml"/></Relationships>"#,
+        )
+        .unwrap();
+        zip.add(
+            "xl/sharedStrings.xml",
+            br#"<sst><si><t>Name</t></si><si><t>Joined</t></si><si><r><t>Ada </t></r><r><rPr><b/></rPr><t>Lovelace</t></r></si><si><t xml:space="preserve"> Tom &amp; Jerry </t></si><si><t>Report</t></si></sst>"#,
+        )
+        .unwrap();
+        zip.add(
+            "xl/styles.xml",
+            br#"<styleSheet><numFmts count="1"><numFmt numFmtId="164" formatCode="yyyy\-mm\-dd"/></numFmts><cellStyleXfs><xf numFmtId="14"/></cellStyleXfs><cellXfs count="3"><xf numFmtId="0"/><xf numFmtId="164"/><xf numFmtId="2"/></cellXfs></styleSheet>"#,
+        )
+        .unwrap();
+        zip.add(
+            "xl/worksheets/sheet1.xml",
+            br#"<worksheet><sheetData>
+              <row r="1"><c r="A1" t="s"><v>4</v></c></row>
+              <row r="3"><c r="A3" t="s"><v>0</v></c><c r="B3" t="s"><v>1</v></c><c r="D3" t="inlineStr"><is><t>Score</t></is></c></row>
+              <row r="4"><c r="A4" t="s"><v>2</v></c><c r="B4" s="1"><v>45306</v></c><c r="D4" s="2"><v>9.5</v></c></row>
+              <row r="5"/>
+              <row r="6"><c r="A6" t="s"><v>3</v></c><c r="B6" s="1"><v>45306.5</v></c><c r="C6" t="b"><v>1</v></c><c r="D6" t="e"><v>#N/A</v></c></row>
+            </sheetData></worksheet>"#,
+        )
+        .unwrap();
+        zip.add(
+            "xl/worksheets/sheet2.xml",
+            b"<worksheet><sheetData/></worksheet>",
+        )
+        .unwrap();
+        zip.finish()
+    }
+
+    #[test]
+    fn test_read_sheet_with_header_row() {
+        let options = SheetOptions {
+            sheet: Some("data".to_string()),
+            header_row: 3,
+        };
+        let sheet = read_sheet(&excel_workbook(), &options).unwrap();
+
+        assert_eq!(sheet.name, "Data");
+        assert_eq!(
+            sheet.headers,
+            strings(&["Name", "Joined", "column_3", "Score"])
+        );
+        assert_eq!(
+            sheet.rows,
+            vec![
+                strings(&["Ada Lovelace", "2024-01-15", "", "9.5"]),
+                strings(&[" Tom & Jerry ", "2024-01-15 12:00:00", "TRUE", "#N/A"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sheet_selection() {
+        let workbook = excel_workbook();
+        assert_eq!(
+            sheet_names(&workbook).unwrap(),
+            strings(&["Summary", "Data"])
+        );
+
+        let first = read_sheet(&workbook, &SheetOptions::default()).unwrap();
+        assert_eq!(first.name, "Summary");
+        assert!(first.rows.is_empty());
+
+        let by_index = SheetOptions {
+            sheet: Some("2".to_string()),
+            header_row: 1,
+        };
+        assert_eq!(read_sheet(&workbook, &by_index).unwrap().name, "Data");
+
+        let missing = SheetOptions {
+            sheet: Some("Nope".to_string()),
+            header_row: 1,
+        };
+        let error = read_sheet(&workbook, &missing).unwrap_err().to_string();
+        assert!(error.contains("available: Summary, Data"), "{}", error);
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        let headers = strings(&["id", "name", "note"]);
+        let rows = vec![
+            strings(&["1", "Ada", "a < b & \"c\""]),
+            strings(&["007", "", "tab\there _x0041_"]),
+            strings(&["3.25", "Über", ""]),
+        ];
+
+        let bytes = write_workbook("Export", &headers, &rows).unwrap();
+        let sheet = read_sheet(&bytes, &SheetOptions::default()).unwrap();
+
+        assert_eq!(sheet.name, "Export");
+        assert_eq!(sheet.headers, headers);
+        assert_eq!(sheet.rows, rows);
+        assert!(write_workbook("bad/name", &headers, &rows).is_err());
+    }
+
+    #[test]
+    fn test_rejects_cells_beyond_sheet_limits() {
+        let workbook = |cells: &str| {
+            let mut zip = ZipWriter::new();
+            zip.add(
+                "xl/workbook.xml",
+                br#"<workbook><sheets><sheet name="S" r:id="rId1"/></sheets></workbook>"#,
+            )
+            .unwrap();
+            zip.add(
+                "xl/_rels/workbook.xml.rels",
+                br#"<Relationships><Relationship Id="rId1" Target="worksheets/sheet1.xml"/></Relationships>"#,
+            )
+            .unwrap();
+            let sheet = format!("<worksheet><sheetData>{}</sheetData></worksheet>", cells);
+            zip.add("xl/worksheets/sheet1.xml", sheet.as_bytes())
+                .unwrap();
+            zip.finish()
+        };
+        let read = |cells: &str| read_sheet(&workbook(cells), &SheetOptions::default());
+
+        assert!(read(
+            r#"<row r="1048576"><c r="XFD1048576" t="inlineStr"><is><t>x</t></is></c></row>"#
+        )
+        .is_ok());
+        for cells in [
+            r#"<row r="1"><c r="AAAAAAAAAAAAAAAAAAAA1"><v>1</v></c></row>"#,
+            r#"<row r="1"><c r="XFE1"><v>1</v></c></row>"#,
+            r#"<row r="4000000000"><c><v>1</v></c></row>"#,
+        ] {
+            let error = read(cells).unwrap_err().to_string();
+            assert!(error.contains("beyond the sheet limit"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_helpers() {
+        assert_eq!(column_index("A1").unwrap(), Some(0));
+        assert_eq!(column_index("AB12").unwrap(), Some(27));
+        assert_eq!(column_index("XFD1").unwrap(), Some(MAX_COLUMNS - 1));
+        assert_eq!(column_index("12").unwrap(), None);
+        assert!(column_index("XFE1").is_err());
+        assert!(column_index("AAAAAAAAAAAAAAAAAAAA1").is_err());
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(27), "AB");
+        assert_eq!(column_name(701), "ZZ");
+        assert_eq!(column_name(702), "AAA");
+
+        assert!(looks_numeric("42") && looks_numeric("-0.5") && looks_numeric("0"));
+        assert!(!looks_numeric("007") && !looks_numeric("1e5") && !looks_numeric("1."));
+        assert!(!looks_numeric("1234567890123456"));
+
+        assert!(is_date_format("dd/mm/yyyy") && is_date_format("[h]:mm:ss"));
+        assert!(
+            !is_date_format("0.00") && !is_date_format("\"day\" 0") && !is_date_format("[Red]0")
+        );
+
+        assert_eq!(
+            format_serial_date(1.0, false).as_deref(),
+            Some("1899-12-31")
+        );
+        assert_eq!(format_serial_date(0.75, false).as_deref(), Some("18:00:00"));
+        assert_eq!(format_serial_date(0.0, true).as_deref(), Some("1904-01-01"));
+    }
+}
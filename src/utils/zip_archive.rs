@@ -0,0 +1,297 @@
+use anyhow::{bail, Context, Result};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+/// The largest entry `read` will inflate. Entry sizes in the directory are
+/// only claims, so inflation is capped rather than trusted.
+const MAX_ENTRY_SIZE: usize = 512 * 1024 * 1024;
+/// Up-front allocation for an inflated entry, whatever its claimed size.
+const MAX_PREALLOCATION: usize = 16 * 1024 * 1024;
+
+/// 1980-01-01 00:00 in MS-DOS format, so written archives are reproducible.
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+/// Reads entries from an in-memory zip archive. Only what Office documents
+/// need is supported: stored and deflated entries, no encryption, no Zip64.
+pub struct ZipReader<'a> {
+    data: &'a [u8],
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    name: String,
+    method: u16,
+    compressed_size: usize,
+    size: usize,
+    local_offset: usize,
+}
+
+#[allow(dead_code)]
+impl<'a> ZipReader<'a> {
+    pub fn new(data: &'a [u8]) -> Result<Self> {
+        // The end-of-central-directory record sits in the last 64 KiB + 22
+        // bytes (it may be followed by a comment).
+        let search_start = data.len().saturating_sub(0xFFFF + 22);
+        let end = (search_start..data.len().saturating_sub(21))
+            .rev()
+            .find(|&i| read_u32(data, i) == Some(END_OF_CENTRAL_DIRECTORY))
+            .context("not a zip archive (no end of central directory)")?;
+
+        let count = read_u16(data, end + 10).context("truncated zip directory")? as usize;
+        let mut offset = read_u32(data, end + 16).context("truncated zip directory")? as usize;
+        if count == 0xFFFF || offset == 0xFFFF_FFFF {
+            bail!("Zip64 archives are not supported");
+        }
+
+        let mut entries = Vec::with_capacity(count);
+        for _ in 0..count {
+            if read_u32(data, offset) != Some(CENTRAL_HEADER) {
+                bail!("corrupt zip central directory");
+            }
+            let field = |at: usize| read_u16(data, offset + at).context("truncated zip entry");
+            let wide = |at: usize| read_u32(data, offset + at).context("truncated zip entry");
+
+            let flags = field(8)?;
+            let method = field(10)?;
+            let compressed_size = wide(20)? as usize;
+            let size = wide(24)? as usize;
+            let name_len = field(28)? as usize;
+            let extra_len = field(30)? as usize;
+            let comment_len = field(32)? as usize;
+            let local_offset = wide(42)? as usize;
+
+            let name_bytes = data
+                .get(offset + 46..offset + 46 + name_len)
+                .context("truncated zip entry name")?;
+            if flags & 1 != 0 {
+                bail!("encrypted zip entries are not supported");
+            }
+
+            entries.push(Entry {
+                name: String::from_utf8_lossy(name_bytes).into_owned(),
+                method,
+                compressed_size,
+                size,
+                local_offset,
+            });
+            offset += 46 + name_len + extra_len + comment_len;
+        }
+
+        Ok(Self { data, entries })
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|e| e.name.as_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.iter().any(|e| e.name == name)
+    }
+
+    /// Returns the uncompressed contents of `name`, or `None` if the archive
+    /// has no such entry.
+    pub fn read(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        self.read_limited(name, MAX_ENTRY_SIZE)
+    }
+
+    fn read_limited(&self, name: &str, limit: usize) -> Result<Option<Vec<u8>>> {
+        let Some(entry) = self.entries.iter().find(|e| e.name == name) else {
+            return Ok(None);
+        };
+        if entry.size > limit {
+            bail!("zip entry {} is too large ({} bytes)", name, entry.size);
+        }
+
+        let header = entry.local_offset;
+        if read_u32(self.data, header) != Some(LOCAL_HEADER) {
+            bail!("corrupt zip entry {}", name);
+        }
+        let name_len = read_u16(self.data, header + 26).context("truncated zip entry")? as usize;
+        let extra_len = read_u16(self.data, header + 28).context("truncated zip entry")? as usize;
+        let start = header + 30 + name_len + extra_len;
+        let raw = self
+            .data
+            .get(start..start + entry.compressed_size)
+            .with_context(|| format!("truncated zip entry {}", name))?;
+
+        let contents = match entry.method {
+            STORED => raw.to_vec(),
+            DEFLATED => {
+                let mut out = Vec::with_capacity(entry.size.min(MAX_PREALLOCATION));
+                DeflateDecoder::new(raw)
+                    .take(limit as u64 + 1)
+                    .read_to_end(&mut out)
+                    .with_context(|| format!("corrupt compressed data in {}", name))?;
+                if out.len() > limit {
+                    bail!("zip entry {} inflates to more than {} bytes", name, limit);
+                }
+                out
+            }
+            method => bail!("unsupported compression method {} in {}", method, name),
+        };
+
+        Ok(Some(contents))
+    }
+
+    pub fn read_string(&self, name: &str) -> Result<Option<String>> {
+        Ok(self
+            .read(name)?
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned()))
+    }
+}
+
+/// Builds a zip archive in memory with deflated entries.
+pub struct ZipWriter {
+    out: Vec<u8>,
+    central: Vec<u8>,
+    count: u16,
+}
+
+impl ZipWriter {
+    pub fn new() -> Self {
+        Self {
+            out: Vec::new(),
+            central: Vec::new(),
+            count: 0,
+        }
+    }
+
+    pub fn add(&mut self, name: &str, contents: &[u8]) -> Result<()> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(contents)?;
+        let compressed = encoder.finish()?;
+        let crc = crc32fast::hash(contents);
+        let offset = self.out.len() as u32;
+
+        // Local file header.
+        self.out.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+        self.out.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        self.out.extend_from_slice(&0x0800u16.to_le_bytes()); // UTF-8 names
+        self.out.extend_from_slice(&DEFLATED.to_le_bytes());
+        self.out.extend_from_slice(&0u16.to_le_bytes()); // time
+        self.out.extend_from_slice(&DOS_DATE.to_le_bytes());
+        self.out.extend_from_slice(&crc.to_le_bytes());
+        self.out
+            .extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        self.out
+            .extend_from_slice(&(contents.len() as u32).to_le_bytes());
+        self.out
+            .extend_from_slice(&(name.len() as u16).to_le_bytes());
+        self.out.extend_from_slice(&0u16.to_le_bytes()); // extra length
+        self.out.extend_from_slice(name.as_bytes());
+        self.out.extend_from_slice(&compressed);
+
+        // Matching central directory record.
+        self.central
+            .extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+        self.central.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        self.central.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        self.central.extend_from_slice(&0x0800u16.to_le_bytes());
+        self.central.extend_from_slice(&DEFLATED.to_le_bytes());
+        self.central.extend_from_slice(&0u16.to_le_bytes());
+        self.central.extend_from_slice(&DOS_DATE.to_le_bytes());
+        self.central.extend_from_slice(&crc.to_le_bytes());
+        self.central
+            .extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        self.central
+            .extend_from_slice(&(contents.len() as u32).to_le_bytes());
+        self.central
+            .extend_from_slice(&(name.len() as u16).to_le_bytes());
+        self.central.extend_from_slice(&[0; 12]); // extra, comment, disk, attributes
+        self.central.extend_from_slice(&offset.to_le_bytes());
+        self.central.extend_from_slice(name.as_bytes());
+
+        self.count += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        let directory_offset = self.out.len() as u32;
+        let directory_size = self.central.len() as u32;
+        self.out.extend_from_slice(&self.central);
+
+        self.out
+            .extend_from_slice(&END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        self.out.extend_from_slice(&[0; 4]); // disk numbers
+        self.out.extend_from_slice(&self.count.to_le_bytes());
+        self.out.extend_from_slice(&self.count.to_le_bytes());
+        self.out.extend_from_slice(&directory_size.to_le_bytes());
+        self.out.extend_from_slice(&directory_offset.to_le_bytes());
+        self.out.extend_from_slice(&0u16.to_le_bytes()); // comment length
+
+        self.out
+    }
+}
+
+fn read_u16(data: &[u8], at: usize) -> Option<u16> {
+    data.get(at..at + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    data.get(at..at + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut writer = ZipWriter::new();
+        writer.add("a.txt", b"hello hello hello").unwrap();
+        writer.add("dir/b.xml", b"<x/>").unwrap();
+        let bytes = writer.finish();
+
+        let reader = ZipReader::new(&bytes).unwrap();
+        assert_eq!(
+            reader.names().collect::<Vec<_>>(),
+            vec!["a.txt", "dir/b.xml"]
+        );
+        assert_eq!(reader.read("a.txt").unwrap().unwrap(), b"hello hello hello");
+        assert_eq!(reader.read_string("dir/b.xml").unwrap().unwrap(), "<x/>");
+        assert!(reader.read("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_entry_size_limits() {
+        let mut writer = ZipWriter::new();
+        writer.add("zeros.bin", &[0u8; 1000]).unwrap();
+        let mut archive = writer.finish();
+
+        let reader = ZipReader::new(&archive).unwrap();
+        assert!(reader.read_limited("zeros.bin", 1000).unwrap().is_some());
+        assert!(reader.read_limited("zeros.bin", 999).is_err());
+
+        // Understate the size in the central directory: inflation is still
+        // cut off at the limit.
+        let central = archive
+            .windows(4)
+            .position(|w| w == CENTRAL_HEADER.to_le_bytes())
+            .unwrap();
+        archive[central + 24..central + 28].copy_from_slice(&10u32.to_le_bytes());
+        let reader = ZipReader::new(&archive).unwrap();
+        let error = reader.read_limited("zeros.bin", 100).unwrap_err();
+        assert!(
+            error.to_string().contains("inflates to more than"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_rejects_non_zip() {
+        assert!(ZipReader::new(b"not a zip file at all, just text").is_err());
+    }
+}